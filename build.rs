@@ -0,0 +1,22 @@
+//! Generates the gRPC server and client code from `rustquote_service/proto`.
+
+use std::env;
+use std::path::PathBuf;
+
+const PROTO_DIR: &str = "rustquote_service/proto";
+const PROTO_FILE: &str = "rustquote_service/proto/quotes.proto";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Prefer a protoc from the environment; otherwise use the vendored binary
+    // so building does not require a system install.
+    if env::var_os("PROTOC").is_none() {
+        env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("rustquote_descriptor.bin"))
+        .compile_protos(&[PROTO_FILE], &[PROTO_DIR])?;
+    println!("cargo:rerun-if-changed={}", PROTO_DIR);
+    Ok(())
+}
@@ -0,0 +1,256 @@
+// RustQuote API documentation viewer.
+//
+// Renders the OpenAPI document named by the `data-spec-url` attribute of the
+// `#docs` element: operations grouped by tag, then the component schemas.
+// It is embedded in the page served at /api/docs, so viewing the docs fetches
+// nothing but the document itself.
+(function () {
+  "use strict";
+
+  var METHODS = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+  function el(tag, className, text) {
+    var node = document.createElement(tag);
+    if (className) {
+      node.className = className;
+    }
+    if (text !== undefined && text !== null) {
+      node.textContent = String(text);
+    }
+    return node;
+  }
+
+  function refName(ref) {
+    return ref.slice(ref.lastIndexOf("/") + 1);
+  }
+
+  // A short, human-readable description of a schema's type.
+  function typeOf(schema) {
+    if (!schema) {
+      return "";
+    }
+    if (schema.$ref) {
+      return refName(schema.$ref);
+    }
+    var variants = schema.oneOf || schema.anyOf || schema.allOf;
+    if (variants) {
+      return variants.map(typeOf).join(schema.allOf ? " & " : " | ");
+    }
+    var type = Array.isArray(schema.type) ? schema.type.join(" | ") : schema.type || "object";
+    if (type.indexOf("array") !== -1 && schema.items) {
+      type = type.replace("array", typeOf(schema.items) + "[]");
+    }
+    if (schema.enum) {
+      type += " (" + schema.enum.map(function (v) { return JSON.stringify(v); }).join(", ") + ")";
+    }
+    if (schema.format) {
+      type += " <" + schema.format + ">";
+    }
+    return type;
+  }
+
+  function anchor(name) {
+    return "schema-" + name.replace(/[^A-Za-z0-9_-]/g, "-");
+  }
+
+  // Links schema names in a type description to their definitions.
+  function typeNode(schema, schemas) {
+    var node = el("code", "type");
+    var text = typeOf(schema);
+    var names = Object.keys(schemas).sort(function (a, b) { return b.length - a.length; });
+    var position = 0;
+    while (position < text.length) {
+      var match = null;
+      for (var i = 0; i < names.length; i++) {
+        if (text.substr(position, names[i].length) === names[i]) {
+          match = names[i];
+          break;
+        }
+      }
+      if (match) {
+        var link = el("a", null, match);
+        link.href = "#" + anchor(match);
+        node.appendChild(link);
+        position += match.length;
+      } else {
+        node.appendChild(document.createTextNode(text.charAt(position)));
+        position += 1;
+      }
+    }
+    return node;
+  }
+
+  function table(headings, rows) {
+    var node = el("table");
+    var head = el("tr");
+    headings.forEach(function (heading) {
+      head.appendChild(el("th", null, heading));
+    });
+    node.appendChild(head);
+    rows.forEach(function (cells) {
+      var row = el("tr");
+      cells.forEach(function (cell) {
+        var td = el("td");
+        if (cell instanceof Node) {
+          td.appendChild(cell);
+        } else {
+          td.textContent = cell === undefined ? "" : String(cell);
+        }
+        row.appendChild(td);
+      });
+      node.appendChild(row);
+    });
+    return node;
+  }
+
+  function renderOperation(path, method, operation, schemas) {
+    var section = el("details", "operation" + (operation.deprecated ? " deprecated" : ""));
+    var summary = el("summary");
+    summary.appendChild(el("span", "method " + method, method.toUpperCase()));
+    summary.appendChild(el("code", "path", path));
+    summary.appendChild(el("span", "summary", operation.summary || ""));
+    if (operation.deprecated) {
+      summary.appendChild(el("span", "badge", "deprecated"));
+    }
+    section.appendChild(summary);
+
+    if (operation.description) {
+      section.appendChild(el("p", "description", operation.description));
+    }
+    if (operation.parameters && operation.parameters.length) {
+      section.appendChild(el("h4", null, "Parameters"));
+      section.appendChild(table(
+        ["Name", "In", "Type", "Required", "Description"],
+        operation.parameters.map(function (p) {
+          return [p.name, p.in, typeNode(p.schema, schemas), p.required ? "yes" : "no", p.description];
+        })
+      ));
+    }
+    if (operation.requestBody && operation.requestBody.content) {
+      section.appendChild(el("h4", null, "Request body"));
+      var content = operation.requestBody.content;
+      section.appendChild(table(
+        ["Content type", "Schema"],
+        Object.keys(content).map(function (type) {
+          return [type, typeNode(content[type].schema, schemas)];
+        })
+      ));
+    }
+    var responses = operation.responses || {};
+    section.appendChild(el("h4", null, "Responses"));
+    section.appendChild(table(
+      ["Status", "Description", "Content"],
+      Object.keys(responses).map(function (status) {
+        var response = responses[status];
+        var content = response.content || {};
+        var cell = el("span");
+        Object.keys(content).forEach(function (type, i) {
+          if (i > 0) {
+            cell.appendChild(document.createTextNode(", "));
+          }
+          cell.appendChild(document.createTextNode(type + " "));
+          cell.appendChild(typeNode(content[type].schema, schemas));
+        });
+        return [status, response.description, cell];
+      })
+    ));
+    return section;
+  }
+
+  function renderSchema(name, schema, schemas) {
+    var section = el("section", "schema");
+    section.id = anchor(name);
+    section.appendChild(el("h3", null, name));
+    if (schema.description) {
+      section.appendChild(el("p", "description", schema.description));
+    }
+    var properties = schema.properties || {};
+    var required = schema.required || [];
+    var names = Object.keys(properties);
+    if (names.length) {
+      section.appendChild(table(
+        ["Field", "Type", "Required", "Description"],
+        names.map(function (field) {
+          var property = properties[field];
+          return [field, typeNode(property, schemas), required.indexOf(field) !== -1 ? "yes" : "no", property.description];
+        })
+      ));
+    } else {
+      var type = el("p");
+      type.appendChild(typeNode(schema, schemas));
+      section.appendChild(type);
+    }
+    return section;
+  }
+
+  function render(root, spec) {
+    var info = spec.info || {};
+    var schemas = (spec.components && spec.components.schemas) || {};
+    root.textContent = "";
+    root.appendChild(el("h1", null, (info.title || "API") + (info.version ? " " + info.version : "")));
+    if (info.description) {
+      root.appendChild(el("p", "description", info.description));
+    }
+
+    var tags = (spec.tags || []).map(function (tag) { return tag.name; });
+    var byTag = {};
+    var paths = spec.paths || {};
+    Object.keys(paths).forEach(function (path) {
+      METHODS.forEach(function (method) {
+        var operation = paths[path][method];
+        if (!operation) {
+          return;
+        }
+        var tag = (operation.tags && operation.tags[0]) || "Other";
+        if (tags.indexOf(tag) === -1) {
+          tags.push(tag);
+        }
+        (byTag[tag] = byTag[tag] || []).push(renderOperation(path, method, operation, schemas));
+      });
+    });
+    var descriptions = {};
+    (spec.tags || []).forEach(function (tag) {
+      descriptions[tag.name] = tag.description;
+    });
+    tags.forEach(function (tag) {
+      if (!byTag[tag]) {
+        return;
+      }
+      var section = el("section", "tag");
+      section.appendChild(el("h2", null, tag));
+      if (descriptions[tag]) {
+        section.appendChild(el("p", "description", descriptions[tag]));
+      }
+      byTag[tag].forEach(function (operation) {
+        section.appendChild(operation);
+      });
+      root.appendChild(section);
+    });
+
+    var names = Object.keys(schemas).sort();
+    if (names.length) {
+      var section = el("section", "schemas");
+      section.appendChild(el("h2", null, "Schemas"));
+      names.forEach(function (name) {
+        section.appendChild(renderSchema(name, schemas[name], schemas));
+      });
+      root.appendChild(section);
+    }
+  }
+
+  var root = document.getElementById("docs");
+  fetch(root.getAttribute("data-spec-url"))
+    .then(function (response) {
+      if (!response.ok) {
+        throw new Error("HTTP " + response.status);
+      }
+      return response.json();
+    })
+    .then(function (spec) {
+      render(root, spec);
+    })
+    .catch(function (error) {
+      root.textContent = "";
+      root.appendChild(el("p", "error", "Failed to load the OpenAPI document: " + error.message));
+    });
+})();
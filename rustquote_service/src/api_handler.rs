@@ -3,18 +3,121 @@
 //! This module contains the Axum handlers for the API endpoints.
 //! It defines the logic for responding to HTTP requests for health checks and quote retrieval.
 
-use crate::services::quote_service;
-use crate::AppState;
-use axum::{extract::State, http::StatusCode, Json}; // Added State // Import AppState
-                                                    // Quote model is not directly used here anymore for response construction, but might be for logic
-                                                    // use crate::models::Quote;
-use crate::errors::AppError;
-use crate::responses::{HealthStatus, QuoteResponse}; // ErrorResponse is now handled by AppError // Import the custom error type
+use crate::caching::CacheValidators;
+use crate::card;
+use crate::feeds::{self, atom, ical, rss};
+use crate::fieldsets::{FieldSet, FieldsQueryParams};
+use crate::filter_query::{Filter, FilterQueryParams};
+use crate::models::{Collection, Quote};
+use crate::negotiation::ResponseFormatParams;
+use crate::services::corpus_stats::CorpusStats;
+use crate::services::facet_index::{FacetQuery, Facets, DEFAULT_FACET_LIMIT, MAX_FACET_LIMIT};
+use crate::services::near_duplicates::DuplicateReport;
+use crate::services::quote_store::Corpus;
+use crate::services::similarity::{DEFAULT_SIMILAR_LIMIT, MAX_SIMILAR_LIMIT};
+use crate::services::suggest_index::SuggestKind;
+use crate::services::{collection_service, quote_service};
+use crate::AppState; // Import AppState
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+// Quote model is not directly used here anymore for response construction, but might be for logic
+// use crate::models::Quote;
+use crate::errors::AppError; // Import the custom error type
+use crate::responses::{
+    BatchQuoteItem, BatchQuotesResponse, CollectionResponse, CollectionSummary, ErrorResponse,
+    HealthStatus, QuoteCountResponse, QuoteResource, QuoteResponse, QuoteRevisionsResponse,
+    SimilarQuote, SimilarQuotesResponse, SuggestResponse,
+};
+
+/// Header carrying the admin token for admin-only request parameters.
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// Query parameters accepted by the quote endpoints.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuoteQueryParams {
+    /// Admin-only: an RFC 3339 instant at which to evaluate publishing windows,
+    /// to preview what the corpus will look like then.
+    pub preview_at: Option<String>,
+}
+
+/// Determines the instant at which publishing windows are evaluated.
+///
+/// This is the current time unless an authorised admin asked for a preview.
+fn effective_instant(
+    app_state: &AppState,
+    headers: &HeaderMap,
+    params: &QuoteQueryParams,
+) -> Result<DateTime<Utc>, AppError> {
+    let Some(preview_at) = params.preview_at.as_deref() else {
+        return Ok(Utc::now());
+    };
+
+    if !is_admin(app_state, headers) {
+        tracing::warn!("Rejected preview_at request without a valid admin token.");
+        return Err(AppError::Forbidden(
+            "The preview_at parameter requires a valid admin token.".to_string(),
+        ));
+    }
+
+    DateTime::parse_from_rfc3339(preview_at)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| {
+            AppError::BadRequest(format!(
+                "Invalid preview_at '{}': expected an RFC 3339 timestamp ({}).",
+                preview_at, e
+            ))
+        })
+}
+
+/// Returns `true` if the request carries the configured admin token. Always
+/// `false` when no admin token is configured.
+fn is_admin(app_state: &AppState, headers: &HeaderMap) -> bool {
+    let provided = headers
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    match (app_state.admin_token.as_deref(), provided) {
+        (Some(expected), Some(provided)) => constant_time_eq(expected, provided),
+        _ => false,
+    }
+}
+
+/// Compares two strings without short-circuiting on the first difference.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
 
 /// Handles requests to the `/api/health` endpoint.
 ///
 /// Returns a JSON response indicating the service is healthy.
 /// This endpoint can be used for liveness/readiness probes.
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "Status",
+    operation_id = "healthCheck",
+    summary = "Health check",
+    description = "Reports that the service is running.",
+    responses(
+        (status = 200, description = "The service is healthy", body = HealthStatus),
+    )
+)]
 pub async fn health_check_handler() -> (StatusCode, Json<HealthStatus>) {
     // No input parameters or body to validate for this endpoint.
     // If there were, validation logic would go here.
@@ -26,48 +129,82 @@ pub async fn health_check_handler() -> (StatusCode, Json<HealthStatus>) {
 
 /// Handles requests to the `/api/v1/quote` endpoint.
 ///
-/// Attempts to load quotes from the configured data file, selects a random quote,
-/// and returns it in a JSON response.
+/// Fetches the current corpus from the quote store (reloading the data file if it
+/// changed), selects a random quote, and returns it in a JSON response.
+/// Quotes outside their publishing window are never picked; admins can pass
+/// `preview_at` to evaluate the windows at another instant. `filter` restricts
+/// the pick to matching quotes, and `fields` selects which quote fields are
+/// returned.
 ///
 /// # Errors
 ///
-/// Returns an [`AppError::NotFound`] if no quotes are available.
+/// Returns an [`AppError::NotFound`] if no active quotes are available, or none match `filter`.
+/// Returns an [`AppError::BadRequest`] if `filter` is malformed or `fields` names an unknown field.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
 /// Returns an [`AppError::InternalServerError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote",
+    tag = "Quotes",
+    operation_id = "getRandomQuote",
+    summary = "Get a random quote",
+    description = "Picks a random active, published quote, among those matching `filter` if given. \
+                   With `fields`, the body has exactly the selected quote fields instead. Deprecated \
+                   in favour of `/api/v2/quote`.",
+    params(QuoteQueryParams, FilterQueryParams, FieldsQueryParams, ResponseFormatParams),
+    responses(
+        (status = 200, description = "A random quote", body = QuoteResponse),
+        (status = 404, description = "No active quotes are available or match the filter", body = ErrorResponse),
+        (status = 400, description = "Malformed `preview_at` or `filter`, or unknown field in `fields`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 406, description = "Unsupported response format", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
 pub async fn get_quote_handler(
     State(app_state): State<AppState>, // Extract AppState
-) -> Result<Json<QuoteResponse>, AppError> {
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+    Query(filter): Query<FilterQueryParams>,
+    Query(fields): Query<FieldsQueryParams>,
+) -> Result<Response, AppError> {
     tracing::debug!(
         "Received request for /api/v1/quote. Using quotes_file_path: {}",
         app_state.quotes_file_path.display()
     );
-    match quote_service::load_quotes_from_file(&app_state.quotes_file_path) {
-        Ok(quotes) => {
+    let filter = Filter::from_params(&filter)?;
+    let fields = FieldSet::from_params(&fields)?;
+    let at = effective_instant(&app_state, &headers, &params)?;
+    match app_state.quote_store.current().await {
+        Ok(corpus) => {
+            let quotes = &corpus.quotes;
             if quotes.is_empty() {
                 // Explicitly check for empty quotes vector
                 Err(AppError::NotFound(
                     "No quotes available in the data file.".to_string(),
                 ))
-            } else if let Some(random_quote) = quote_service::get_random_quote(&quotes) {
-                let response = QuoteResponse {
-                    id: random_quote.id,
-                    quote: random_quote.text.clone(),
-                    author: random_quote.author.clone(),
-                };
+            } else if let Some(random_quote) =
+                quote_service::get_random_quote_where(quotes, at, |q| {
+                    filter.as_ref().is_none_or(|f| f.matches(q))
+                })
+            {
                 tracing::info!("Successfully retrieved and returned a random quote.");
-                Ok(Json(response))
+                Ok(v1_quote_body(random_quote, fields.as_ref()))
+            } else if filter.is_some() {
+                Err(AppError::NotFound(
+                    "No active quotes match the filter.".to_string(),
+                ))
             } else {
-                // This branch should ideally not be hit if quotes.is_empty() is checked above
-                // but kept for safety, or if get_random_quote could fail for other reasons on non-empty.
+                // Every quote in the file is archived, deleted or outside its publishing window.
                 Err(AppError::NotFound(
-                    // Should be unreachable if quotes.is_empty() is handled
-                    "Could not select a random quote (unexpected).".to_string(),
+                    "No active quotes available in the data file.".to_string(),
                 ))
             }
         }
         Err(service_error) => {
             // Log the specific service_error for better diagnostics
             tracing::error!(
-                "Quote service error during load_quotes_from_file: {:?}",
+                "Quote service error while loading the corpus: {:?}",
                 service_error
             ); // Changed to debug print
             tracing::error!("Failed to load quotes (display): {}", service_error);
@@ -78,55 +215,64 @@ pub async fn get_quote_handler(
 }
 /// Handles requests to the `/api/v1/quote/:id` endpoint.
 ///
-/// Attempts to load quotes from the configured data file, finds the quote by ID,
-/// and returns it in a JSON response.
+/// Fetches the current corpus from the quote store, finds the quote by ID,
+/// and returns it in a JSON response. The `:id` segment may be the numeric id,
+/// the string uid or the slug of the quote. Archived quotes are still served;
+/// quotes outside their publishing window are not, unless an admin previews
+/// them with `preview_at`. `fields` selects which quote fields are returned.
 ///
 /// # Errors
 ///
-/// Returns an [`AppError::NotFound`] if the quote with the specified ID is not found or no quotes are available.
+/// Returns an [`AppError::Gone`] if the quote is marked as deleted or was removed from the data file.
+/// Returns an [`AppError::NotFound`] if the quote with the specified ID is not found, is outside its publishing window, or no quotes are available.
+/// Returns an [`AppError::BadRequest`] if `fields` names an unknown field.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
 /// Returns an [`AppError::InternalServerError`] or [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/{id}",
+    tag = "Quotes",
+    operation_id = "getQuote",
+    summary = "Get a quote",
+    description = "Looks a quote up by numeric id, uid or slug. With `fields`, the body has exactly the \
+                   selected quote fields instead. Deprecated in favour of `/api/v2/quote/{id}`.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote"), QuoteQueryParams, FieldsQueryParams, ResponseFormatParams),
+    responses(
+        (status = 200, description = "The quote", body = QuoteResponse),
+        (status = 404, description = "No such quote, or it is outside its publishing window", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+        (status = 400, description = "Malformed `preview_at` or unknown field in `fields`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 406, description = "Unsupported response format", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
 pub async fn get_quote_by_id_handler(
     State(app_state): State<AppState>,
-    axum::extract::Path(id): axum::extract::Path<u32>,
-) -> Result<Json<QuoteResponse>, AppError> {
+    axum::extract::Path(id): axum::extract::Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+    Query(fields): Query<FieldsQueryParams>,
+) -> Result<Response, AppError> {
     tracing::debug!(
         "Received request for /api/v1/quote/{}. Using quotes_file_path: {}",
         id,
         app_state.quotes_file_path.display()
     );
+    let fields = FieldSet::from_params(&fields)?;
+    let at = effective_instant(&app_state, &headers, &params)?;
 
-    match quote_service::load_quotes_from_file(&app_state.quotes_file_path) {
-        Ok(quotes) => {
-            if quotes.is_empty() {
-                tracing::warn!(
-                    "No quotes available in the data file when searching for ID: {}",
-                    id
-                );
-                return Err(AppError::NotFound(format!(
-                    "No quotes available in the data file. Cannot find quote with ID: {}.",
-                    id
-                )));
-            }
-
-            if let Some(quote) = quote_service::get_quote_by_id(&quotes, id) {
-                let response = QuoteResponse {
-                    id: quote.id,
-                    quote: quote.text.clone(),
-                    author: quote.author.clone(),
-                };
-                tracing::info!("Successfully retrieved and returned quote with ID: {}", id);
-                Ok(Json(response))
-            } else {
-                tracing::info!("Quote with ID: {} not found.", id);
-                Err(AppError::NotFound(format!(
-                    "Quote with ID: {} not found.",
-                    id
-                )))
-            }
+    match app_state.quote_store.current().await {
+        Ok(corpus) => {
+            let quote = find_servable_quote(&corpus, &id, at)?;
+            let mut response = v1_quote_body(quote, fields.as_ref());
+            apply_by_id_caching(&mut response, quote, corpus.version, &params);
+            tracing::info!("Successfully retrieved and returned quote with ID: {}", id);
+            Ok(response)
         }
         Err(service_error) => {
             tracing::error!(
-                "Quote service error during load_quotes_from_file for ID {}: {:?}",
+                "Quote service error while loading the corpus for ID {}: {:?}",
                 id,
                 service_error
             );
@@ -135,3 +281,1470 @@ pub async fn get_quote_by_id_handler(
         }
     }
 }
+
+/// The v1 body for `quote`: the selected fields, or [`QuoteResponse`] when
+/// no `fields` were requested.
+fn v1_quote_body(quote: &Quote, fields: Option<&FieldSet>) -> Response {
+    match fields {
+        Some(fields) => Json(fields.project(quote)).into_response(),
+        None => Json(QuoteResponse::from(quote)).into_response(),
+    }
+}
+
+/// Adds the caching headers for a quote served by id.
+fn apply_by_id_caching(
+    response: &mut Response,
+    quote: &Quote,
+    corpus_version: u64,
+    params: &QuoteQueryParams,
+) {
+    if params.preview_at.is_some() {
+        // Admin previews must never end up in shared caches.
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-store"),
+        );
+    } else {
+        CacheValidators::for_quote(quote, corpus_version, Utc::now()).apply(response.headers_mut());
+    }
+}
+
+/// Handles requests to the `/api/v2/quote` endpoint.
+///
+/// Picks a random quote with the same rules as [`get_quote_handler`] and
+/// returns the full [`QuoteResource`].
+///
+/// # Errors
+///
+/// Returns an [`AppError::NotFound`] if no active quotes are available.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v2/quote",
+    tag = "Quotes",
+    operation_id = "getRandomQuoteV2",
+    summary = "Get a random quote resource",
+    description = "Picks a random active, published quote and returns the full resource.",
+    params(QuoteQueryParams, ResponseFormatParams),
+    responses(
+        (status = 200, description = "A random quote", body = QuoteResource),
+        (status = 404, description = "No active quotes are available", body = ErrorResponse),
+        (status = 400, description = "Malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 406, description = "Unsupported response format", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_v2_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+) -> Result<Json<QuoteResource>, AppError> {
+    tracing::debug!("Received request for /api/v2/quote.");
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let corpus = app_state.quote_store.current().await?;
+    if corpus.quotes.is_empty() {
+        return Err(AppError::NotFound(
+            "No quotes available in the data file.".to_string(),
+        ));
+    }
+    let quote = quote_service::get_random_quote_at(&corpus.quotes, at).ok_or_else(|| {
+        AppError::NotFound("No active quotes available in the data file.".to_string())
+    })?;
+    let base_url = public_base_url(&app_state, &headers);
+    Ok(Json(QuoteResource::new(quote, corpus.version, &base_url)))
+}
+
+/// Handles requests to the `/api/v2/quote/:id` endpoint.
+///
+/// Looks the quote up with the same rules and caching headers as
+/// [`get_quote_by_id_handler`] and returns the full [`QuoteResource`].
+///
+/// # Errors
+///
+/// Returns an [`AppError::Gone`] if the quote is marked as deleted or was removed from the data file.
+/// Returns an [`AppError::NotFound`] if the quote is not found, is outside its publishing window, or no quotes are available.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v2/quote/{id}",
+    tag = "Quotes",
+    operation_id = "getQuoteV2",
+    summary = "Get a quote resource",
+    description = "Looks a quote up by numeric id, uid or slug and returns the full resource.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote"), QuoteQueryParams, ResponseFormatParams),
+    responses(
+        (status = 200, description = "The quote", body = QuoteResource),
+        (status = 404, description = "No such quote, or it is outside its publishing window", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+        (status = 400, description = "Malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 406, description = "Unsupported response format", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_by_id_v2_handler(
+    State(app_state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /api/v2/quote/{}.", id);
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let corpus = app_state.quote_store.current().await?;
+    let quote = find_servable_quote(&corpus, &id, at)?;
+    let base_url = public_base_url(&app_state, &headers);
+    let mut response = Json(QuoteResource::new(quote, corpus.version, &base_url)).into_response();
+    apply_by_id_caching(&mut response, quote, corpus.version, &params);
+    Ok(response)
+}
+
+/// Finds the quote addressed by `id` (numeric id, uid or slug) for the by-id
+/// endpoints, applying the lifecycle and publishing rules at instant `at`.
+///
+/// # Errors
+///
+/// Returns an [`AppError::Gone`] if the quote is marked as deleted or was removed from the data file.
+/// Returns an [`AppError::NotFound`] if the quote is not found, is outside its publishing window, or no quotes are available.
+fn find_servable_quote<'a>(
+    corpus: &'a Corpus,
+    id: &str,
+    at: DateTime<Utc>,
+) -> Result<&'a Quote, AppError> {
+    quote_service::find_servable_quote(corpus, id, at)
+        .map_err(|error| AppError::from_quote_lookup(id, error))
+}
+
+/// Handles requests to the `/api/v1/quote/:id/revisions` endpoint.
+///
+/// Returns the revision log recorded for the quote as the data file was edited,
+/// along with its current `created_at`/`updated_at` timestamps. Quotes that have
+/// been removed from the file still return their history when addressed by
+/// numeric id; uids and slugs only resolve for quotes still in the corpus.
+///
+/// # Errors
+///
+/// Returns an [`AppError::NotFound`] if the quote is neither in the corpus nor has any recorded history.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/{id}/revisions",
+    tag = "Quotes",
+    operation_id = "getQuoteRevisions",
+    summary = "Get a quote's revision history",
+    description = "Lists the changes observed as the quotes file was edited. Removed quotes can still be addressed by numeric id.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote")),
+    responses(
+        (status = 200, description = "The revision history", body = QuoteRevisionsResponse),
+        (status = 404, description = "No such quote and no recorded history", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_revisions_handler(
+    State(app_state): State<AppState>,
+    axum::extract::Path(key): axum::extract::Path<String>,
+) -> Result<Json<QuoteRevisionsResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/{}/revisions.", key);
+
+    let corpus = app_state
+        .quote_store
+        .current()
+        .await
+        .map_err(|service_error| {
+            tracing::error!(
+                "Quote service error while loading the corpus for revisions of ID {}: {:?}",
+                key,
+                service_error
+            );
+            AppError::from(service_error)
+        })?;
+
+    let quote = corpus.find(&key);
+    let Some(id) = quote.map(|q| q.id).or_else(|| key.parse::<u32>().ok()) else {
+        tracing::info!("Quote with ID: {} not found.", key);
+        return Err(AppError::NotFound(format!(
+            "Quote with ID: {} not found.",
+            key
+        )));
+    };
+    let revisions = corpus.revisions_for(id).unwrap_or_default();
+    if quote.is_none() && revisions.is_empty() {
+        tracing::info!("Quote with ID: {} has no revision history.", key);
+        return Err(AppError::NotFound(format!(
+            "Quote with ID: {} not found.",
+            key
+        )));
+    }
+
+    Ok(Json(QuoteRevisionsResponse {
+        id,
+        created_at: quote.and_then(|q| q.created_at),
+        updated_at: quote.and_then(|q| q.updated_at),
+        revisions: revisions.to_vec(),
+    }))
+}
+
+/// Maximum number of ids in one bulk quote fetch.
+pub const MAX_BATCH_IDS: usize = 500;
+
+/// Path of the POST form of the bulk quote fetch, a custom method on `/api/v1/quotes`.
+pub const BATCH_GET_PATH: &str = "/api/v1/quotes:batchGet";
+
+/// Query parameters accepted by the `/api/v1/quotes` endpoint.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchQueryParams {
+    /// Comma-separated numeric ids, uids or slugs, e.g. `1,2,3`.
+    pub ids: Option<String>,
+}
+
+/// Request body of the `/api/v1/quotes:batchGet` endpoint.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchGetRequest {
+    /// Numeric ids, uids or slugs of the quotes to fetch.
+    #[schema(example = json!([1, "steve-jobs-stay-hungry-stay-foolish"]))]
+    pub ids: Vec<QuoteKey>,
+}
+
+/// A quote key in a [`BatchGetRequest`]: a JSON number or string.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum QuoteKey {
+    Id(u32),
+    Key(String),
+}
+
+impl QuoteKey {
+    fn into_key(self) -> String {
+        match self {
+            QuoteKey::Id(id) => id.to_string(),
+            QuoteKey::Key(key) => key,
+        }
+    }
+}
+
+/// Loads the corpus and looks up `ids` in it with [`batch_lookup`].
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `ids` is empty or longer than [`MAX_BATCH_IDS`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+async fn batch_get_quotes(
+    app_state: &AppState,
+    ids: Vec<String>,
+) -> Result<Json<BatchQuotesResponse>, AppError> {
+    check_batch_ids(&ids)?;
+    let corpus = app_state.quote_store.current().await?;
+    Ok(Json(batch_lookup(&corpus, ids, Utc::now())))
+}
+
+/// Checks that a bulk fetch lists between one and [`MAX_BATCH_IDS`] ids.
+fn check_batch_ids(ids: &[String]) -> Result<(), AppError> {
+    if ids.is_empty() {
+        return Err(AppError::BadRequest(
+            "ids must list at least one quote id.".to_string(),
+        ));
+    }
+    if ids.len() > MAX_BATCH_IDS {
+        return Err(AppError::BadRequest(format!(
+            "ids lists {} quote ids; at most {} can be fetched at once.",
+            ids.len(),
+            MAX_BATCH_IDS
+        )));
+    }
+    Ok(())
+}
+
+/// Looks up each of `ids` with the by-id rules at instant `now`, keeping
+/// request order and reporting ids that cannot be served per item.
+fn batch_lookup(corpus: &Corpus, ids: Vec<String>, now: DateTime<Utc>) -> BatchQuotesResponse {
+    let mut missing = Vec::new();
+    let items = ids
+        .into_iter()
+        .map(|id| match find_servable_quote(corpus, &id, now) {
+            Ok(quote) => BatchQuoteItem {
+                quote: Some(QuoteResponse::from(quote)),
+                error: None,
+                id,
+            },
+            Err(error) => {
+                missing.push(id.clone());
+                BatchQuoteItem {
+                    quote: None,
+                    error: Some(ErrorResponse {
+                        error_code: error.error_code().to_string(),
+                        message: error.message().to_string(),
+                    }),
+                    id,
+                }
+            }
+        })
+        .collect();
+    BatchQuotesResponse { items, missing }
+}
+
+/// Handles requests to the `/api/v1/quotes` endpoint.
+///
+/// Fetches the quotes listed in `ids` in one call. Each id is looked up as by
+/// [`get_quote_by_id_handler`]; ids that cannot be served are reported in
+/// their item rather than failing the request.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `ids` is missing, empty or longer than [`MAX_BATCH_IDS`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quotes",
+    tag = "Quotes",
+    operation_id = "listQuotesByIds",
+    summary = "Get several quotes by id",
+    description = "Looks up each listed id like `/api/v1/quote/{id}` and returns the results in request order. \
+                   Ids that cannot be served carry an `error` instead of a `quote`.",
+    params(BatchQueryParams),
+    responses(
+        (status = 200, description = "One item per requested id", body = BatchQuotesResponse),
+        (status = 400, description = "`ids` is missing, empty or too long", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn list_quotes_by_ids_handler(
+    State(app_state): State<AppState>,
+    Query(params): Query<BatchQueryParams>,
+) -> Result<Json<BatchQuotesResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/quotes.");
+    let ids = params
+        .ids
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect();
+    batch_get_quotes(&app_state, ids).await
+}
+
+/// Handles requests to the `/api/v1/quotes:batchGet` endpoint.
+///
+/// The POST form of [`list_quotes_by_ids_handler`], for lists of ids too long
+/// for a URL.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if the body is not a valid [`BatchGetRequest`], or lists no ids or more than [`MAX_BATCH_IDS`].
+/// Returns an [`AppError::NotFound`] for paths that only resemble this one.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    post,
+    path = "/api/v1/quotes:batchGet",
+    tag = "Quotes",
+    operation_id = "batchGetQuotes",
+    summary = "Get several quotes by id (POST)",
+    description = "Same as `GET /api/v1/quotes`, with the ids in a JSON body.",
+    request_body = BatchGetRequest,
+    responses(
+        (status = 200, description = "One item per requested id", body = BatchQuotesResponse),
+        (status = 400, description = "Malformed body, or no ids or too many", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn batch_get_quotes_handler(
+    State(app_state): State<AppState>,
+    uri: Uri,
+    body: Bytes,
+) -> Result<Json<BatchQuotesResponse>, AppError> {
+    // The router (matchit 0.7, via axum 0.7) has no way to escape `:`, so
+    // BATCH_GET_PATH registers the literal prefix `/api/v1/quotes` followed by
+    // a parameter named `batchGet`. That route also matches any other suffix,
+    // e.g. `/api/v1/quotesX:batchGet` or `/api/v1/quotes-archive:batchGet`, so
+    // everything but the exact path is answered with a 404 here.
+    if uri.path() != BATCH_GET_PATH {
+        return Err(AppError::NotFound(format!(
+            "No resource at {}.",
+            uri.path()
+        )));
+    }
+    tracing::debug!("Received request for /api/v1/quotes:batchGet.");
+    let request: BatchGetRequest = serde_json::from_slice(&body).map_err(|e| {
+        AppError::BadRequest(format!(
+            "Invalid batchGet body: {}. Expected {{\"ids\": [...]}}.",
+            e
+        ))
+    })?;
+    let ids = request.ids.into_iter().map(QuoteKey::into_key).collect();
+    batch_get_quotes(&app_state, ids).await
+}
+
+/// Handles requests to the `/api/v1/quotes/count` endpoint.
+///
+/// Counts the quotes `GET /api/v1/quote` picks from: active quotes inside
+/// their publishing window, matching `filter` if given. Admins can pass
+/// `preview_at` to evaluate the windows at another instant.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `filter` is malformed.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quotes/count",
+    tag = "Quotes",
+    operation_id = "countQuotes",
+    summary = "Count matching quotes",
+    description = "Counts the active, published quotes matching `filter`, or all of them without one.",
+    params(QuoteQueryParams, FilterQueryParams),
+    responses(
+        (status = 200, description = "The number of matching quotes", body = QuoteCountResponse),
+        (status = 400, description = "Malformed `filter` or `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn count_quotes_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+    Query(filter_params): Query<FilterQueryParams>,
+) -> Result<Json<QuoteCountResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/quotes/count.");
+    let filter = Filter::from_params(&filter_params)?;
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let corpus = app_state.quote_store.current().await?;
+    let count = quote_service::listable_quotes_at(&corpus.quotes, at)
+        .filter(|q| filter.as_ref().is_none_or(|f| f.matches(q)))
+        .count();
+    Ok(Json(QuoteCountResponse {
+        filter: filter_params.filter,
+        count,
+    }))
+}
+
+/// Query parameters accepted by the `/api/v1/quotes/facets` endpoint.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FacetQueryParams {
+    /// Words the quote text must all contain, ignoring case and accents.
+    pub text: Option<String>,
+    /// The author, matched as a whole ignoring case and accents.
+    pub author: Option<String>,
+    /// Minimum quote length in characters, inclusive.
+    pub min_len: Option<String>,
+    /// Maximum quote length in characters, inclusive.
+    pub max_len: Option<String>,
+    /// Maximum number of author and source values listed, from 1 to 100; defaults to 10.
+    pub limit: Option<String>,
+}
+
+/// Parses the optional numeric query parameter `name`, which must lie within
+/// `range`; `default` applies when it is absent.
+fn parse_bounded_limit<T>(
+    raw: Option<&str>,
+    range: RangeInclusive<T>,
+    default: T,
+    name: &str,
+) -> Result<T, AppError>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    let Some(raw) = raw else {
+        return Ok(default);
+    };
+    raw.trim()
+        .parse::<T>()
+        .ok()
+        .filter(|value| range.contains(value))
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid {} '{}': expected a number between {} and {}.",
+                name,
+                raw,
+                range.start(),
+                range.end()
+            ))
+        })
+}
+
+/// Parses an optional non-negative length parameter named `name`.
+fn parse_length_param(name: &str, value: Option<&str>) -> Result<Option<usize>, AppError> {
+    value
+        .map(|value| {
+            value.trim().parse::<usize>().map_err(|_| {
+                AppError::BadRequest(format!(
+                    "Invalid {} '{}': expected a whole number of characters.",
+                    name, value
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Handles requests to the `/api/v1/quotes/facets` endpoint.
+///
+/// Counts the active, published quotes matching the text, author and length
+/// filters by author, source and length bucket, using the facet index built
+/// when the current corpus was loaded. Admins can pass `preview_at` to
+/// evaluate publishing windows at another instant.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if a length is not a number, `min_len` exceeds `max_len`, or `limit` is out of range.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quotes/facets",
+    tag = "Quotes",
+    operation_id = "getQuoteFacets",
+    summary = "Count matching quotes by facet",
+    description = "Counts the active, published quotes matching the text, author and length filters, \
+                   in total and by author, source and length bucket, for filter sidebars.",
+    params(FacetQueryParams, QuoteQueryParams),
+    responses(
+        (status = 200, description = "The facet counts", body = Facets),
+        (status = 400, description = "Invalid length, limit or `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_facets_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<FacetQueryParams>,
+    Query(preview): Query<QuoteQueryParams>,
+) -> Result<Json<Facets>, AppError> {
+    tracing::debug!("Received request for /api/v1/quotes/facets.");
+    let min_len = parse_length_param("min_len", params.min_len.as_deref())?;
+    let max_len = parse_length_param("max_len", params.max_len.as_deref())?;
+    if let (Some(min_len), Some(max_len)) = (min_len, max_len) {
+        if min_len > max_len {
+            return Err(AppError::BadRequest(format!(
+                "min_len {} is greater than max_len {}.",
+                min_len, max_len
+            )));
+        }
+    }
+    let limit = parse_bounded_limit(
+        params.limit.as_deref(),
+        1..=MAX_FACET_LIMIT,
+        DEFAULT_FACET_LIMIT,
+        "limit",
+    )?;
+    let at = effective_instant(&app_state, &headers, &preview)?;
+
+    let corpus = app_state.quote_store.current().await?;
+    let query = FacetQuery {
+        text: params.text.as_deref().filter(|t| !t.trim().is_empty()),
+        author: params.author.as_deref().filter(|a| !a.trim().is_empty()),
+        min_len,
+        max_len,
+    };
+    Ok(Json(corpus.facets.facets(&query, at, limit)))
+}
+
+/// Handles requests to the `/api/v1/stats` endpoint.
+///
+/// Returns the [`CorpusStats`] computed when the current corpus was loaded.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    tag = "Quotes",
+    operation_id = "getCorpusStats",
+    summary = "Get corpus statistics",
+    description = "Counts, authors, length percentiles, missing sources and duplicate texts for the \
+                   current version of the quotes file. Computed once per load.",
+    responses(
+        (status = 200, description = "The statistics", body = CorpusStats),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_stats_handler(
+    State(app_state): State<AppState>,
+) -> Result<Json<CorpusStats>, AppError> {
+    tracing::debug!("Received request for /api/v1/stats.");
+    let corpus = app_state.quote_store.current().await?;
+    Ok(Json(corpus.stats.clone()))
+}
+
+/// Handles requests to the `/api/v1/diagnostics/duplicates` endpoint.
+///
+/// Returns the near-duplicate clusters found when the current corpus was loaded.
+/// The report quotes every quote in full, including deleted and scheduled
+/// ones, so it is admin-only.
+///
+/// # Errors
+///
+/// Returns an [`AppError::Forbidden`] without a valid admin token.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes,
+/// including a first load refused for a near-duplicate rate above
+/// `RUSTQUOTE_MAX_DUPLICATE_RATE`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/diagnostics/duplicates",
+    tag = "Diagnostics",
+    operation_id = "getNearDuplicates",
+    summary = "List near-duplicate quotes",
+    description = "Clusters of quotes that are the same quote with small punctuation or wording \
+                   differences, found when the quotes file was loaded. Admin-only: requires the \
+                   `X-Admin-Token` header.",
+    responses(
+        (status = 200, description = "The near-duplicate clusters", body = DuplicateReport),
+        (status = 403, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded, or has more near \
+                                      duplicates than allowed", body = ErrorResponse),
+    )
+)]
+pub async fn get_duplicates_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<DuplicateReport>, AppError> {
+    tracing::debug!("Received request for /api/v1/diagnostics/duplicates.");
+    if !is_admin(&app_state, &headers) {
+        tracing::warn!("Rejected duplicates diagnostics request without a valid admin token.");
+        return Err(AppError::Forbidden(
+            "The near-duplicate diagnostics require a valid admin token.".to_string(),
+        ));
+    }
+    let corpus = app_state.quote_store.current().await?;
+    Ok(Json(corpus.duplicates.clone()))
+}
+
+/// Query parameters accepted by the `/api/v1/suggest` endpoint.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestQueryParams {
+    /// The text typed so far. Matched ignoring case and accents.
+    pub prefix: Option<String>,
+    /// `author` or `term`; defaults to `term`.
+    pub kind: Option<String>,
+    /// Maximum number of suggestions, up to the configured maximum (the default).
+    pub limit: Option<String>,
+}
+
+/// Handles requests to the `/api/v1/suggest` endpoint.
+///
+/// Looks `prefix` up in the suggestion index built when the current corpus
+/// was loaded; a request only checks the quotes file's metadata and reads
+/// the file only if it changed. Only quotes published now count; admins can
+/// pass `preview_at` to evaluate publishing windows at another instant.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `prefix` is missing or blank, `kind` is unknown, or `limit` is not between 1 and the configured maximum.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/suggest",
+    tag = "Quotes",
+    operation_id = "suggest",
+    summary = "Autocomplete authors and terms",
+    description = "Suggests authors or words from active, published quotes that start with `prefix`, \
+                   ignoring case and accents, ranked by the number of quotes they appear in. Authors \
+                   also match on later words of their name.",
+    params(SuggestQueryParams, QuoteQueryParams),
+    responses(
+        (status = 200, description = "The suggestions", body = SuggestResponse),
+        (status = 400, description = "Missing prefix, unknown kind, invalid limit or malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn suggest_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SuggestQueryParams>,
+    Query(preview): Query<QuoteQueryParams>,
+) -> Result<Json<SuggestResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/suggest.");
+    let prefix = params.prefix.unwrap_or_default();
+    if prefix.trim().is_empty() {
+        return Err(AppError::BadRequest(
+            "The prefix parameter is required.".to_string(),
+        ));
+    }
+    let kind = params
+        .kind
+        .as_deref()
+        .map_or(Ok(SuggestKind::Term), str::parse)?;
+    let max_results = app_state.suggest_max_results;
+    let limit = parse_bounded_limit(
+        params.limit.as_deref(),
+        1..=max_results,
+        max_results,
+        "limit",
+    )?;
+
+    let at = effective_instant(&app_state, &headers, &preview)?;
+
+    let corpus = app_state.quote_store.current().await?;
+    let suggestions = corpus.suggest.suggest(kind, &prefix, limit, at);
+    Ok(Json(SuggestResponse {
+        prefix,
+        kind,
+        suggestions,
+    }))
+}
+
+/// Query parameters accepted by the `/api/v1/quote/:id/similar` endpoint.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SimilarQueryParams {
+    /// Maximum number of quotes to return, from 1 to 50; defaults to 5.
+    pub limit: Option<String>,
+    /// Added to the score of quotes by the same author, from 0 to 1; defaults to 0.
+    pub author_boost: Option<String>,
+}
+
+/// Handles requests to the `/api/v1/quote/:id/similar` endpoint.
+///
+/// Ranks other active, published quotes by the cosine similarity of their
+/// precomputed TF-IDF vectors to the quote's, optionally boosting quotes by
+/// the same author. The quote itself is looked up as by
+/// [`get_quote_by_id_handler`]. Admins can pass `preview_at` to evaluate
+/// publishing windows at another instant.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `limit` or `author_boost` is out of range.
+/// Returns an [`AppError::Forbidden`] or [`AppError::BadRequest`] for an unauthorised or malformed preview.
+/// Returns an [`AppError::Gone`] or [`AppError::NotFound`] under the same conditions as [`get_quote_by_id_handler`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+/// Returns an [`AppError::InternalServerError`] if the quote is missing from the corpus key index.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/{id}/similar",
+    tag = "Quotes",
+    operation_id = "getSimilarQuotes",
+    summary = "Get similar quotes",
+    description = "Recommends other active quotes with similar wording (TF-IDF cosine similarity), best \
+                   first. `author_boost` favours quotes by the same author. Quotes with nothing in \
+                   common are not returned.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote"), SimilarQueryParams, QuoteQueryParams),
+    responses(
+        (status = 200, description = "The similar quotes", body = SimilarQuotesResponse),
+        (status = 400, description = "`limit` or `author_boost` is out of range, or malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 404, description = "No such quote, or it is outside its publishing window", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_similar_quotes_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(params): Query<SimilarQueryParams>,
+    Query(preview): Query<QuoteQueryParams>,
+) -> Result<Json<SimilarQuotesResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/{}/similar.", id);
+    let limit = parse_bounded_limit(
+        params.limit.as_deref(),
+        1..=MAX_SIMILAR_LIMIT,
+        DEFAULT_SIMILAR_LIMIT,
+        "limit",
+    )?;
+    let author_boost = parse_bounded_limit(
+        params.author_boost.as_deref(),
+        0.0..=1.0,
+        0.0,
+        "author_boost",
+    )?;
+    let at = effective_instant(&app_state, &headers, &preview)?;
+
+    let corpus = app_state.quote_store.current().await?;
+    let quote = find_servable_quote(&corpus, &id, at)?;
+    let index = corpus.position(&id).ok_or_else(|| {
+        tracing::error!(
+            "Quote {} was found but has no position in the corpus.",
+            quote.id
+        );
+        AppError::InternalServerError(format!("Quote {} could not be indexed.", quote.id))
+    })?;
+    let similar = corpus
+        .similarity
+        .similar(&corpus.quotes, index, author_boost, at, limit)
+        .into_iter()
+        .map(|(quote, score)| SimilarQuote {
+            score,
+            quote: QuoteResponse::from(quote),
+        })
+        .collect();
+    Ok(Json(SimilarQuotesResponse {
+        id: quote.id,
+        similar,
+    }))
+}
+
+/// Loads the current corpus and the curated collections for collection handlers.
+async fn load_collections(
+    app_state: &AppState,
+) -> Result<(Arc<Corpus>, Arc<Vec<Collection>>), AppError> {
+    let corpus = app_state
+        .quote_store
+        .current()
+        .await
+        .map_err(|service_error| {
+            tracing::error!(
+                "Quote service error while loading the corpus for collections: {:?}",
+                service_error
+            );
+            AppError::from(service_error)
+        })?;
+    let collections = app_state
+        .collection_store
+        .current()
+        .await
+        .map_err(|service_error| {
+            tracing::error!(
+                "Collection service error while loading {}: {:?}",
+                app_state.collection_store.file_path().display(),
+                service_error
+            );
+            AppError::from(service_error)
+        })?;
+    Ok((corpus, collections))
+}
+
+/// Finds a collection by ID or returns an [`AppError::NotFound`].
+fn find_collection<'a>(
+    collections: &'a [Collection],
+    id: &str,
+) -> Result<&'a Collection, AppError> {
+    collection_service::get_collection_by_id(collections, id).ok_or_else(|| {
+        tracing::info!("Collection with ID: {} not found.", id);
+        AppError::NotFound(format!("Collection with ID: {} not found.", id))
+    })
+}
+
+/// Handles requests to the `/api/v1/collections` endpoint.
+///
+/// Lists every curated collection with the number of quotes that resolve in the
+/// corpus and the IDs that no longer do.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if the quotes or collections file cannot be loaded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/collections",
+    tag = "Collections",
+    operation_id = "listCollections",
+    summary = "List collections",
+    description = "Lists the curated collections.",
+    params(QuoteQueryParams),
+    responses(
+        (status = 200, description = "The collections", body = Vec<CollectionSummary>),
+        (status = 400, description = "Malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn list_collections_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQueryParams>,
+) -> Result<Json<Vec<CollectionSummary>>, AppError> {
+    tracing::debug!("Received request for /api/v1/collections.");
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let (corpus, collections) = load_collections(&app_state).await?;
+    let summaries = collections
+        .iter()
+        .map(|collection| {
+            let resolved = collection_service::resolve_collection(collection, &corpus.quotes, at);
+            CollectionSummary {
+                id: collection.id.clone(),
+                name: collection.name.clone(),
+                description: collection.description.clone(),
+                quote_count: resolved.quotes.len(),
+                missing_ids: resolved.missing_ids,
+            }
+        })
+        .collect();
+    Ok(Json(summaries))
+}
+
+/// Handles requests to the `/api/v1/collections/:id` endpoint.
+///
+/// Returns the collection's quotes in order. IDs that no longer exist in the
+/// corpus are listed in `missing_ids` instead of failing the request.
+///
+/// # Errors
+///
+/// Returns an [`AppError::NotFound`] if the collection does not exist.
+/// Returns an [`AppError::QuoteSourcingError`] if the quotes or collections file cannot be loaded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/collections/{id}",
+    tag = "Collections",
+    operation_id = "getCollection",
+    summary = "Get a collection",
+    description = "Returns a collection with its quotes in order.",
+    params(("id" = String, Path, description = "Collection id"), QuoteQueryParams),
+    responses(
+        (status = 200, description = "The collection", body = CollectionResponse),
+        (status = 400, description = "Malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 404, description = "No such collection", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_collection_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(params): Query<QuoteQueryParams>,
+) -> Result<Json<CollectionResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/collections/{}.", id);
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let (corpus, collections) = load_collections(&app_state).await?;
+    let collection = find_collection(&collections, &id)?;
+    let resolved = collection_service::resolve_collection(collection, &corpus.quotes, at);
+    if !resolved.missing_ids.is_empty() {
+        tracing::warn!(
+            "Collection {} references missing quote IDs: {:?}",
+            id,
+            resolved.missing_ids
+        );
+    }
+    Ok(Json(CollectionResponse {
+        id: collection.id.clone(),
+        name: collection.name.clone(),
+        description: collection.description.clone(),
+        quotes: resolved.quotes.iter().map(QuoteResponse::from).collect(),
+        missing_ids: resolved.missing_ids,
+    }))
+}
+
+/// Handles requests to the `/api/v1/collections/:id/random` endpoint.
+///
+/// Picks a random active quote from the collection, following the same rules
+/// as [`get_quote_handler`].
+///
+/// # Errors
+///
+/// Returns an [`AppError::NotFound`] if the collection does not exist or has no active quotes.
+/// Returns an [`AppError::QuoteSourcingError`] if the quotes or collections file cannot be loaded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/collections/{id}/random",
+    tag = "Collections",
+    operation_id = "getRandomCollectionQuote",
+    summary = "Get a random quote from a collection",
+    description = "Picks a random active quote from the collection.",
+    params(("id" = String, Path, description = "Collection id"), QuoteQueryParams, ResponseFormatParams),
+    responses(
+        (status = 200, description = "A random quote from the collection", body = QuoteResponse),
+        (status = 400, description = "Malformed `preview_at`", body = ErrorResponse),
+        (status = 403, description = "`preview_at` without a valid admin token", body = ErrorResponse),
+        (status = 404, description = "No such collection, or it has no active quotes", body = ErrorResponse),
+        (status = 406, description = "Unsupported response format", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_random_collection_quote_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(params): Query<QuoteQueryParams>,
+) -> Result<Json<QuoteResponse>, AppError> {
+    tracing::debug!("Received request for /api/v1/collections/{}/random.", id);
+    let at = effective_instant(&app_state, &headers, &params)?;
+    let (corpus, collections) = load_collections(&app_state).await?;
+    let collection = find_collection(&collections, &id)?;
+    let resolved = collection_service::resolve_collection(collection, &corpus.quotes, at);
+    match quote_service::get_random_quote_at(&resolved.quotes, at) {
+        Some(quote) => Ok(Json(QuoteResponse::from(quote))),
+        None => Err(AppError::NotFound(format!(
+            "Collection with ID: {} has no active quotes.",
+            id
+        ))),
+    }
+}
+
+/// Query parameters accepted by the quote card endpoints.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CardQueryParams {
+    /// Card theme name; see [`card::THEMES`].
+    pub theme: Option<String>,
+    /// Card size preset name; see [`card::SIZES`].
+    pub size: Option<String>,
+    /// PNG card width in pixels, overriding the size preset.
+    pub width: Option<String>,
+    /// PNG card height in pixels, overriding the size preset.
+    pub height: Option<String>,
+}
+
+/// Renders `quote` as an SVG card response.
+fn svg_card_response(quote: &Quote, params: &CardQueryParams) -> Result<Response, AppError> {
+    let theme = card::theme_by_name(params.theme.as_deref())?;
+    let size = card::size_by_name(params.size.as_deref())?;
+    let layout = card::layout_card(
+        &quote.text,
+        &quote.author,
+        size,
+        theme,
+        &card::approximate_text_width,
+    );
+    Ok((
+        [(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")],
+        card::svg::render_svg(&layout),
+    )
+        .into_response())
+}
+
+/// Handles requests to the `/api/v1/quote/:id/card.svg` endpoint.
+///
+/// Renders the quote as an SVG card. The quote is resolved exactly as for
+/// [`get_quote_by_id_handler`]; `theme` and `size` select the card style.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] for an unknown theme or size.
+/// Returns an [`AppError::Gone`] or [`AppError::NotFound`] under the same conditions as [`get_quote_by_id_handler`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/{id}/card.svg",
+    tag = "Cards",
+    operation_id = "getQuoteCardSvg",
+    summary = "Render a quote as an SVG card",
+    description = "Renders the quote as an SVG image.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote"), CardQueryParams),
+    responses(
+        (status = 200, description = "The card", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "Unknown theme or size", body = ErrorResponse),
+        (status = 404, description = "No such quote", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_card_svg_handler(
+    State(app_state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(params): Query<CardQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/{}/card.svg.", id);
+    let corpus = app_state.quote_store.current().await?;
+    let now = Utc::now();
+    let quote = find_servable_quote(&corpus, &id, now)?;
+    let mut response = svg_card_response(quote, &params)?;
+    CacheValidators::for_quote(quote, corpus.version, now).apply(response.headers_mut());
+    Ok(response)
+}
+
+/// Handles requests to the `/api/v1/quote/random/card.svg` endpoint.
+///
+/// Renders a random active quote as an SVG card, picked as for [`get_quote_handler`].
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] for an unknown theme or size.
+/// Returns an [`AppError::NotFound`] if no active quotes are available.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/random/card.svg",
+    tag = "Cards",
+    operation_id = "getRandomQuoteCardSvg",
+    summary = "Render a random quote as an SVG card",
+    description = "Renders a random active quote as an SVG image.",
+    params(CardQueryParams),
+    responses(
+        (status = 200, description = "The card", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "Unknown theme or size", body = ErrorResponse),
+        (status = 404, description = "No active quotes are available", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_random_quote_card_svg_handler(
+    State(app_state): State<AppState>,
+    Query(params): Query<CardQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/random/card.svg.");
+    let corpus = app_state.quote_store.current().await?;
+    let quote = quote_service::get_random_quote(&corpus.quotes).ok_or_else(|| {
+        AppError::NotFound("No active quotes available in the data file.".to_string())
+    })?;
+    svg_card_response(quote, &params)
+}
+
+/// Renders `quote` as a PNG card response, reusing a cached image when the
+/// same quote was already rendered with the same parameters.
+async fn png_card_response(
+    app_state: &AppState,
+    quote: &Quote,
+    params: &CardQueryParams,
+) -> Result<Response, AppError> {
+    let theme = card::theme_by_name(params.theme.as_deref())?;
+    let preset = card::size_by_name(params.size.as_deref())?;
+    let size = card::png::custom_size(preset, params.width.as_deref(), params.height.as_deref())?;
+    let key = card::png::CardCacheKey::new(quote, theme, size);
+
+    let image = match app_state.card_cache.get(&key) {
+        Some(image) => image,
+        None => {
+            let (text, author) = (quote.text.clone(), quote.author.clone());
+            // Rasterizing is CPU-bound, so keep it off the async workers.
+            let rendered = tokio::task::spawn_blocking(move || {
+                let layout =
+                    card::layout_card(&text, &author, size, theme, &card::png::measure_text);
+                card::png::render_png(&layout)
+            })
+            .await
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Card rendering failed: {}", e))
+            })??;
+            let image = Bytes::from(rendered);
+            app_state.card_cache.insert(key, image.clone());
+            image
+        }
+    };
+    Ok(([(header::CONTENT_TYPE, "image/png")], image).into_response())
+}
+
+/// Handles requests to the `/api/v1/quote/:id/card.png` endpoint.
+///
+/// Renders the quote as a PNG card with the bundled font. Accepts the same
+/// parameters as [`get_quote_card_svg_handler`] plus `width` and `height`.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] for an unknown theme or size, or dimensions outside the allowed range.
+/// Returns an [`AppError::Gone`] or [`AppError::NotFound`] under the same conditions as [`get_quote_by_id_handler`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/{id}/card.png",
+    tag = "Cards",
+    operation_id = "getQuoteCardPng",
+    summary = "Render a quote as a PNG card",
+    description = "Renders the quote as a PNG image.",
+    params(("id" = String, Path, description = "Numeric id, uid or slug of the quote"), CardQueryParams),
+    responses(
+        (status = 200, description = "The card", content_type = "image/png", body = Vec<u8>),
+        (status = 400, description = "Unknown theme or size, or dimensions out of range", body = ErrorResponse),
+        (status = 404, description = "No such quote", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_quote_card_png_handler(
+    State(app_state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(params): Query<CardQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/{}/card.png.", id);
+    let corpus = app_state.quote_store.current().await?;
+    let now = Utc::now();
+    let quote = find_servable_quote(&corpus, &id, now)?;
+    let mut response = png_card_response(&app_state, quote, &params).await?;
+    CacheValidators::for_quote(quote, corpus.version, now).apply(response.headers_mut());
+    Ok(response)
+}
+
+/// Handles requests to the `/api/v1/quote/random/card.png` endpoint.
+///
+/// Renders a random active quote as a PNG card.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] for an unknown theme or size, or dimensions outside the allowed range.
+/// Returns an [`AppError::NotFound`] if no active quotes are available.
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/api/v1/quote/random/card.png",
+    tag = "Cards",
+    operation_id = "getRandomQuoteCardPng",
+    summary = "Render a random quote as a PNG card",
+    description = "Renders a random active quote as a PNG image.",
+    params(CardQueryParams),
+    responses(
+        (status = 200, description = "The card", content_type = "image/png", body = Vec<u8>),
+        (status = 400, description = "Unknown theme or size, or dimensions out of range", body = ErrorResponse),
+        (status = 404, description = "No active quotes are available", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn get_random_quote_card_png_handler(
+    State(app_state): State<AppState>,
+    Query(params): Query<CardQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /api/v1/quote/random/card.png.");
+    let corpus = app_state.quote_store.current().await?;
+    let quote = quote_service::get_random_quote(&corpus.quotes).ok_or_else(|| {
+        AppError::NotFound("No active quotes available in the data file.".to_string())
+    })?;
+    png_card_response(&app_state, quote, &params).await
+}
+
+/// Returns the base URL for absolute links: the configured public URL, or
+/// `http://` plus the request's `Host` header.
+fn public_base_url(app_state: &AppState, headers: &HeaderMap) -> String {
+    if let Some(url) = &app_state.public_base_url {
+        return url.to_string();
+    }
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .filter(|h| {
+            !h.is_empty()
+                && h.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+        })
+        .unwrap_or("localhost");
+    format!("http://{}", host)
+}
+
+fn feed_response(content_type: &'static str, body: String) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+/// Handles requests to the `/feeds/daily.rss` endpoint.
+///
+/// Returns an RSS 2.0 feed with the quote of the day for each of the last
+/// [`feeds::DAILY_FEED_DAYS`] UTC days.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/feeds/daily.rss",
+    tag = "Feeds",
+    operation_id = "getDailyRssFeed",
+    summary = "Quote of the day RSS feed",
+    description = "The quote of the day for each of the last 7 days.",
+    responses(
+        (status = 200, description = "An RSS 2.0 feed", content_type = "application/rss+xml", body = String),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn daily_rss_feed_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /feeds/daily.rss.");
+    let corpus = app_state.quote_store.current().await?;
+    let base_url = public_base_url(&app_state, &headers);
+    let self_link = format!("{}/feeds/daily.rss", base_url);
+    let feed = feeds::daily_feed(&corpus, Utc::now().date_naive(), &base_url, &self_link);
+    Ok(feed_response(rss::CONTENT_TYPE, rss::render_rss(&feed)))
+}
+
+/// Handles requests to the `/feeds/daily.atom` endpoint.
+///
+/// Returns the same entries as [`daily_rss_feed_handler`] as an Atom feed.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/feeds/daily.atom",
+    tag = "Feeds",
+    operation_id = "getDailyAtomFeed",
+    summary = "Quote of the day Atom feed",
+    description = "The quote of the day for each of the last 7 days.",
+    responses(
+        (status = 200, description = "An Atom feed", content_type = "application/atom+xml", body = String),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn daily_atom_feed_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /feeds/daily.atom.");
+    let corpus = app_state.quote_store.current().await?;
+    let base_url = public_base_url(&app_state, &headers);
+    let self_link = format!("{}/feeds/daily.atom", base_url);
+    let feed = feeds::daily_feed(&corpus, Utc::now().date_naive(), &base_url, &self_link);
+    Ok(feed_response(atom::CONTENT_TYPE, atom::render_atom(&feed)))
+}
+
+/// Handles requests to the `/feeds/new.atom` endpoint.
+///
+/// Returns an Atom feed of the quotes that appeared in the data file since
+/// earlier loads, newest first.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/feeds/new.atom",
+    tag = "Feeds",
+    operation_id = "getNewQuotesAtomFeed",
+    summary = "New quotes Atom feed",
+    description = "Quotes added to the quotes file since the service started, newest first.",
+    responses(
+        (status = 200, description = "An Atom feed", content_type = "application/atom+xml", body = String),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn new_quotes_atom_feed_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /feeds/new.atom.");
+    let corpus = app_state.quote_store.current().await?;
+    let base_url = public_base_url(&app_state, &headers);
+    let self_link = format!("{}/feeds/new.atom", base_url);
+    let feed = feeds::new_quotes_feed(&corpus, Utc::now(), &base_url, &self_link);
+    Ok(feed_response(atom::CONTENT_TYPE, atom::render_atom(&feed)))
+}
+
+/// Query parameters accepted by the calendar feed.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CalendarQueryParams {
+    /// Number of upcoming days to include, overriding the configured default.
+    pub days: Option<String>,
+}
+
+/// Handles requests to the `/feeds/daily.ics` endpoint.
+///
+/// Returns an iCalendar document with one all-day event per upcoming UTC day,
+/// starting today, each carrying that day's quote. The horizon defaults to
+/// the configured number of days and can be changed with `days`.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if `days` is not a number between 1 and [`ical::MAX_CALENDAR_DAYS`].
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    get,
+    path = "/feeds/daily.ics",
+    tag = "Feeds",
+    operation_id = "getDailyCalendar",
+    summary = "Quote of the day calendar",
+    description = "One all-day event per upcoming day with that day's quote.",
+    params(CalendarQueryParams),
+    responses(
+        (status = 200, description = "An iCalendar document", content_type = "text/calendar", body = String),
+        (status = 400, description = "`days` out of range", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn daily_calendar_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CalendarQueryParams>,
+) -> Result<Response, AppError> {
+    tracing::debug!("Received request for /feeds/daily.ics.");
+    let days = parse_bounded_limit(
+        params.days.as_deref(),
+        1..=ical::MAX_CALENDAR_DAYS,
+        app_state.calendar_days,
+        "days",
+    )?;
+    let corpus = app_state.quote_store.current().await?;
+    let base_url = public_base_url(&app_state, &headers);
+    let now = Utc::now();
+    let calendar = ical::render_daily_calendar(&corpus, now.date_naive(), days, now, &base_url);
+    Ok(feed_response(ical::CONTENT_TYPE, calendar))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
+    use crate::services::quote_service::assign_identifiers;
+
+    fn keys(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn error_code(item: &BatchQuoteItem) -> Option<&str> {
+        item.error.as_ref().map(|e| e.error_code.as_str())
+    }
+
+    #[test]
+    fn test_parse_bounded_limit() {
+        assert_eq!(parse_bounded_limit(None, 1..=50, 5, "limit").unwrap(), 5);
+        assert_eq!(
+            parse_bounded_limit(Some(" 50 "), 1..=50, 5, "limit").unwrap(),
+            50
+        );
+        assert_eq!(
+            parse_bounded_limit(Some("0.5"), 0.0..=1.0, 0.0, "author_boost").unwrap(),
+            0.5
+        );
+        for raw in ["0", "51", "-1", "ten"] {
+            match parse_bounded_limit(Some(raw), 1..=50, 5, "limit") {
+                Err(AppError::BadRequest(message)) => assert_eq!(
+                    message,
+                    format!(
+                        "Invalid limit '{}': expected a number between 1 and 50.",
+                        raw
+                    )
+                ),
+                other => panic!("unexpected result for {}: {:?}", raw, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_lookup_keeps_request_order_and_reports_errors_per_item() {
+        let now = Utc::now();
+        let mut quotes = vec![
+            quote(1, "One", "Ada", None),
+            Quote {
+                status: QuoteStatus::Deleted,
+                ..quote(2, "Two", "Ada", None)
+            },
+            Quote {
+                publish_at: Some(now + chrono::Duration::days(1)),
+                ..quote(3, "Three", "Ada", None)
+            },
+            quote(4, "Four", "Ada", None),
+        ];
+        assign_identifiers(&mut quotes);
+        let uid = quotes[0].uid.clone();
+        let first = Corpus::from_load(None, quotes.clone(), now);
+        quotes.pop();
+        let corpus = Corpus::from_load(Some(&first), quotes, now);
+
+        let response = batch_lookup(&corpus, keys(&["3", &uid, "missing", "2", "4", "1"]), now);
+        let ids: Vec<&str> = response.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["3", uid.as_str(), "missing", "2", "4", "1"]);
+        let codes: Vec<Option<&str>> = response.items.iter().map(error_code).collect();
+        assert_eq!(
+            codes,
+            [
+                Some("NOT_FOUND"),
+                None,
+                Some("NOT_FOUND"),
+                Some("GONE"),
+                Some("GONE"),
+                None
+            ]
+        );
+        assert_eq!(response.items[1].quote.as_ref().unwrap().id, 1);
+        assert_eq!(response.items[5].quote.as_ref().unwrap().id, 1);
+        assert!(response.items[0].quote.is_none());
+        assert_eq!(response.missing, ["3", "missing", "2", "4"]);
+    }
+
+    #[test]
+    fn test_check_batch_ids_bounds() {
+        assert!(matches!(check_batch_ids(&[]), Err(AppError::BadRequest(_))));
+        assert!(check_batch_ids(&keys(&["1"])).is_ok());
+        assert!(check_batch_ids(&vec!["1".to_string(); MAX_BATCH_IDS]).is_ok());
+        assert!(matches!(
+            check_batch_ids(&vec!["1".to_string(); MAX_BATCH_IDS + 1]),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! # HTTP Caching
+//!
+//! This module provides cache validators and conditional request handling.
+//! Handlers attach an ETag, `Last-Modified` and `Cache-Control` to cacheable
+//! responses with [`CacheValidators`]; the [`conditional_get`] middleware then
+//! answers `If-None-Match` and `If-Modified-Since` requests with
+//! `304 Not Modified`. Routes that must never be cached use [`no_store`].
+
+use crate::models::Quote;
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{
+        header::{
+            CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Longest time a by-id response may be cached without revalidation.
+pub const BY_ID_MAX_AGE_SECS: i64 = 86_400;
+
+/// `Cache-Control` value for responses that must never be cached.
+pub const NO_STORE: &str = "no-store";
+
+/// Cache validators and freshness for one response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheValidators {
+    /// The strong entity tag, including its quotes.
+    pub etag: String,
+    /// When the underlying content last changed.
+    pub last_modified: Option<DateTime<Utc>>,
+    /// The `Cache-Control` header value.
+    pub cache_control: String,
+}
+
+impl CacheValidators {
+    /// Builds validators for a quote served by id.
+    ///
+    /// The ETag covers the quote's full content and the corpus version, so any
+    /// reload that changes the corpus also changes it. Responses may be cached
+    /// for up to [`BY_ID_MAX_AGE_SECS`], but never past the quote's `expire_at`.
+    pub fn for_quote(quote: &Quote, corpus_version: u64, now: DateTime<Utc>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(corpus_version.to_be_bytes());
+        hasher.update(serde_json::to_vec(quote).unwrap_or_default());
+        let digest = hasher.finalize();
+        let hex: String = digest[..10].iter().map(|b| format!("{:02x}", b)).collect();
+
+        let max_age = quote
+            .expire_at
+            .map(|expire_at| (expire_at - now).num_seconds().clamp(0, BY_ID_MAX_AGE_SECS))
+            .unwrap_or(BY_ID_MAX_AGE_SECS);
+        CacheValidators {
+            etag: format!("\"v{}-{}\"", corpus_version, hex),
+            last_modified: quote.updated_at.or(quote.created_at),
+            cache_control: format!("public, max-age={}", max_age),
+        }
+    }
+
+    /// Writes the validators into response headers.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(ETAG, etag);
+        }
+        if let Some(last_modified) = self.last_modified {
+            if let Ok(value) = HeaderValue::from_str(&http_date(last_modified)) {
+                headers.insert(LAST_MODIFIED, value);
+            }
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.cache_control) {
+            headers.insert(CACHE_CONTROL, value);
+        }
+    }
+}
+
+/// Formats a timestamp as an HTTP date (IMF-fixdate).
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an HTTP date. Only the IMF-fixdate form is accepted; other forms
+/// are treated as absent, which just disables the `If-Modified-Since` check.
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Returns `true` if an `If-None-Match` header value matches `etag`, using
+/// the weak comparison required for `GET`.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+/// Makes an ETag specific to the response's media type, so that the JSON,
+/// text, HTML, Markdown and XML renderings of a negotiated resource do not
+/// share a tag.
+fn representation_etag(etag: &str, content_type: Option<&str>) -> String {
+    let subtype = content_type
+        .and_then(|ct| ct.split(';').next())
+        .and_then(|mime| mime.split('/').nth(1))
+        .map(|s| s.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    match subtype.as_str() {
+        "" | "json" => etag.to_string(),
+        subtype => {
+            let subtype: String = subtype
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+                .collect();
+            format!("{}-{}\"", etag.trim_end_matches('"'), subtype)
+        }
+    }
+}
+
+/// Middleware that answers conditional `GET`/`HEAD` requests.
+///
+/// Successful responses that carry an ETag get it made representation-specific
+/// (see [`representation_etag`]). If the request's `If-None-Match` matches it,
+/// or, when `If-None-Match` is absent, the response has not been modified
+/// since `If-Modified-Since`, the body is dropped and `304 Not Modified` is
+/// returned with the validators and caching headers.
+pub async fn conditional_get(request: Request, next: Next) -> Response {
+    let is_get = matches!(*request.method(), Method::GET | Method::HEAD);
+    let if_none_match = header_string(request.headers(), IF_NONE_MATCH);
+    let if_modified_since = header_string(request.headers(), IF_MODIFIED_SINCE);
+
+    let mut response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let Some(etag) = header_string(response.headers(), ETAG) else {
+        return response;
+    };
+    let etag = representation_etag(
+        &etag,
+        header_string(response.headers(), CONTENT_TYPE).as_deref(),
+    );
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(ETAG, value);
+    }
+    if !is_get {
+        return response;
+    }
+
+    let not_modified = match (if_none_match, if_modified_since) {
+        (Some(if_none_match), _) => etag_matches(&if_none_match, &etag),
+        (None, Some(since)) => {
+            let last_modified = header_string(response.headers(), LAST_MODIFIED)
+                .as_deref()
+                .and_then(parse_http_date);
+            match (parse_http_date(&since), last_modified) {
+                (Some(since), Some(last_modified)) => {
+                    last_modified.timestamp() <= since.timestamp()
+                }
+                _ => false,
+            }
+        }
+        (None, None) => false,
+    };
+    if !not_modified {
+        return response;
+    }
+
+    let (mut parts, _) = response.into_parts();
+    parts.status = StatusCode::NOT_MODIFIED;
+    parts.headers.remove(CONTENT_TYPE);
+    parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::empty())
+}
+
+/// Middleware that marks every response as `Cache-Control: no-store`.
+pub async fn no_store(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static(NO_STORE));
+    response
+}
+
+fn header_string(headers: &HeaderMap, name: axum::http::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures;
+    use chrono::Duration;
+
+    fn quote() -> Quote {
+        Quote {
+            updated_at: Some("2025-06-01T10:00:00Z".parse().unwrap()),
+            ..fixtures::quote(1, "Text", "Author", None)
+        }
+    }
+
+    #[test]
+    fn test_etag_changes_with_content_and_version() {
+        let now = Utc::now();
+        let base = CacheValidators::for_quote(&quote(), 1, now);
+        assert!(base.etag.starts_with("\"v1-") && base.etag.ends_with('"'));
+        assert_eq!(base, CacheValidators::for_quote(&quote(), 1, now));
+        assert_ne!(base.etag, CacheValidators::for_quote(&quote(), 2, now).etag);
+        let edited = Quote {
+            text: "Edited".to_string(),
+            ..quote()
+        };
+        assert_ne!(base.etag, CacheValidators::for_quote(&edited, 1, now).etag);
+        assert_eq!(base.cache_control, "public, max-age=86400");
+        assert_eq!(base.last_modified, quote().updated_at);
+    }
+
+    #[test]
+    fn test_max_age_stops_at_expiry() {
+        let now: DateTime<Utc> = "2025-06-01T12:00:00Z".parse().unwrap();
+        let expiring = Quote {
+            expire_at: Some(now + Duration::minutes(10)),
+            ..quote()
+        };
+        assert_eq!(
+            CacheValidators::for_quote(&expiring, 1, now).cache_control,
+            "public, max-age=600"
+        );
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"a\"", "\"a\""));
+        assert!(etag_matches("\"b\", W/\"a\"", "\"a\""));
+        assert!(etag_matches("*", "\"a\""));
+        assert!(!etag_matches("\"b\"", "\"a\""));
+    }
+
+    #[test]
+    fn test_representation_etag() {
+        assert_eq!(
+            representation_etag("\"v1-ab\"", Some("application/json")),
+            "\"v1-ab\""
+        );
+        assert_eq!(
+            representation_etag("\"v1-ab\"", Some("text/html; charset=utf-8")),
+            "\"v1-ab-html\""
+        );
+        assert_eq!(
+            representation_etag("\"v1-ab\"", Some("image/svg+xml")),
+            "\"v1-ab-svg+xml\""
+        );
+    }
+
+    #[test]
+    fn test_http_date_round_trip() {
+        let at: DateTime<Utc> = "2025-06-01T10:00:00Z".parse().unwrap();
+        assert_eq!(http_date(at), "Sun, 01 Jun 2025 10:00:00 GMT");
+        assert_eq!(parse_http_date(&http_date(at)), Some(at));
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+}
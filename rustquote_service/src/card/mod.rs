@@ -0,0 +1,406 @@
+//! # Quote Cards
+//!
+//! This module lays out shareable quote cards: it picks a font size, wraps the
+//! quote text to fit the card, and positions the author line. The layout is
+//! independent of the output format; the `svg` submodule turns it into an SVG
+//! document and the `png` submodule rasterizes it with a bundled font.
+//!
+//! Text is measured through a caller-supplied function so that renderers with
+//! real font metrics can share the same layout code.
+
+pub mod png;
+pub mod svg;
+
+use crate::errors::AppError;
+
+/// Colours used to draw a card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardTheme {
+    /// The theme name used in the `theme` query parameter.
+    pub name: &'static str,
+    /// Card background colour.
+    pub background: &'static str,
+    /// Quote text colour.
+    pub foreground: &'static str,
+    /// Colour of the accent bar.
+    pub accent: &'static str,
+    /// Author line colour.
+    pub muted: &'static str,
+}
+
+/// The available card themes. The first one is the default.
+pub const THEMES: &[CardTheme] = &[
+    CardTheme {
+        name: "light",
+        background: "#ffffff",
+        foreground: "#1f2933",
+        accent: "#3e7cb1",
+        muted: "#52606d",
+    },
+    CardTheme {
+        name: "dark",
+        background: "#1a1d23",
+        foreground: "#f5f7fa",
+        accent: "#f7c948",
+        muted: "#9aa5b1",
+    },
+    CardTheme {
+        name: "sepia",
+        background: "#f4ecd8",
+        foreground: "#433422",
+        accent: "#a0522d",
+        muted: "#7a6651",
+    },
+    CardTheme {
+        name: "ocean",
+        background: "#0b3954",
+        foreground: "#e0f2f1",
+        accent: "#4fd1c5",
+        muted: "#a3c4bc",
+    },
+];
+
+/// A named card size preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardSize {
+    /// The size name used in the `size` query parameter.
+    pub name: &'static str,
+    /// Card width in pixels.
+    pub width: u32,
+    /// Card height in pixels.
+    pub height: u32,
+}
+
+/// The available card sizes. The first one is the default.
+pub const SIZES: &[CardSize] = &[
+    // Open Graph / link preview size.
+    CardSize {
+        name: "landscape",
+        width: 1200,
+        height: 630,
+    },
+    CardSize {
+        name: "square",
+        width: 1080,
+        height: 1080,
+    },
+    CardSize {
+        name: "portrait",
+        width: 1080,
+        height: 1350,
+    },
+    CardSize {
+        name: "story",
+        width: 1080,
+        height: 1920,
+    },
+];
+
+/// Looks up a theme by name, defaulting to the first theme.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] listing the valid themes if `name` is unknown.
+pub fn theme_by_name(name: Option<&str>) -> Result<CardTheme, AppError> {
+    let Some(name) = name else {
+        return Ok(THEMES[0]);
+    };
+    THEMES
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+        .copied()
+        .ok_or_else(|| {
+            let valid: Vec<&str> = THEMES.iter().map(|t| t.name).collect();
+            AppError::BadRequest(format!(
+                "Unknown theme '{}'. Valid themes: {}.",
+                name,
+                valid.join(", ")
+            ))
+        })
+}
+
+/// Looks up a size preset by name, defaulting to the first size.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] listing the valid sizes if `name` is unknown.
+pub fn size_by_name(name: Option<&str>) -> Result<CardSize, AppError> {
+    let Some(name) = name else {
+        return Ok(SIZES[0]);
+    };
+    SIZES
+        .iter()
+        .find(|s| s.name.eq_ignore_ascii_case(name))
+        .copied()
+        .ok_or_else(|| {
+            let valid: Vec<&str> = SIZES.iter().map(|s| s.name).collect();
+            AppError::BadRequest(format!(
+                "Unknown size '{}'. Valid sizes: {}.",
+                name,
+                valid.join(", ")
+            ))
+        })
+}
+
+/// Line height as a multiple of the font size.
+pub const LINE_HEIGHT: f32 = 1.35;
+
+/// Smallest quote font size tried before the text is truncated.
+const MIN_QUOTE_FONT_SIZE: f32 = 16.0;
+
+/// A fully positioned quote card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardLayout {
+    /// Card width in pixels.
+    pub width: u32,
+    /// Card height in pixels.
+    pub height: u32,
+    /// Colours to draw with.
+    pub theme: CardTheme,
+    /// Left edge of the text block.
+    pub text_x: f32,
+    /// Quote font size in pixels.
+    pub quote_font_size: f32,
+    /// The wrapped quote lines, including typographic quotation marks.
+    pub lines: Vec<String>,
+    /// Baseline of the first quote line.
+    pub first_baseline: f32,
+    /// Author line text, including the leading dash.
+    pub author: String,
+    /// Author font size in pixels.
+    pub author_font_size: f32,
+    /// Baseline of the author line.
+    pub author_baseline: f32,
+    /// Accent bar as `(x, y, width, height)`.
+    pub accent_bar: (f32, f32, f32, f32),
+}
+
+/// Lays out a quote card.
+///
+/// `measure(text, font_size)` must return the rendered width of `text` in
+/// pixels. The quote font size starts large and shrinks until the wrapped text
+/// fits; if it still does not fit at the minimum size, the last visible line is
+/// cut with an ellipsis.
+pub fn layout_card(
+    text: &str,
+    author: &str,
+    size: CardSize,
+    theme: CardTheme,
+    measure: &dyn Fn(&str, f32) -> f32,
+) -> CardLayout {
+    let width = size.width as f32;
+    let height = size.height as f32;
+    let padding = (width.min(height) * 0.09).round();
+    let text_width = width - 2.0 * padding;
+    let quoted = format!("\u{201c}{}\u{201d}", text.trim());
+
+    let mut font_size = (height / 7.0).min(width / 9.0).max(MIN_QUOTE_FONT_SIZE);
+    let (quote_font_size, author_font_size, lines) = loop {
+        let author_font_size = (font_size * 0.6).clamp(14.0, 44.0);
+        // Room left for the quote once the author line and its gap are reserved.
+        let available = height - 2.0 * padding - author_font_size * (LINE_HEIGHT + 1.0);
+        let lines = wrap_text(&quoted, text_width, font_size, measure);
+        let needed = lines.len() as f32 * font_size * LINE_HEIGHT;
+        if needed <= available || font_size <= MIN_QUOTE_FONT_SIZE {
+            let max_lines = ((available / (font_size * LINE_HEIGHT)).floor() as usize).max(1);
+            let lines = truncate_lines(lines, max_lines, text_width, font_size, measure);
+            break (font_size, author_font_size, lines);
+        }
+        font_size = (font_size * 0.92).max(MIN_QUOTE_FONT_SIZE);
+    };
+
+    let block_height =
+        lines.len() as f32 * quote_font_size * LINE_HEIGHT + author_font_size * (LINE_HEIGHT + 1.0);
+    let top = ((height - block_height) / 2.0).max(padding);
+    let first_baseline = top + quote_font_size;
+    let author_baseline = top
+        + lines.len() as f32 * quote_font_size * LINE_HEIGHT
+        + author_font_size * (LINE_HEIGHT + 0.6);
+
+    CardLayout {
+        width: size.width,
+        height: size.height,
+        theme,
+        text_x: padding,
+        quote_font_size,
+        lines,
+        first_baseline,
+        author: format!("\u{2014} {}", author.trim()),
+        author_font_size,
+        author_baseline,
+        accent_bar: (
+            padding,
+            padding * 0.5,
+            padding * 1.2,
+            (padding * 0.08).max(4.0),
+        ),
+    }
+}
+
+/// Greedily wraps `text` into lines no wider than `max_width`.
+///
+/// Words wider than a whole line are broken between characters.
+pub fn wrap_text(
+    text: &str,
+    max_width: f32,
+    font_size: f32,
+    measure: &dyn Fn(&str, f32) -> f32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if measure(&candidate, font_size) <= max_width {
+            current = candidate;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if measure(word, font_size) <= max_width {
+            current = word.to_string();
+            continue;
+        }
+        for c in word.chars() {
+            current.push(c);
+            if measure(&current, font_size) > max_width && current.chars().count() > 1 {
+                current.pop();
+                lines.push(std::mem::take(&mut current));
+                current.push(c);
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Keeps at most `max_lines` lines, ending the last kept line with an ellipsis
+/// if anything was cut.
+fn truncate_lines(
+    mut lines: Vec<String>,
+    max_lines: usize,
+    max_width: f32,
+    font_size: f32,
+    measure: &dyn Fn(&str, f32) -> f32,
+) -> Vec<String> {
+    if lines.len() <= max_lines {
+        return lines;
+    }
+    lines.truncate(max_lines);
+    if let Some(last) = lines.last_mut() {
+        while !last.is_empty() && measure(&format!("{}\u{2026}", last), font_size) > max_width {
+            last.pop();
+        }
+        let trimmed = last.trim_end().to_string();
+        *last = format!("{}\u{2026}", trimmed);
+    }
+    lines
+}
+
+/// Estimates the width of `text` without font metrics.
+///
+/// Uses rough per-character advance widths for a proportional serif face,
+/// expressed as fractions of the font size. Wide (e.g. CJK) characters count
+/// as a full em.
+pub fn approximate_text_width(text: &str, font_size: f32) -> f32 {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'j' | '\'' | '.' | ',' | ':' | ';' | '!' | '|' => 0.28,
+            'f' | 't' | 'r' | 'I' | '(' | ')' | '[' | ']' | '-' | ' ' => 0.36,
+            'm' | 'w' | 'M' | 'W' | '@' | '\u{2014}' => 0.85,
+            'A'..='Z' => 0.68,
+            '\u{201c}' | '\u{201d}' | '"' => 0.42,
+            c if c.is_ascii() => 0.52,
+            c if is_wide(c) => 1.0,
+            _ => 0.58,
+        })
+        .sum::<f32>()
+        * font_size
+}
+
+/// Returns `true` for characters usually drawn a full em wide.
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x1F300..=0x1FAFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed_width(text: &str, font_size: f32) -> f32 {
+        text.chars().count() as f32 * font_size * 0.5
+    }
+
+    #[test]
+    fn test_theme_and_size_lookup() {
+        assert_eq!(theme_by_name(None).unwrap().name, "light");
+        assert_eq!(theme_by_name(Some("DARK")).unwrap().name, "dark");
+        match theme_by_name(Some("neon")) {
+            Err(AppError::BadRequest(msg)) => assert!(msg.contains("light, dark, sepia, ocean")),
+            _ => panic!("Expected BadRequest"),
+        }
+        assert_eq!(size_by_name(Some("square")).unwrap().height, 1080);
+        assert!(matches!(
+            size_by_name(Some("huge")),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrap_text_respects_width() {
+        let lines = wrap_text("aaaa bbbb cccc", 40.0, 10.0, &fixed_width);
+        assert_eq!(lines, vec!["aaaa", "bbbb", "cccc"]);
+        let lines = wrap_text("aa bb cc dd", 30.0, 10.0, &fixed_width);
+        assert_eq!(lines, vec!["aa bb", "cc dd"]);
+    }
+
+    #[test]
+    fn test_wrap_text_breaks_long_words() {
+        let lines = wrap_text("abcdefghij", 30.0, 10.0, &fixed_width);
+        assert_eq!(lines, vec!["abcdef", "ghij"]);
+    }
+
+    #[test]
+    fn test_layout_fits_long_quotes() {
+        let size = size_by_name(None).unwrap();
+        let text = "word ".repeat(120);
+        let layout = layout_card(&text, "Author", size, THEMES[0], &approximate_text_width);
+        let max_width = size.width as f32 - 2.0 * layout.text_x;
+        for line in &layout.lines {
+            assert!(approximate_text_width(line, layout.quote_font_size) <= max_width);
+        }
+        assert!(layout.author_baseline <= size.height as f32);
+        assert!(layout.quote_font_size < 90.0);
+    }
+
+    #[test]
+    fn test_layout_truncates_text_that_cannot_fit() {
+        let size = size_by_name(None).unwrap();
+        let text = "word ".repeat(2000);
+        let layout = layout_card(&text, "Author", size, THEMES[0], &approximate_text_width);
+        assert_eq!(layout.quote_font_size, MIN_QUOTE_FONT_SIZE);
+        assert!(layout.lines.last().unwrap().ends_with('\u{2026}'));
+        assert!(layout.author_baseline <= size.height as f32);
+    }
+
+    #[test]
+    fn test_layout_short_quote_uses_large_font() {
+        let layout = layout_card(
+            "Stay hungry.",
+            "Steve Jobs",
+            SIZES[0],
+            THEMES[0],
+            &approximate_text_width,
+        );
+        assert_eq!(layout.lines, vec!["\u{201c}Stay hungry.\u{201d}"]);
+        assert_eq!(layout.author, "\u{2014} Steve Jobs");
+        assert!(layout.quote_font_size > 60.0);
+    }
+}
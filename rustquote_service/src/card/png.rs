@@ -0,0 +1,370 @@
+//! # PNG Quote Cards
+//!
+//! Rasterizes a [`CardLayout`] in-process with a font bundled into the binary
+//! (DejaVu Serif, see `assets/fonts/LICENSE-DejaVu.txt`), for platforms that
+//! do not display SVG previews. Rendered cards are kept in a small
+//! [`PngCardCache`].
+
+use super::{CardLayout, CardSize, CardTheme, LINE_HEIGHT};
+use crate::errors::AppError;
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use axum::body::Bytes;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// The bundled font file.
+static FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSerif.ttf");
+
+/// Smallest accepted card width or height in pixels.
+pub const MIN_DIMENSION: u32 = 200;
+/// Largest accepted card width or height in pixels.
+pub const MAX_DIMENSION: u32 = 2400;
+
+/// Number of rendered cards kept by default.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+fn font() -> &'static FontRef<'static> {
+    static FONT: OnceLock<FontRef<'static>> = OnceLock::new();
+    FONT.get_or_init(|| FontRef::try_from_slice(FONT_DATA).expect("bundled font is valid"))
+}
+
+/// Returns the glyph scale at which one em is `font_size` pixels.
+fn scale_for(font: &FontRef<'static>, font_size: f32) -> PxScale {
+    let units_per_em = font.units_per_em().unwrap_or(2048.0);
+    PxScale::from(font_size * font.height_unscaled() / units_per_em)
+}
+
+/// Measures `text` with the bundled font's advances and kerning.
+pub fn measure_text(text: &str, font_size: f32) -> f32 {
+    let font = font();
+    let scaled = font.as_scaled(scale_for(font, font_size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Builds a card size from `width`/`height` parameters, falling back to the
+/// given preset for whichever is missing.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] if either dimension is not a number in
+/// [`MIN_DIMENSION`]..=[`MAX_DIMENSION`].
+pub fn custom_size(
+    preset: CardSize,
+    width: Option<&str>,
+    height: Option<&str>,
+) -> Result<CardSize, AppError> {
+    let dimension = |name: &str, value: Option<&str>, default: u32| match value {
+        None => Ok(default),
+        Some(value) => value
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|v| (MIN_DIMENSION..=MAX_DIMENSION).contains(v))
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Card {} must be a whole number of pixels between {} and {}, got '{}'.",
+                    name, MIN_DIMENSION, MAX_DIMENSION, value
+                ))
+            }),
+    };
+    Ok(CardSize {
+        name: if width.is_some() || height.is_some() {
+            "custom"
+        } else {
+            preset.name
+        },
+        width: dimension("width", width, preset.width)?,
+        height: dimension("height", height, preset.height)?,
+    })
+}
+
+/// An RGB pixel buffer.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: [u8; 3]) -> Self {
+        let pixels = background
+            .iter()
+            .copied()
+            .cycle()
+            .take(width as usize * height as usize * 3)
+            .collect();
+        Canvas {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Blends `color` into the pixel at (`x`, `y`) with the given coverage.
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let coverage = coverage.clamp(0.0, 1.0);
+        let offset = (y as usize * self.width as usize + x as usize) * 3;
+        for (channel, &value) in self.pixels[offset..offset + 3].iter_mut().zip(&color) {
+            *channel = (*channel as f32 * (1.0 - coverage) + value as f32 * coverage).round() as u8;
+        }
+    }
+
+    fn fill_rect(&mut self, (x, y, w, h): (f32, f32, f32, f32), color: [u8; 3]) {
+        for py in y.round() as i64..(y + h).round() as i64 {
+            for px in x.round() as i64..(x + w).round() as i64 {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, baseline: f32, font_size: f32, color: [u8; 3]) {
+        let font = font();
+        let scaled = font.as_scaled(scale_for(font, font_size));
+        let mut caret = x;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(scaled.scale(), point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+            if let Some(outlined) = font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    self.blend(
+                        bounds.min.x as i64 + gx as i64,
+                        bounds.min.y as i64 + gy as i64,
+                        color,
+                        coverage,
+                    );
+                });
+            }
+        }
+    }
+}
+
+/// Parses a `#rrggbb` theme colour.
+fn parse_color(hex: &str) -> [u8; 3] {
+    let hex = hex.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .unwrap_or(0)
+    };
+    [channel(0), channel(2), channel(4)]
+}
+
+/// Rasterizes the card and encodes it as a PNG image.
+///
+/// # Errors
+///
+/// Returns an [`AppError::InternalServerError`] if PNG encoding fails.
+pub fn render_png(layout: &CardLayout) -> Result<Vec<u8>, AppError> {
+    let theme = layout.theme;
+    let mut canvas = Canvas::new(layout.width, layout.height, parse_color(theme.background));
+    canvas.fill_rect(layout.accent_bar, parse_color(theme.accent));
+    let foreground = parse_color(theme.foreground);
+    for (i, line) in layout.lines.iter().enumerate() {
+        let baseline = layout.first_baseline + i as f32 * layout.quote_font_size * LINE_HEIGHT;
+        canvas.draw_text(
+            line,
+            layout.text_x,
+            baseline,
+            layout.quote_font_size,
+            foreground,
+        );
+    }
+    canvas.draw_text(
+        &layout.author,
+        layout.text_x,
+        layout.author_baseline,
+        layout.author_font_size,
+        parse_color(theme.muted),
+    );
+
+    let mut output = Vec::new();
+    let mut encoder = png::Encoder::new(&mut output, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&canvas.pixels))
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode PNG card: {}", e)))?;
+    Ok(output)
+}
+
+/// Identifies a rendered card. The quote uid is included so that editing a
+/// quote's text or author never serves a stale image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CardCacheKey {
+    pub quote_id: u32,
+    pub quote_uid: String,
+    pub theme: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CardCacheKey {
+    /// Builds the key for a quote rendered with the given theme and size.
+    pub fn new(quote: &crate::models::Quote, theme: CardTheme, size: CardSize) -> Self {
+        CardCacheKey {
+            quote_id: quote.id,
+            quote_uid: quote.uid.clone(),
+            theme: theme.name,
+            width: size.width,
+            height: size.height,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    images: HashMap<CardCacheKey, Bytes>,
+    /// Insertion order, oldest first, used for eviction.
+    order: VecDeque<CardCacheKey>,
+}
+
+/// A bounded cache of rendered PNG cards, evicting the oldest entry when full.
+#[derive(Debug)]
+pub struct PngCardCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+impl Default for PngCardCache {
+    fn default() -> Self {
+        PngCardCache::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl PngCardCache {
+    /// Creates a cache holding at most `capacity` images.
+    pub fn new(capacity: usize) -> Self {
+        PngCardCache {
+            capacity,
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Returns the cached image for `key`, if any.
+    pub fn get(&self, key: &CardCacheKey) -> Option<Bytes> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.images.get(key).cloned()
+    }
+
+    /// Stores an image, evicting the oldest entries beyond capacity.
+    pub fn insert(&self, key: CardCacheKey, image: Bytes) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.images.insert(key.clone(), image).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.images.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the number of cached images.
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.images.len()
+    }
+
+    /// Returns `true` if no images are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{layout_card, SIZES, THEMES};
+    use crate::models::quote::fixtures::quote;
+
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[test]
+    fn test_measure_text_grows_with_text_and_size() {
+        let short = measure_text("Hello", 20.0);
+        assert!(short > 0.0);
+        assert!(measure_text("Hello, world", 20.0) > short);
+        assert!((measure_text("Hello", 40.0) - 2.0 * short).abs() < 0.5);
+        assert_eq!(measure_text("", 20.0), 0.0);
+    }
+
+    #[test]
+    fn test_custom_size_limits() {
+        let preset = SIZES[0];
+        assert_eq!(custom_size(preset, None, None).unwrap(), preset);
+        let size = custom_size(preset, Some("800"), None).unwrap();
+        assert_eq!((size.width, size.height), (800, preset.height));
+        match custom_size(preset, Some("100000000000"), None) {
+            Err(AppError::BadRequest(msg)) => assert!(msg.contains("width")),
+            _ => panic!("Expected BadRequest"),
+        }
+        assert!(matches!(
+            custom_size(preset, None, Some("10")),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_png_draws_text() {
+        let size = custom_size(SIZES[0], Some("400"), Some("300")).unwrap();
+        let layout = layout_card("Hello there.", "Tester", size, THEMES[0], &measure_text);
+        let png = render_png(&layout).unwrap();
+        assert!(png.starts_with(PNG_SIGNATURE));
+
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (400, 300));
+        // Some pixels must differ from the white background and the accent bar.
+        let background = parse_color(THEMES[0].background);
+        let accent = parse_color(THEMES[0].accent);
+        let drawn = pixels
+            .chunks(3)
+            .filter(|p| *p != background && *p != accent)
+            .count();
+        assert!(drawn > 100);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_entries() {
+        let cache = PngCardCache::new(2);
+        let key = |id| CardCacheKey::new(&quote(id, "Text", "Author", None), THEMES[0], SIZES[0]);
+        cache.insert(key(1), Bytes::from_static(b"one"));
+        cache.insert(key(2), Bytes::from_static(b"two"));
+        cache.insert(key(3), Bytes::from_static(b"three"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key(1)).is_none());
+        assert_eq!(cache.get(&key(3)).unwrap(), Bytes::from_static(b"three"));
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#3e7cb1"), [0x3e, 0x7c, 0xb1]);
+        assert_eq!(parse_color("bogus"), [0, 0, 0]);
+    }
+}
@@ -0,0 +1,105 @@
+//! # SVG Quote Cards
+//!
+//! Renders a [`CardLayout`] as a standalone SVG document. Only generic,
+//! locally installed font families are referenced, so viewers never fetch
+//! fonts or other resources.
+
+use super::CardLayout;
+use crate::utils::escape_xml;
+use std::fmt::Write;
+
+/// Font stack used for card text; resolved by the viewer from local fonts.
+pub const FONT_FAMILY: &str = "Georgia, 'Times New Roman', Times, serif";
+
+/// Renders the card as an SVG document. All text is XML-escaped.
+pub fn render_svg(layout: &CardLayout) -> String {
+    let theme = layout.theme;
+    let label = format!("{} {}", layout.lines.join(" "), layout.author);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="{label}">"#,
+        w = layout.width,
+        h = layout.height,
+        label = escape_xml(&label)
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        layout.width, layout.height, theme.background
+    );
+    let (x, y, w, h) = layout.accent_bar;
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+        x, y, w, h, theme.accent
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text font-family="{}" font-size="{:.1}" fill="{}">"#,
+        FONT_FAMILY, layout.quote_font_size, theme.foreground
+    );
+    for (i, line) in layout.lines.iter().enumerate() {
+        let baseline =
+            layout.first_baseline + i as f32 * layout.quote_font_size * super::LINE_HEIGHT;
+        let _ = writeln!(
+            svg,
+            r#"<tspan x="{:.1}" y="{:.1}">{}</tspan>"#,
+            layout.text_x,
+            baseline,
+            escape_xml(line)
+        );
+    }
+    svg.push_str("</text>\n");
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" font-family="{}" font-size="{:.1}" font-style="italic" fill="{}">{}</text>"#,
+        layout.text_x,
+        layout.author_baseline,
+        FONT_FAMILY,
+        layout.author_font_size,
+        theme.muted,
+        escape_xml(&layout.author)
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{approximate_text_width, layout_card, SIZES, THEMES};
+
+    #[test]
+    fn test_render_svg_escapes_text() {
+        let layout = layout_card(
+            "<script>alert('x')</script> & more",
+            "A \"quoted\" <author>",
+            SIZES[0],
+            THEMES[1],
+            &approximate_text_width,
+        );
+        let svg = render_svg(&layout);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(!svg.contains("<script>"));
+        assert!(!svg.contains("<author>"));
+        assert!(svg.contains("&lt;script&gt;"));
+        assert!(svg.contains("A &quot;quoted&quot; &lt;author&gt;"));
+        assert!(svg.contains(THEMES[1].background));
+        assert!(!svg.contains("href") && !svg.contains("url("));
+    }
+
+    #[test]
+    fn test_render_svg_has_one_tspan_per_line() {
+        let layout = layout_card(
+            &"long words here ".repeat(30),
+            "Author",
+            SIZES[1],
+            THEMES[0],
+            &approximate_text_width,
+        );
+        let svg = render_svg(&layout);
+        assert_eq!(svg.matches("<tspan").count(), layout.lines.len());
+        assert!(svg.contains(r#"width="1080" height="1080""#));
+    }
+}
@@ -17,7 +17,7 @@ use thiserror::Error;
 // For example, if responses is a sibling module: use crate::responses::ErrorResponse;
 // Assuming responses.rs is in the same module or crate root for now.
 use crate::responses::ErrorResponse;
-use crate::services::quote_service::QuoteServiceError; // Added import
+use crate::services::quote_service::{QuoteLookupError, QuoteServiceError}; // Added import
 
 /// Represents all possible errors that can occur within the application.
 ///
@@ -45,29 +45,106 @@ pub enum AppError {
     /// Contains a message describing the sourcing issue.
     #[error("Quote Sourcing Error: {0}")]
     QuoteSourcingError(String),
+
+    /// Represents a request that lacks the credentials required for an operation.
+    /// Contains a message describing what was refused.
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// Represents a request for a representation the service cannot produce.
+    /// Contains a message listing the supported representations.
+    #[error("Not Acceptable: {0}")]
+    NotAcceptable(String),
+
+    /// Represents a resource that existed but has been deliberately removed.
+    /// Contains a message describing what is gone.
+    #[error("Gone: {0}")]
+    Gone(String),
+
+    /// Represents a request the service cannot take on right now, e.g. because
+    /// a connection limit has been reached.
+    /// Contains a message describing which capacity is exhausted.
+    #[error("Service Unavailable: {0}")]
+    ServiceUnavailable(String),
     // Add other specific error types as needed
 }
 
+impl AppError {
+    /// Returns the machine-readable code reported alongside the message.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AppError::InternalServerError(_) => "INTERNAL_SERVER_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::QuoteSourcingError(_) => "QUOTE_SOURCING_ERROR",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::NotAcceptable(_) => "NOT_ACCEPTABLE",
+            AppError::Gone(_) => "GONE",
+            AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+        }
+    }
+
+    /// Returns the human-readable message, without the variant prefix.
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::InternalServerError(msg)
+            | AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::QuoteSourcingError(msg)
+            | AppError::Forbidden(msg)
+            | AppError::NotAcceptable(msg)
+            | AppError::Gone(msg)
+            | AppError::ServiceUnavailable(msg) => msg,
+        }
+    }
+
+    /// Builds the error reported when the quote addressed by `id` cannot be
+    /// served, logging why.
+    pub fn from_quote_lookup(id: &str, error: QuoteLookupError) -> Self {
+        match error {
+            QuoteLookupError::Removed => {
+                tracing::info!("Quote with ID: {} was removed from the data file.", id);
+                AppError::Gone(format!("Quote with ID: {} has been removed.", id))
+            }
+            QuoteLookupError::NoQuotes => {
+                tracing::warn!(
+                    "No quotes available in the data file when searching for ID: {}",
+                    id
+                );
+                AppError::NotFound(format!(
+                    "No quotes available in the data file. Cannot find quote with ID: {}.",
+                    id
+                ))
+            }
+            QuoteLookupError::NotFound => {
+                tracing::info!("Quote with ID: {} not found or not published.", id);
+                AppError::NotFound(format!("Quote with ID: {} not found.", id))
+            }
+            QuoteLookupError::Deleted => {
+                tracing::info!("Quote with ID: {} is marked as deleted.", id);
+                AppError::Gone(format!("Quote with ID: {} has been deleted.", id))
+            }
+        }
+    }
+}
+
 /// Converts an [`AppError`] into an Axum `Response`.
 ///
 /// This implementation maps each variant of `AppError` to an appropriate
 /// HTTP status code and a JSON error response body.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_code, message) = match self {
-            AppError::InternalServerError(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "INTERNAL_SERVER_ERROR".to_string(),
-                msg,
-            ),
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND".to_string(), msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST".to_string(), msg),
-            AppError::QuoteSourcingError(msg) => (
-                // Could be 500 or a more specific client error depending on context
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "QUOTE_SOURCING_ERROR".to_string(),
-                msg,
-            ),
+        let error_code = self.error_code().to_string();
+        let (status, message) = match self {
+            AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            // Could be 500 or a more specific client error depending on context
+            AppError::QuoteSourcingError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::NotAcceptable(msg) => (StatusCode::NOT_ACCEPTABLE, msg),
+            AppError::Gone(msg) => (StatusCode::GONE, msg),
+            AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
         };
 
         let body = Json(ErrorResponse {
@@ -112,6 +189,9 @@ impl From<QuoteServiceError> for AppError {
             QuoteServiceError::ParseError(parse_err) => {
                 AppError::QuoteSourcingError(format!("Error parsing quote data: {}", parse_err))
             }
+            err @ QuoteServiceError::TooManyDuplicates { .. } => {
+                AppError::QuoteSourcingError(err.to_string())
+            }
         }
     }
 }
@@ -194,6 +274,78 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_app_error_forbidden_into_response() {
+        let error = AppError::Forbidden("Admin token required".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let expected_json = json!({
+            "error_code": "FORBIDDEN",
+            "message": "Admin token required"
+        });
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            expected_json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_error_not_acceptable_into_response() {
+        let error = AppError::NotAcceptable("Unsupported format".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let expected_json = json!({
+            "error_code": "NOT_ACCEPTABLE",
+            "message": "Unsupported format"
+        });
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            expected_json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_error_gone_into_response() {
+        let error = AppError::Gone("Quote was deleted".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::GONE);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let expected_json = json!({
+            "error_code": "GONE",
+            "message": "Quote was deleted"
+        });
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            expected_json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_error_service_unavailable_into_response() {
+        let error = AppError::ServiceUnavailable("Too many streams".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let expected_json = json!({
+            "error_code": "SERVICE_UNAVAILABLE",
+            "message": "Too many streams"
+        });
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            expected_json
+        );
+    }
+
     #[test]
     fn test_from_io_error_for_app_error() {
         let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
@@ -268,5 +420,17 @@ mod tests {
             AppError::QuoteSourcingError("file access".to_string()).to_string(),
             "Quote Sourcing Error: file access"
         );
+        assert_eq!(
+            AppError::Forbidden("preview".to_string()).to_string(),
+            "Forbidden: preview"
+        );
+        assert_eq!(
+            AppError::NotAcceptable("image/png".to_string()).to_string(),
+            "Not Acceptable: image/png"
+        );
+        assert_eq!(
+            AppError::Gone("quote 7".to_string()).to_string(),
+            "Gone: quote 7"
+        );
     }
 }
@@ -0,0 +1,106 @@
+//! # Atom Serialization
+//!
+//! Serializes a [`Feed`] as an Atom (RFC 4287) document, with RFC 3339 dates,
+//! a `self` link and per-entry authors.
+
+use super::Feed;
+use crate::utils::escape_xml;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fmt::Write;
+
+/// Content type for Atom documents.
+pub const CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+fn atom_date(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Renders the feed as an Atom document.
+pub fn render_atom(feed: &Feed) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(xml, "<id>{}</id>", escape_xml(&feed.id));
+    let _ = writeln!(xml, "<title>{}</title>", escape_xml(&feed.title));
+    let _ = writeln!(
+        xml,
+        "<subtitle>{}</subtitle>",
+        escape_xml(&feed.description)
+    );
+    let _ = writeln!(xml, "<updated>{}</updated>", atom_date(feed.updated));
+    let _ = writeln!(
+        xml,
+        "<link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>",
+        escape_xml(&feed.self_link)
+    );
+    let _ = writeln!(
+        xml,
+        "<link rel=\"alternate\" href=\"{}\"/>",
+        escape_xml(&feed.site_link)
+    );
+    // Feed-level author, required when the feed has no entries.
+    xml.push_str("<author><name>RustQuote</name></author>\n");
+    for entry in &feed.entries {
+        xml.push_str("<entry>\n");
+        let _ = writeln!(xml, "<id>{}</id>", escape_xml(&entry.id));
+        let _ = writeln!(xml, "<title>{}</title>", escape_xml(&entry.title));
+        let _ = writeln!(xml, "<updated>{}</updated>", atom_date(entry.updated));
+        let _ = writeln!(xml, "<published>{}</published>", atom_date(entry.published));
+        let _ = writeln!(
+            xml,
+            "<author><name>{}</name></author>",
+            escape_xml(&entry.author)
+        );
+        let _ = writeln!(
+            xml,
+            "<link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>",
+            escape_xml(&entry.link)
+        );
+        let _ = writeln!(
+            xml,
+            "<content type=\"text\">{}</content>",
+            escape_xml(&entry.content)
+        );
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feeds::FeedEntry;
+
+    #[test]
+    fn test_render_atom_required_elements() {
+        let at = "2025-06-10T12:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let feed = Feed {
+            id: "urn:uuid:feed".to_string(),
+            title: "New".to_string(),
+            description: "Fresh quotes".to_string(),
+            self_link: "https://q.example/feeds/new.atom".to_string(),
+            site_link: "https://q.example/".to_string(),
+            updated: at,
+            entries: vec![FeedEntry {
+                id: "urn:uuid:entry".to_string(),
+                title: "New quote by \"Q\"".to_string(),
+                content: "Less is <more>".to_string(),
+                author: "A & B".to_string(),
+                link: "https://q.example/api/v1/quote/1?format=html".to_string(),
+                published: at,
+                updated: at,
+            }],
+        };
+        let xml = render_atom(&feed);
+        assert!(xml.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(xml.contains("<id>urn:uuid:feed</id>"));
+        assert!(xml.contains("<updated>2025-06-10T12:30:00Z</updated>"));
+        assert!(xml.contains(
+            "<link rel=\"self\" type=\"application/atom+xml\" href=\"https://q.example/feeds/new.atom\"/>"
+        ));
+        assert!(xml.contains("<title>New quote by &quot;Q&quot;</title>"));
+        assert!(xml.contains("<author><name>A &amp; B</name></author>"));
+        assert!(xml.contains("<content type=\"text\">Less is &lt;more&gt;</content>"));
+        assert!(xml.contains("<published>2025-06-10T12:30:00Z</published>"));
+    }
+}
@@ -0,0 +1,240 @@
+//! # iCalendar Serialization
+//!
+//! Builds an iCalendar (RFC 5545) document with one all-day event per upcoming
+//! day, each carrying that day's quote. Event UIDs depend only on the date,
+//! not on the host the calendar was fetched through, so calendar clients
+//! update an existing event when the pick for a day changes instead of adding
+//! a duplicate.
+
+use crate::services::quote_service;
+use crate::services::quote_store::Corpus;
+use crate::utils::name_uuid;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use std::fmt::Write;
+
+/// Content type for iCalendar documents.
+pub const CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+/// Default number of days, starting today, covered by the calendar.
+pub const DEFAULT_CALENDAR_DAYS: u32 = 30;
+
+/// Largest accepted calendar horizon in days.
+pub const MAX_CALENDAR_DAYS: u32 = 366;
+
+/// Maximum content line length in octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Escapes a TEXT property value.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c if c.is_control() && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folding it at [`MAX_LINE_OCTETS`] without splitting
+/// UTF-8 sequences, and terminates it with CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit.
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn ical_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn ical_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Renders the quote of the day calendar for `days` days starting on `today`.
+///
+/// Quotes that are scheduled but not yet published at `now` are left out, so
+/// the calendar never reveals them early; otherwise each day uses the same
+/// pick as [`quote_service::get_quote_of_the_day`]. `DTSTAMP` and
+/// `LAST-MODIFIED` are the corpus load time, so unchanged data yields a
+/// byte-identical calendar.
+pub fn render_daily_calendar(
+    corpus: &Corpus,
+    today: NaiveDate,
+    days: u32,
+    now: DateTime<Utc>,
+    base_url: &str,
+) -> String {
+    let published: Vec<_> = corpus
+        .quotes
+        .iter()
+        .filter(|q| q.publish_at.is_none_or(|p| p <= now))
+        .collect();
+    let stamp = ical_timestamp(corpus.loaded_at);
+
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//RustQuote//Quote of the Day//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, "METHOD:PUBLISH");
+    push_line(&mut ics, "X-WR-CALNAME:RustQuote: Quote of the Day");
+    for offset in 0..days {
+        let Some(date) = today.checked_add_days(Days::new(offset.into())) else {
+            break;
+        };
+        let Some(quote) =
+            quote_service::get_quote_of_the_day_among(published.iter().copied(), date)
+        else {
+            continue;
+        };
+        let Some(next_day) = date.succ_opt() else {
+            break;
+        };
+        let uid = name_uuid(&format!("rustquote:daily:{}", date));
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:{}@rustquote", uid));
+        push_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut ics, &format!("LAST-MODIFIED:{}", stamp));
+        push_line(&mut ics, &format!("DTSTART;VALUE=DATE:{}", ical_date(date)));
+        push_line(
+            &mut ics,
+            &format!("DTEND;VALUE=DATE:{}", ical_date(next_day)),
+        );
+        push_line(
+            &mut ics,
+            &format!(
+                "SUMMARY:{}",
+                escape_text(&format!(
+                    "\u{201c}{}\u{201d} \u{2014} {}",
+                    quote.text, quote.author
+                ))
+            ),
+        );
+        let mut description = format!("{}\n\u{2014} {}", quote.text, quote.author);
+        if let Some(source) = quote.source.as_deref().filter(|s| !s.is_empty()) {
+            let _ = write!(description, ", {}", source);
+        }
+        push_line(
+            &mut ics,
+            &format!("DESCRIPTION:{}", escape_text(&description)),
+        );
+        push_line(
+            &mut ics,
+            &format!("URL:{}/api/v1/quote/{}?format=html", base_url, quote.id),
+        );
+        push_line(&mut ics, "TRANSP:TRANSPARENT");
+        push_line(&mut ics, "END:VEVENT");
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::Quote;
+
+    const BASE: &str = "https://quotes.example";
+
+    fn corpus(quotes: Vec<Quote>) -> Corpus {
+        let loaded = "2025-06-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        Corpus::from_load(None, quotes, loaded)
+    }
+
+    fn events(ics: &str) -> Vec<&str> {
+        ics.split("BEGIN:VEVENT\r\n").skip(1).collect()
+    }
+
+    #[test]
+    fn test_calendar_has_one_all_day_event_per_day() {
+        let corpus = corpus(vec![
+            quote(1, "One; two, three", "A", None),
+            quote(2, "Four", "B", None),
+        ]);
+        let today = "2025-06-10".parse::<NaiveDate>().unwrap();
+        let ics = render_daily_calendar(&corpus, today, 3, corpus.loaded_at, BASE);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+        let events = events(&ics);
+        assert_eq!(events.len(), 3);
+        assert!(events[0].contains("DTSTART;VALUE=DATE:20250610\r\n"));
+        assert!(events[0].contains("DTEND;VALUE=DATE:20250611\r\n"));
+        assert!(events[2].contains("DTSTART;VALUE=DATE:20250612\r\n"));
+        assert!(events[0].contains("DTSTAMP:20250610T080000Z\r\n"));
+    }
+
+    #[test]
+    fn test_calendar_uids_are_stable_per_date() {
+        let corpus = corpus(vec![quote(1, "One", "A", None), quote(2, "Two", "B", None)]);
+        let today = "2025-06-10".parse::<NaiveDate>().unwrap();
+        let uid = |ics: &str, i: usize| {
+            events(ics)[i]
+                .lines()
+                .find(|l| l.starts_with("UID:"))
+                .unwrap()
+                .to_string()
+        };
+        let first = render_daily_calendar(&corpus, today, 2, corpus.loaded_at, BASE);
+        let next = render_daily_calendar(
+            &corpus,
+            today.succ_opt().unwrap(),
+            2,
+            corpus.loaded_at,
+            BASE,
+        );
+        assert_eq!(uid(&first, 1), uid(&next, 0));
+        assert_ne!(uid(&first, 0), uid(&first, 1));
+        let other_host =
+            render_daily_calendar(&corpus, today, 2, corpus.loaded_at, "http://localhost:3000");
+        assert_eq!(uid(&other_host, 0), uid(&first, 0));
+        assert_eq!(
+            first,
+            render_daily_calendar(&corpus, today, 2, corpus.loaded_at, BASE)
+        );
+    }
+
+    #[test]
+    fn test_calendar_hides_unpublished_quotes() {
+        let corpus = corpus(vec![Quote {
+            publish_at: Some("2025-06-11T00:00:00Z".parse().unwrap()),
+            ..quote(1, "Scheduled", "A", None)
+        }]);
+        let today = "2025-06-10".parse::<NaiveDate>().unwrap();
+        let ics = render_daily_calendar(&corpus, today, 5, corpus.loaded_at, BASE);
+        assert!(events(&ics).is_empty());
+        let later = "2025-06-11T01:00:00Z".parse().unwrap();
+        let ics = render_daily_calendar(&corpus, today, 5, later, BASE);
+        assert_eq!(events(&ics).len(), 4);
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape_text("a;b,c\\d\ne"), "a\\;b\\,c\\\\d\\ne");
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60)));
+        for line in out.trim_end_matches("\r\n").split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(
+            out.replace("\r\n ", "").trim_end(),
+            format!("SUMMARY:{}", "é".repeat(60))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! # Feeds
+//!
+//! This module builds syndication feeds over the quote corpus: the quote of
+//! the day and the quotes that appeared in the data file since its history
+//! was started. Feeds are assembled as a format-neutral [`Feed`] and then
+//! serialized by the `rss` and `atom` submodules; the `ical` submodule
+//! renders upcoming quotes of the day as a calendar.
+
+pub mod atom;
+pub mod ical;
+pub mod rss;
+
+use crate::models::Quote;
+use crate::services::quote_service;
+use crate::services::quote_store::Corpus;
+use crate::utils::name_uuid;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+/// Number of days, ending today, carried by the daily feeds.
+pub const DAILY_FEED_DAYS: u64 = 7;
+
+/// Maximum number of entries in the new quotes feed.
+pub const NEW_FEED_LIMIT: usize = 50;
+
+/// A format-neutral feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    /// Stable feed identifier (a `urn:uuid:` IRI).
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Absolute URL of the feed document itself.
+    pub self_link: String,
+    /// Absolute URL of the site the feed belongs to.
+    pub site_link: String,
+    /// When the feed content last changed.
+    pub updated: DateTime<Utc>,
+    /// Entries, newest first.
+    pub entries: Vec<FeedEntry>,
+}
+
+/// One feed item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEntry {
+    /// Stable entry identifier (a `urn:uuid:` IRI).
+    pub id: String,
+    pub title: String,
+    /// Plain-text quote and attribution.
+    pub content: String,
+    pub author: String,
+    /// Absolute URL of the quote.
+    pub link: String,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// Returns the absolute URL of the HTML rendering of a quote.
+fn quote_link(base_url: &str, quote: &Quote) -> String {
+    format!("{}/api/v1/quote/{}?format=html", base_url, quote.id)
+}
+
+fn entry_content(quote: &Quote) -> String {
+    format!("\u{201c}{}\u{201d} \u{2014} {}", quote.text, quote.author)
+}
+
+/// Builds the quote of the day feed: one entry per UTC day for the
+/// [`DAILY_FEED_DAYS`] days ending on `today`, newest first.
+///
+/// Entry IDs are derived from the date and the picked quote's uid, so they are
+/// stable for as long as the pick for that day does not change.
+pub fn daily_feed(corpus: &Corpus, today: NaiveDate, base_url: &str, self_link: &str) -> Feed {
+    let entries: Vec<FeedEntry> = (0..DAILY_FEED_DAYS)
+        .filter_map(|offset| today.checked_sub_days(Days::new(offset)))
+        .filter_map(|date| {
+            let quote = quote_service::get_quote_of_the_day(&corpus.quotes, date)?;
+            let day_start = date.and_hms_opt(0, 0, 0)?.and_utc();
+            Some(FeedEntry {
+                id: format!(
+                    "urn:uuid:{}",
+                    name_uuid(&format!("rustquote:daily:{}:{}", date, quote.uid))
+                ),
+                title: format!("Quote of the day for {}: {}", date, quote.author),
+                content: entry_content(quote),
+                author: quote.author.clone(),
+                link: quote_link(base_url, quote),
+                published: day_start,
+                updated: day_start,
+            })
+        })
+        .collect();
+    let updated = entries
+        .first()
+        .map(|e| e.updated)
+        .unwrap_or(corpus.loaded_at);
+    Feed {
+        id: format!("urn:uuid:{}", name_uuid("rustquote:feed:daily")),
+        title: "RustQuote: Quote of the Day".to_string(),
+        description: "A quote picked from the RustQuote corpus every day.".to_string(),
+        self_link: self_link.to_string(),
+        site_link: format!("{}/", base_url),
+        updated,
+        entries,
+    }
+}
+
+/// Builds the new quotes feed from the `Created` revisions recorded by
+/// reloads, newest first and at most [`NEW_FEED_LIMIT`] entries.
+///
+/// Quotes that have since been removed, or that are not active and published
+/// at `now`, are left out. The very first load of a data file records no
+/// revisions, so its quotes are not in the feed. Entry IDs are derived from
+/// when the quote was picked up and its uid.
+pub fn new_quotes_feed(
+    corpus: &Corpus,
+    now: DateTime<Utc>,
+    base_url: &str,
+    self_link: &str,
+) -> Feed {
+    let entries: Vec<FeedEntry> = corpus
+        .created_revisions()
+        .into_iter()
+        .filter_map(|revision| {
+            let id = revision.after.as_ref()?.id;
+            let quote = quote_service::get_quote_by_id(&corpus.quotes, id)?;
+            if !(quote.status.is_active() && quote.is_published_at(now)) {
+                return None;
+            }
+            Some(FeedEntry {
+                id: format!(
+                    "urn:uuid:{}",
+                    name_uuid(&format!(
+                        "rustquote:new:{}:{}",
+                        revision.changed_at.to_rfc3339(),
+                        quote.uid
+                    ))
+                ),
+                title: format!("New quote by {}", quote.author),
+                content: entry_content(quote),
+                author: quote.author.clone(),
+                link: quote_link(base_url, quote),
+                published: revision.changed_at,
+                updated: quote.updated_at.unwrap_or(revision.changed_at),
+            })
+        })
+        .take(NEW_FEED_LIMIT)
+        .collect();
+    let updated = entries
+        .iter()
+        .map(|e| e.updated)
+        .max()
+        .unwrap_or(corpus.loaded_at);
+    Feed {
+        id: format!("urn:uuid:{}", name_uuid("rustquote:feed:new")),
+        title: "RustQuote: New Quotes".to_string(),
+        description: "Quotes recently added to the RustQuote corpus.".to_string(),
+        self_link: self_link.to_string(),
+        site_link: format!("{}/", base_url),
+        updated,
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::services::quote_service::assign_identifiers;
+
+    const BASE: &str = "https://quotes.example";
+
+    fn corpus(ids: &[u32], previous: Option<&Corpus>, now: DateTime<Utc>) -> Corpus {
+        let mut quotes: Vec<Quote> = ids
+            .iter()
+            .map(|&id| quote(id, &format!("Quote {}", id), "Author", None))
+            .collect();
+        assign_identifiers(&mut quotes);
+        Corpus::from_load(previous, quotes, now)
+    }
+
+    #[test]
+    fn test_daily_feed_has_one_stable_entry_per_day() {
+        let now = "2025-06-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let corpus = corpus(&[1, 2, 3], None, now);
+        let today = now.date_naive();
+        let feed = daily_feed(
+            &corpus,
+            today,
+            BASE,
+            "https://quotes.example/feeds/daily.rss",
+        );
+        assert_eq!(feed.entries.len(), DAILY_FEED_DAYS as usize);
+        assert!(feed.entries[0].title.contains("2025-06-10"));
+        assert!(feed.entries[1].title.contains("2025-06-09"));
+        assert_eq!(
+            feed.entries[0].published,
+            "2025-06-10T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(feed.updated, feed.entries[0].updated);
+        assert!(feed.entries[0].link.starts_with(BASE));
+
+        let again = daily_feed(
+            &corpus,
+            today,
+            BASE,
+            "https://quotes.example/feeds/daily.rss",
+        );
+        assert_eq!(feed, again);
+        // Yesterday's entry keeps its id when the feed moves on a day.
+        let tomorrow = daily_feed(
+            &corpus,
+            today.succ_opt().unwrap(),
+            BASE,
+            "https://quotes.example/feeds/daily.rss",
+        );
+        assert_eq!(tomorrow.entries[1].id, feed.entries[0].id);
+    }
+
+    #[test]
+    fn test_daily_feed_empty_corpus() {
+        let now = Utc::now();
+        let corpus = corpus(&[], None, now);
+        let feed = daily_feed(&corpus, now.date_naive(), BASE, BASE);
+        assert!(feed.entries.is_empty());
+        assert_eq!(feed.updated, corpus.loaded_at);
+    }
+
+    #[test]
+    fn test_new_quotes_feed_lists_created_quotes() {
+        let first = "2025-06-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let second = "2025-06-11T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let v1 = corpus(&[1], None, first);
+        assert!(new_quotes_feed(&v1, first, BASE, BASE).entries.is_empty());
+
+        let v2 = corpus(&[1, 2, 3], Some(&v1), second);
+        let feed = new_quotes_feed(&v2, second, BASE, BASE);
+        let links: Vec<&str> = feed.entries.iter().map(|e| e.link.as_str()).collect();
+        assert_eq!(
+            links,
+            vec![
+                "https://quotes.example/api/v1/quote/3?format=html",
+                "https://quotes.example/api/v1/quote/2?format=html"
+            ]
+        );
+        assert_eq!(feed.entries[0].published, second);
+        assert_eq!(feed.updated, second);
+
+        // Ids do not depend on the host the feed was requested through.
+        let other_host = new_quotes_feed(&v2, second, "http://localhost:3000", BASE);
+        assert_eq!(other_host.id, feed.id);
+        assert_eq!(other_host.entries[0].id, feed.entries[0].id);
+        assert_ne!(feed.entries[0].id, feed.entries[1].id);
+
+        // Removed quotes drop out of the feed.
+        let v3 = corpus(&[1, 3], Some(&v2), second);
+        assert_eq!(new_quotes_feed(&v3, second, BASE, BASE).entries.len(), 1);
+    }
+}
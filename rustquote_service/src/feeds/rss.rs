@@ -0,0 +1,93 @@
+//! # RSS 2.0 Serialization
+//!
+//! Serializes a [`Feed`] as an RSS 2.0 document. Entry IDs are emitted as
+//! non-permalink GUIDs and dates in RFC 822 format, as the specification
+//! requires. An `atom:link rel="self"` is included, as recommended by feed
+//! validators.
+
+use super::Feed;
+use crate::utils::escape_xml;
+use std::fmt::Write;
+
+/// Content type for RSS documents.
+pub const CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+
+/// Renders the feed as an RSS 2.0 document.
+pub fn render_rss(feed: &Feed) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+    let _ = writeln!(xml, "<title>{}</title>", escape_xml(&feed.title));
+    let _ = writeln!(xml, "<link>{}</link>", escape_xml(&feed.site_link));
+    let _ = writeln!(
+        xml,
+        "<description>{}</description>",
+        escape_xml(&feed.description)
+    );
+    let _ = writeln!(
+        xml,
+        "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>",
+        escape_xml(&feed.self_link)
+    );
+    let _ = writeln!(
+        xml,
+        "<lastBuildDate>{}</lastBuildDate>",
+        feed.updated.to_rfc2822()
+    );
+    for entry in &feed.entries {
+        xml.push_str("<item>\n");
+        let _ = writeln!(xml, "<title>{}</title>", escape_xml(&entry.title));
+        let _ = writeln!(xml, "<link>{}</link>", escape_xml(&entry.link));
+        let _ = writeln!(
+            xml,
+            "<description>{}</description>",
+            escape_xml(&entry.content)
+        );
+        let _ = writeln!(
+            xml,
+            "<guid isPermaLink=\"false\">{}</guid>",
+            escape_xml(&entry.id)
+        );
+        let _ = writeln!(xml, "<pubDate>{}</pubDate>", entry.published.to_rfc2822());
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feeds::FeedEntry;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn test_render_rss_escapes_and_formats_dates() {
+        let at = "2025-06-10T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let feed = Feed {
+            id: "urn:uuid:feed".to_string(),
+            title: "Quotes & more".to_string(),
+            description: "<daily>".to_string(),
+            self_link: "https://q.example/feeds/daily.rss".to_string(),
+            site_link: "https://q.example/".to_string(),
+            updated: at,
+            entries: vec![FeedEntry {
+                id: "urn:uuid:entry".to_string(),
+                title: "Day".to_string(),
+                content: "\u{201c}1 < 2 & 3 > 2\u{201d} \u{2014} Math".to_string(),
+                author: "Math".to_string(),
+                link: "https://q.example/api/v1/quote/1?format=html&x=1".to_string(),
+                published: at,
+                updated: at,
+            }],
+        };
+        let xml = render_rss(&feed);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\""));
+        assert!(xml.contains("<title>Quotes &amp; more</title>"));
+        assert!(xml.contains("<description>&lt;daily&gt;</description>"));
+        assert!(xml.contains("1 &lt; 2 &amp; 3 &gt; 2"));
+        assert!(xml.contains("format=html&amp;x=1"));
+        assert!(xml.contains("<guid isPermaLink=\"false\">urn:uuid:entry</guid>"));
+        assert!(xml.contains("<pubDate>Tue, 10 Jun 2025 00:00:00 +0000</pubDate>"));
+        assert_eq!(xml.matches("<item>").count(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! # Sparse Fieldsets
+//!
+//! Lets clients choose which quote fields a response carries with the
+//! `fields` query parameter, e.g. `?fields=quote,author`. Without it, the v1
+//! quote endpoints keep serving [`QuoteResponse`](crate::responses::QuoteResponse).
+//!
+//! Selected fields are always present, in the order they were requested,
+//! with `null` for unset optional values.
+
+use crate::errors::AppError;
+use crate::models::Quote;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::IntoParams;
+
+/// Every field that can be selected, in their canonical order.
+pub const QUOTE_FIELDS: &[&str] = &[
+    "id",
+    "uid",
+    "slug",
+    "quote",
+    "author",
+    "source",
+    "status",
+    "publish_at",
+    "expire_at",
+    "created_at",
+    "updated_at",
+];
+
+/// The `fields` query parameter of the v1 quote endpoints.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQueryParams {
+    /// Comma-separated quote fields to include, e.g. `quote,author`. One of
+    /// id, uid, slug, quote, author, source, status, publish_at, expire_at,
+    /// created_at, updated_at.
+    pub fields: Option<String>,
+}
+
+/// A validated selection of quote fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSet(Vec<&'static str>);
+
+impl FieldSet {
+    /// Parses a comma-separated `fields` value. Names are trimmed and
+    /// case-insensitive; repeats are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::BadRequest`] listing the valid fields if a name
+    /// is unknown or no field is named.
+    pub fn parse(value: &str) -> Result<Self, AppError> {
+        let mut fields = Vec::new();
+        for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let field = QUOTE_FIELDS
+                .iter()
+                .find(|f| f.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Unknown field '{}' in fields. Valid fields: {}.",
+                        name,
+                        QUOTE_FIELDS.join(", ")
+                    ))
+                })?;
+            if !fields.contains(field) {
+                fields.push(*field);
+            }
+        }
+        if fields.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "The fields parameter must name at least one field. Valid fields: {}.",
+                QUOTE_FIELDS.join(", ")
+            )));
+        }
+        Ok(FieldSet(fields))
+    }
+
+    /// Parses the optional `fields` parameter; `None` when it was not given.
+    ///
+    /// # Errors
+    ///
+    /// See [`FieldSet::parse`].
+    pub fn from_params(params: &FieldsQueryParams) -> Result<Option<Self>, AppError> {
+        params.fields.as_deref().map(FieldSet::parse).transpose()
+    }
+
+    /// The selected field names, in order.
+    pub fn names(&self) -> &[&'static str] {
+        &self.0
+    }
+
+    /// Serializes the selected fields of `quote` as a JSON object.
+    pub fn project(&self, quote: &Quote) -> Value {
+        let object: Map<String, Value> = self
+            .0
+            .iter()
+            .map(|name| (name.to_string(), field_value(quote, name)))
+            .collect();
+        Value::Object(object)
+    }
+}
+
+fn field_value(quote: &Quote, name: &str) -> Value {
+    // Formatted as serde formats them elsewhere in the API.
+    let instant = |at: Option<DateTime<Utc>>| serde_json::to_value(at).unwrap_or(Value::Null);
+    match name {
+        "id" => Value::from(quote.id),
+        "uid" => Value::from(quote.uid.clone()),
+        "slug" => Value::from(quote.slug.clone()),
+        "quote" => Value::from(quote.text.clone()),
+        "author" => Value::from(quote.author.clone()),
+        "source" => quote.source.clone().map_or(Value::Null, Value::from),
+        "status" => serde_json::to_value(quote.status).unwrap_or(Value::Null),
+        "publish_at" => instant(quote.publish_at),
+        "expire_at" => instant(quote.expire_at),
+        "created_at" => instant(quote.created_at),
+        "updated_at" => instant(quote.updated_at),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_validates_and_dedups() {
+        let set = FieldSet::parse(" Author, quote,author ,").unwrap();
+        assert_eq!(set.names(), &["author", "quote"]);
+
+        let Err(AppError::BadRequest(message)) = FieldSet::parse("quote,mood") else {
+            panic!("expected a bad request");
+        };
+        assert!(message.contains("'mood'"));
+        assert!(message.contains(&QUOTE_FIELDS.join(", ")));
+        assert!(matches!(
+            FieldSet::parse(" , "),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_project_every_field() {
+        let quote = Quote {
+            uid: "q-1".to_string(),
+            slug: "stay-hungry".to_string(),
+            ..quote(1, "Stay hungry.", "Steve Jobs", None)
+        };
+        let set = FieldSet::parse(&QUOTE_FIELDS.join(",")).unwrap();
+        assert_eq!(
+            set.project(&quote),
+            json!({
+                "id": 1,
+                "uid": "q-1",
+                "slug": "stay-hungry",
+                "quote": "Stay hungry.",
+                "author": "Steve Jobs",
+                "source": null,
+                "status": "active",
+                "publish_at": null,
+                "expire_at": null,
+                "created_at": null,
+                "updated_at": null,
+            })
+        );
+        // Requested order is kept.
+        let set = FieldSet::parse("author,id").unwrap();
+        assert_eq!(
+            serde_json::to_string(&set.project(&quote)).unwrap(),
+            r#"{"author":"Steve Jobs","id":1}"#
+        );
+    }
+}
@@ -0,0 +1,611 @@
+//! # Filter Queries
+//!
+//! A small query language for narrowing down quotes, passed in the `filter`
+//! query parameter, e.g.
+//! `author:"Steve Jobs" AND len<80 AND NOT source:null`.
+//!
+//! A filter combines comparisons with `AND`, `OR`, `NOT` and parentheses;
+//! `NOT` binds tightest and `AND` binds tighter than `OR`. Keywords are
+//! case-insensitive. A comparison is a field, an operator and a value:
+//!
+//! * Text fields (`author`, `quote` or `text`, `source`, `uid`, `slug`)
+//!   accept `:` (contains), `=` and `!=`. Matching ignores case and accents.
+//!   The bare value `null` matches a missing or blank field.
+//! * Number fields (`id`, `len`, the quote length in characters) accept
+//!   `:` or `=`, `!=`, `<`, `<=`, `>` and `>=`.
+//!
+//! Values with spaces or operator characters are written in double quotes,
+//! with `\"` and `\\` as escapes. Syntax errors report the 1-based character
+//! position they were found at.
+
+use crate::errors::AppError;
+use crate::models::Quote;
+use crate::utils::match_key;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Longest accepted filter, in characters.
+pub const MAX_FILTER_LEN: usize = 1000;
+
+/// Deepest accepted nesting of parentheses and `NOT`s.
+const MAX_DEPTH: usize = 32;
+
+/// Fields a filter can compare.
+const FIELDS: &[&str] = &[
+    "id", "author", "quote", "text", "source", "len", "uid", "slug",
+];
+
+/// The `filter` query parameter.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FilterQueryParams {
+    /// A filter expression, e.g. `author:"Steve Jobs" AND len<80 AND NOT source:null`.
+    /// Fields: id, author, quote (or text), source, len, uid, slug.
+    pub filter: Option<String>,
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Text(TextField, TextTest),
+    Number(NumberField, Op, u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextField {
+    Author,
+    Quote,
+    Source,
+    Uid,
+    Slug,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberField {
+    Id,
+    Len,
+}
+
+/// A test on a text field; strings are stored folded with [`match_key`].
+#[derive(Debug, Clone, PartialEq)]
+enum TextTest {
+    Contains(String),
+    Equals(String),
+    NotEquals(String),
+    IsNull,
+    IsNotNull,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Filter {
+    /// Parses a filter expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::BadRequest`] naming the position of the first
+    /// syntax error, or if the filter is empty or too long.
+    pub fn parse(input: &str) -> Result<Self, AppError> {
+        let length = input.chars().count();
+        if length > MAX_FILTER_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Invalid filter: it is {} characters long, the maximum is {}.",
+                length, MAX_FILTER_LEN
+            )));
+        }
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end: length + 1,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Filter(expr)),
+            Some((Token::RParen, at)) => Err(syntax_error(*at, "unmatched ')'")),
+            Some((token, at)) => Err(syntax_error(
+                *at,
+                &format!("expected AND, OR or the end of the filter, found {}", token),
+            )),
+        }
+    }
+
+    /// Parses the optional `filter` parameter; `None` when it was not given.
+    ///
+    /// # Errors
+    ///
+    /// See [`Filter::parse`].
+    pub fn from_params(params: &FilterQueryParams) -> Result<Option<Self>, AppError> {
+        params.filter.as_deref().map(Filter::parse).transpose()
+    }
+
+    /// Returns `true` if `quote` satisfies the filter.
+    pub fn matches(&self, quote: &Quote) -> bool {
+        self.0.matches(quote)
+    }
+}
+
+impl Expr {
+    fn matches(&self, quote: &Quote) -> bool {
+        match self {
+            Expr::And(terms) => terms.iter().all(|t| t.matches(quote)),
+            Expr::Or(terms) => terms.iter().any(|t| t.matches(quote)),
+            Expr::Not(term) => !term.matches(quote),
+            Expr::Text(field, test) => {
+                let value = match field {
+                    TextField::Author => Some(quote.author.as_str()),
+                    TextField::Quote => Some(quote.text.as_str()),
+                    TextField::Source => quote.source.as_deref(),
+                    TextField::Uid => Some(quote.uid.as_str()),
+                    TextField::Slug => Some(quote.slug.as_str()),
+                }
+                .map(match_key)
+                .filter(|v| !v.is_empty());
+                match (test, value) {
+                    (TextTest::IsNull, value) => value.is_none(),
+                    (TextTest::IsNotNull, value) => value.is_some(),
+                    (TextTest::Contains(wanted), Some(value)) => value.contains(wanted.as_str()),
+                    (TextTest::Equals(wanted), Some(value)) => value == *wanted,
+                    (TextTest::NotEquals(wanted), Some(value)) => value != *wanted,
+                    (TextTest::NotEquals(_), None) => true,
+                    (_, None) => false,
+                }
+            }
+            Expr::Number(field, op, wanted) => {
+                let value = match field {
+                    NumberField::Id => u64::from(quote.id),
+                    NumberField::Len => quote.text.chars().count() as u64,
+                };
+                match op {
+                    Op::Eq => value == *wanted,
+                    Op::Ne => value != *wanted,
+                    Op::Lt => value < *wanted,
+                    Op::Le => value <= *wanted,
+                    Op::Gt => value > *wanted,
+                    Op::Ge => value >= *wanted,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    /// `:`, which `Op::Eq` stands for on number fields.
+    Colon,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::Colon => write!(f, "':'"),
+            Token::Op(op) => write!(f, "'{}'", op.symbol()),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+}
+
+fn syntax_error(position: usize, message: &str) -> AppError {
+    AppError::BadRequest(format!(
+        "Invalid filter at position {}: {}.",
+        position, message
+    ))
+}
+
+/// Splits the filter into tokens, each with its 1-based character position.
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, AppError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i + 1;
+        let token = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ':' => Token::Colon,
+            '=' => Token::Op(Op::Eq),
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                i += 1;
+                Token::Op(Op::Ne)
+            }
+            '<' | '>' => {
+                let or_equal = chars.get(i + 1) == Some(&'=');
+                let op = match (chars[i], or_equal) {
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    (_, false) => Op::Gt,
+                    (_, true) => Op::Ge,
+                };
+                if or_equal {
+                    i += 1;
+                }
+                Token::Op(op)
+            }
+            '!' => return Err(syntax_error(start, "expected '!=' or NOT")),
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(start, "unterminated quoted value")),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&c) => {
+                            text.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Quoted(text)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.get(i) {
+                    if c.is_whitespace() || "():=!<>\"".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    i += 1;
+                }
+                tokens.push((Token::Word(word), start));
+                continue;
+            }
+        };
+        tokens.push((token, start));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    /// The position reported for errors at the end of the filter.
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the keyword `keyword`.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some((Token::Word(w), _)) if w.eq_ignore_ascii_case(keyword));
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, AppError> {
+        let mut terms = vec![self.and()?];
+        while self.keyword("OR") {
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, AppError> {
+        let mut terms = vec![self.not()?];
+        while self.keyword("AND") {
+            terms.push(self.not()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr, AppError> {
+        let at = self.peek().map_or(self.end, |(_, at)| *at);
+        if self.keyword("NOT") {
+            self.nest(at)?;
+            let term = self.not()?;
+            self.depth -= 1;
+            Ok(Expr::Not(Box::new(term)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn nest(&mut self, at: usize) -> Result<(), AppError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(syntax_error(
+                at,
+                &format!("nested more than {} levels deep", MAX_DEPTH),
+            ));
+        }
+        Ok(())
+    }
+
+    fn primary(&mut self) -> Result<Expr, AppError> {
+        match self.advance() {
+            Some((Token::LParen, at)) => {
+                self.nest(at)?;
+                let expr = self.or()?;
+                self.depth -= 1;
+                match self.advance() {
+                    Some((Token::RParen, _)) => Ok(expr),
+                    Some((token, position)) => Err(syntax_error(
+                        position,
+                        &format!(
+                            "expected ')' to close the '(' at position {}, found {}",
+                            at, token
+                        ),
+                    )),
+                    None => Err(syntax_error(
+                        self.end,
+                        &format!("expected ')' to close the '(' at position {}", at),
+                    )),
+                }
+            }
+            Some((Token::Word(name), at)) => self.comparison(&name, at),
+            Some((token, at)) => Err(syntax_error(
+                at,
+                &format!("expected a field name, NOT or '(', found {}", token),
+            )),
+            None => Err(syntax_error(
+                self.end,
+                "expected a field name, NOT or '(' but the filter ended",
+            )),
+        }
+    }
+
+    fn comparison(&mut self, name: &str, at: usize) -> Result<Expr, AppError> {
+        let field = name.to_ascii_lowercase();
+        if !FIELDS.contains(&field.as_str()) {
+            return Err(syntax_error(
+                at,
+                &format!(
+                    "unknown field '{}'. Valid fields: {}",
+                    name,
+                    FIELDS.join(", ")
+                ),
+            ));
+        }
+
+        let (op, op_at) = match self.advance() {
+            Some((Token::Colon, op_at)) => (None, op_at),
+            Some((Token::Op(op), op_at)) => (Some(op), op_at),
+            Some((token, op_at)) => {
+                return Err(syntax_error(
+                    op_at,
+                    &format!("expected an operator after '{}', found {}", name, token),
+                ))
+            }
+            None => {
+                return Err(syntax_error(
+                    self.end,
+                    &format!("expected an operator after '{}'", name),
+                ))
+            }
+        };
+
+        let (value, value_at) = match self.advance() {
+            Some((Token::Word(word), value_at)) => (Value::Bare(word), value_at),
+            Some((Token::Quoted(text), value_at)) => (Value::Quoted(text), value_at),
+            Some((token, value_at)) => {
+                return Err(syntax_error(
+                    value_at,
+                    &format!("expected a value, found {}", token),
+                ))
+            }
+            None => {
+                return Err(syntax_error(
+                    self.end,
+                    "expected a value but the filter ended",
+                ))
+            }
+        };
+
+        let number_field = match field.as_str() {
+            "id" => Some(NumberField::Id),
+            "len" => Some(NumberField::Len),
+            _ => None,
+        };
+        if let Some(number_field) = number_field {
+            let number = value.text().parse::<u64>().ok().ok_or_else(|| {
+                syntax_error(
+                    value_at,
+                    &format!("'{}' needs a whole number, found {}", field, value),
+                )
+            })?;
+            return Ok(Expr::Number(number_field, op.unwrap_or(Op::Eq), number));
+        }
+
+        let text_field = match field.as_str() {
+            "author" => TextField::Author,
+            "quote" | "text" => TextField::Quote,
+            "source" => TextField::Source,
+            "uid" => TextField::Uid,
+            _ => TextField::Slug,
+        };
+        let test = match (op, value.is_null()) {
+            (None | Some(Op::Eq), true) => TextTest::IsNull,
+            (Some(Op::Ne), true) => TextTest::IsNotNull,
+            (None, false) => TextTest::Contains(match_key(value.text())),
+            (Some(Op::Eq), false) => TextTest::Equals(match_key(value.text())),
+            (Some(Op::Ne), false) => TextTest::NotEquals(match_key(value.text())),
+            (Some(op), _) => {
+                return Err(syntax_error(
+                    op_at,
+                    &format!(
+                        "'{}' is a text field and does not support '{}'; use ':', '=' or '!='",
+                        field,
+                        op.symbol()
+                    ),
+                ))
+            }
+        };
+        Ok(Expr::Text(text_field, test))
+    }
+}
+
+/// A comparison value as written.
+enum Value {
+    Bare(String),
+    Quoted(String),
+}
+
+impl Value {
+    fn text(&self) -> &str {
+        match self {
+            Value::Bare(text) | Value::Quoted(text) => text,
+        }
+    }
+
+    /// Only the bare word `null` means null; `"null"` is text.
+    fn is_null(&self) -> bool {
+        matches!(self, Value::Bare(word) if word.eq_ignore_ascii_case("null"))
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bare(word) => write!(f, "'{}'", word),
+            Value::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    fn matching(filter: &str, quotes: &[Quote]) -> Vec<u32> {
+        let filter = Filter::parse(filter).unwrap();
+        quotes
+            .iter()
+            .filter(|q| filter.matches(q))
+            .map(|q| q.id)
+            .collect()
+    }
+
+    fn error(filter: &str) -> String {
+        match Filter::parse(filter) {
+            Err(AppError::BadRequest(message)) => message,
+            other => panic!("expected a bad request for {:?}, got {:?}", filter, other),
+        }
+    }
+
+    #[test]
+    fn test_evaluates_filters() {
+        let quotes = [
+            quote(
+                1,
+                "Stay hungry, stay foolish.",
+                "Steve Jobs",
+                Some("Stanford"),
+            ),
+            quote(
+                2,
+                "Simplicity is the ultimate sophistication.",
+                "Steve Jobs",
+                None,
+            ),
+            quote(3, "La vérité est en marche.", "Émile Zola", Some(" ")),
+            quote(4, "Less is more.", "Mies", Some("Interview")),
+        ];
+        assert_eq!(
+            matching(
+                r#"author:"Steve Jobs" AND len<40 AND NOT source:null"#,
+                &quotes
+            ),
+            [1]
+        );
+        assert_eq!(matching("source:null", &quotes), [2, 3]);
+        assert_eq!(matching("source!=null", &quotes), [1, 4]);
+        // Case and accents are ignored; `=` needs the whole value.
+        assert_eq!(matching("author:EMILE or quote:HUNGRY", &quotes), [1, 3]);
+        assert_eq!(matching("author=jobs", &quotes), Vec::<u32>::new());
+        assert_eq!(matching("author!=\"steve jobs\"", &quotes), [3, 4]);
+        // AND binds tighter than OR; parentheses override it.
+        assert_eq!(matching("id=4 OR id>=2 AND id<3", &quotes), [2, 4]);
+        assert_eq!(matching("(id=4 OR id>=2) AND id<3", &quotes), [2]);
+        assert_eq!(matching("NOT NOT id:1", &quotes), [1]);
+        assert_eq!(matching(r#"quote:"\"" OR text:less"#, &quotes), [4]);
+    }
+
+    #[test]
+    fn test_reports_error_positions() {
+        assert_eq!(
+            error("author:Jobs AND"),
+            "Invalid filter at position 16: expected a field name, NOT or '(' but the filter ended."
+        );
+        assert!(
+            error("mood:happy").starts_with("Invalid filter at position 1: unknown field 'mood'")
+        );
+        assert_eq!(
+            error("len<short"),
+            "Invalid filter at position 5: 'len' needs a whole number, found 'short'."
+        );
+        assert!(error("author<\"b\"").starts_with("Invalid filter at position 7:"));
+        assert!(error("(id:1").contains("expected ')' to close the '(' at position 1"));
+        assert!(error("id:1)").contains("position 5: unmatched ')'"));
+        assert!(error("id:1 id:2").contains("position 6: expected AND, OR"));
+        assert!(error("quote:\"open").contains("position 7: unterminated quoted value"));
+        assert!(error("").contains("position 1"));
+        assert!(error(&"(".repeat(40)).contains("nested more than"));
+        assert!(error(&"x".repeat(MAX_FILTER_LEN + 1)).contains("maximum is"));
+    }
+}
@@ -0,0 +1,525 @@
+//! # GraphQL API
+//!
+//! This module exposes the quote corpus as a GraphQL schema served at
+//! `/graphql`. Authors are not stored separately; they are derived from the
+//! `author` field of the quotes that can currently be listed. Every resolver
+//! goes through [`quote_service`], so lookups and listings follow the same
+//! lifecycle and publishing rules as the REST endpoints.
+//!
+//! Queries are validated against a depth limit and a complexity limit before
+//! they run. List fields cost their page size times the cost of their
+//! selection, so deeply nested lists are rejected up front.
+
+use crate::errors::AppError;
+use crate::models::Quote;
+use crate::responses::ErrorResponse;
+use crate::services::quote_service;
+use crate::services::quote_store::Corpus;
+use crate::utils::{match_key, slugify};
+use crate::AppState;
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Schema, SimpleObject,
+};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Maximum nesting depth of a query, including introspection queries.
+pub const MAX_QUERY_DEPTH: usize = 15;
+
+/// Maximum complexity of a query.
+pub const MAX_QUERY_COMPLEXITY: usize = 1_000;
+
+/// Page size used when a list field is queried without `first`.
+pub const DEFAULT_PAGE_SIZE: i32 = 20;
+
+/// Largest accepted `first` argument on list fields.
+pub const MAX_PAGE_SIZE: i32 = 100;
+
+/// Maximum length of the slug derived from an author name.
+const AUTHOR_SLUG_MAX_LEN: usize = 64;
+
+/// The GraphQL schema served at `/graphql`.
+pub type QuoteSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Builds the GraphQL schema with the depth and complexity limits applied.
+pub fn build_schema() -> QuoteSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Per-request data available to resolvers.
+struct RequestContext {
+    corpus: Arc<Corpus>,
+    /// Instant at which publishing windows are evaluated.
+    at: DateTime<Utc>,
+}
+
+impl RequestContext {
+    fn listable(&self) -> impl Iterator<Item = &Quote> {
+        quote_service::listable_quotes_at(&self.corpus.quotes, self.at)
+    }
+
+    /// Returns the listable authors, keyed and sorted by name, with their
+    /// number of listable quotes.
+    fn authors(&self) -> BTreeMap<&str, usize> {
+        let mut authors = BTreeMap::new();
+        for quote in self.listable() {
+            *authors.entry(quote.author.as_str()).or_insert(0) += 1;
+        }
+        authors
+    }
+}
+
+fn request_context<'a>(ctx: &Context<'a>) -> &'a RequestContext {
+    ctx.data_unchecked::<RequestContext>()
+}
+
+/// Converts an [`AppError`] into a GraphQL error carrying the same code as
+/// the REST error responses in its `code` extension.
+fn graphql_error(error: AppError) -> async_graphql::Error {
+    let code = error.error_code();
+    async_graphql::Error::new(error.message()).extend_with(|_, extensions| {
+        extensions.set("code", code);
+    })
+}
+
+/// Validates the `first`/`offset` paging arguments.
+fn page(first: i32, offset: i32) -> async_graphql::Result<(usize, usize)> {
+    if !(1..=MAX_PAGE_SIZE).contains(&first) {
+        return Err(graphql_error(AppError::BadRequest(format!(
+            "first must be between 1 and {}, got {}.",
+            MAX_PAGE_SIZE, first
+        ))));
+    }
+    if offset < 0 {
+        return Err(graphql_error(AppError::BadRequest(format!(
+            "offset must not be negative, got {}.",
+            offset
+        ))));
+    }
+    Ok((first as usize, offset as usize))
+}
+
+/// Cost of a list field: its page size times the cost of its selection.
+fn list_complexity(first: i32, child_complexity: usize) -> usize {
+    (first.clamp(1, MAX_PAGE_SIZE) as usize).saturating_mul(child_complexity.max(1))
+}
+
+fn graphql_timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A quote, as exposed by the GraphQL API.
+pub struct QuoteNode(Quote);
+
+#[Object(name = "Quote")]
+impl QuoteNode {
+    /// Numeric id of the quote.
+    async fn id(&self) -> u32 {
+        self.0.id
+    }
+
+    /// Content-derived string identifier.
+    async fn uid(&self) -> &str {
+        &self.0.uid
+    }
+
+    /// URL-friendly identifier.
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+
+    /// The text of the quote.
+    async fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    /// Where the quote comes from, if known.
+    async fn source(&self) -> Option<&str> {
+        self.0.source.as_deref()
+    }
+
+    /// When the quote first appeared in the corpus, in RFC 3339 format.
+    async fn created_at(&self) -> Option<String> {
+        self.0.created_at.map(graphql_timestamp)
+    }
+
+    /// When the quote last changed, in RFC 3339 format.
+    async fn updated_at(&self) -> Option<String> {
+        self.0.updated_at.map(graphql_timestamp)
+    }
+
+    /// The author of the quote.
+    async fn author(&self) -> AuthorNode {
+        AuthorNode {
+            name: self.0.author.clone(),
+        }
+    }
+}
+
+/// An author, derived from the quotes attributed to them.
+pub struct AuthorNode {
+    name: String,
+}
+
+#[Object(name = "Author")]
+impl AuthorNode {
+    /// The author's name, as written in the quotes.
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// URL-friendly form of the name.
+    async fn slug(&self) -> String {
+        slugify(&self.name, AUTHOR_SLUG_MAX_LEN)
+    }
+
+    /// Number of listable quotes by the author.
+    async fn quote_count(&self, ctx: &Context<'_>) -> usize {
+        request_context(ctx)
+            .listable()
+            .filter(|q| q.author == self.name)
+            .count()
+    }
+
+    /// Listable quotes by the author, in corpus order.
+    #[graphql(complexity = "list_complexity(first, child_complexity)")]
+    async fn quotes(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_PAGE_SIZE")] first: i32,
+        #[graphql(default)] offset: i32,
+        #[graphql(desc = "Leave out the quote with this id, e.g. the one being viewed.")]
+        exclude_id: Option<u32>,
+    ) -> async_graphql::Result<Vec<QuoteNode>> {
+        let (first, offset) = page(first, offset)?;
+        Ok(request_context(ctx)
+            .listable()
+            .filter(|q| q.author == self.name && Some(q.id) != exclude_id)
+            .skip(offset)
+            .take(first)
+            .cloned()
+            .map(QuoteNode)
+            .collect())
+    }
+}
+
+/// Counts over the corpus.
+#[derive(SimpleObject)]
+pub struct CorpusInfo {
+    /// Corpus version, incremented by every reload that changes it.
+    version: u64,
+    /// When this version was loaded, in RFC 3339 format.
+    loaded_at: String,
+    /// Number of quotes in the data file, whatever their status.
+    total_quotes: usize,
+    /// Number of quotes that can currently be listed.
+    quote_count: usize,
+    /// Number of distinct authors of listable quotes.
+    author_count: usize,
+}
+
+/// The root query type.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Looks a quote up by numeric id, uid or slug, with the same rules as
+    /// `GET /api/v1/quote/:id`.
+    async fn quote(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<QuoteNode> {
+        let request = request_context(ctx);
+        quote_service::find_servable_quote(&request.corpus, &id, request.at)
+            .map(|quote| QuoteNode(quote.clone()))
+            .map_err(|error| graphql_error(AppError::from_quote_lookup(&id, error)))
+    }
+
+    /// A random listable quote, or `null` if there is none.
+    async fn random_quote(&self, ctx: &Context<'_>) -> Option<QuoteNode> {
+        let request = request_context(ctx);
+        quote_service::get_random_quote_at(&request.corpus.quotes, request.at)
+            .map(|quote| QuoteNode(quote.clone()))
+    }
+
+    /// Listable quotes in corpus order, optionally only those by one author
+    /// (matched ignoring case and accents).
+    #[graphql(complexity = "list_complexity(first, child_complexity)")]
+    async fn quotes(
+        &self,
+        ctx: &Context<'_>,
+        author: Option<String>,
+        #[graphql(default_with = "DEFAULT_PAGE_SIZE")] first: i32,
+        #[graphql(default)] offset: i32,
+    ) -> async_graphql::Result<Vec<QuoteNode>> {
+        let (first, offset) = page(first, offset)?;
+        let author = author.as_deref().map(match_key);
+        Ok(request_context(ctx)
+            .listable()
+            .filter(|q| author.as_ref().is_none_or(|a| match_key(&q.author) == *a))
+            .skip(offset)
+            .take(first)
+            .cloned()
+            .map(QuoteNode)
+            .collect())
+    }
+
+    /// Finds an author by name (ignoring case and accents) or slug, or
+    /// returns `null` if no listable quote is attributed to them.
+    async fn author(&self, ctx: &Context<'_>, name: String) -> Option<AuthorNode> {
+        let key = match_key(&name);
+        request_context(ctx)
+            .authors()
+            .into_keys()
+            .find(|author| match_key(author) == key || slugify(author, AUTHOR_SLUG_MAX_LEN) == name)
+            .map(|author| AuthorNode {
+                name: author.to_string(),
+            })
+    }
+
+    /// Authors of listable quotes, sorted by name.
+    #[graphql(complexity = "list_complexity(first, child_complexity)")]
+    async fn authors(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_PAGE_SIZE")] first: i32,
+        #[graphql(default)] offset: i32,
+    ) -> async_graphql::Result<Vec<AuthorNode>> {
+        let (first, offset) = page(first, offset)?;
+        Ok(request_context(ctx)
+            .authors()
+            .into_keys()
+            .skip(offset)
+            .take(first)
+            .map(|author| AuthorNode {
+                name: author.to_string(),
+            })
+            .collect())
+    }
+
+    /// Counts over the current corpus.
+    async fn corpus(&self, ctx: &Context<'_>) -> CorpusInfo {
+        let request = request_context(ctx);
+        CorpusInfo {
+            version: request.corpus.version,
+            loaded_at: graphql_timestamp(request.corpus.loaded_at),
+            total_quotes: request.corpus.quotes.len(),
+            quote_count: request.listable().count(),
+            author_count: request.authors().len(),
+        }
+    }
+}
+
+/// Executes a GraphQL request against `corpus`, evaluating publishing
+/// windows at `at`.
+pub async fn execute(
+    schema: &QuoteSchema,
+    corpus: Arc<Corpus>,
+    at: DateTime<Utc>,
+    request: async_graphql::Request,
+) -> async_graphql::Response {
+    schema
+        .execute(request.data(RequestContext { corpus, at }))
+        .await
+}
+
+/// Handles `POST /graphql`.
+///
+/// The body is a standard GraphQL-over-HTTP JSON request (`query`,
+/// `variables`, `operationName`). Query errors, including limit violations,
+/// are reported in the `errors` array of a `200 OK` response.
+///
+/// # Errors
+///
+/// Returns an [`AppError::QuoteSourcingError`] if there's an issue loading quotes.
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    tag = "GraphQL",
+    operation_id = "executeGraphql",
+    summary = "Execute a GraphQL query",
+    description = "Runs a GraphQL query over quotes and authors. The schema is available by introspection.",
+    request_body(content = Object, description = "A GraphQL request: `query`, `variables` and `operationName`"),
+    responses(
+        (status = 200, description = "The GraphQL response, with any errors in `errors`", body = Object),
+    )
+)]
+pub async fn graphql_handler(
+    State(app_state): State<AppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, AppError> {
+    let corpus = app_state
+        .quote_store
+        .current()
+        .await
+        .map_err(|service_error| {
+            tracing::error!("Failed to load quotes for GraphQL: {}", service_error);
+            AppError::from(service_error)
+        })?;
+    Ok(Json(
+        execute(&app_state.graphql_schema, corpus, Utc::now(), request).await,
+    ))
+}
+
+/// Handles `GET /graphql` by serving the GraphiQL IDE, if enabled.
+///
+/// # Errors
+///
+/// Returns an [`AppError::NotFound`] if GraphiQL is disabled.
+#[utoipa::path(
+    get,
+    path = "/graphql",
+    tag = "GraphQL",
+    operation_id = "getGraphiql",
+    summary = "GraphiQL IDE",
+    description = "Serves the GraphiQL IDE when enabled.",
+    responses(
+        (status = 200, description = "The GraphiQL page", content_type = "text/html", body = String),
+        (status = 404, description = "GraphiQL is disabled", body = ErrorResponse),
+    )
+)]
+pub async fn graphiql_handler(State(app_state): State<AppState>) -> Result<Response, AppError> {
+    if !app_state.graphiql_enabled {
+        return Err(AppError::NotFound(
+            "GraphiQL is disabled; send GraphQL queries with POST /graphql.".to_string(),
+        ));
+    }
+    Ok(Html(
+        GraphiQLSource::build()
+            .endpoint("/graphql")
+            .title("RustQuote GraphQL")
+            .finish(),
+    )
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
+    use crate::services::quote_service::assign_identifiers;
+    use serde_json::{json, Value};
+
+    fn corpus() -> Arc<Corpus> {
+        let mut quotes = vec![
+            quote(1, "One", "Émile Zola", None),
+            quote(2, "Two", "Ada", Some("Notes")),
+            quote(3, "Three", "Émile Zola", None),
+            Quote {
+                status: QuoteStatus::Deleted,
+                ..quote(4, "Four", "Ada", None)
+            },
+            Quote {
+                publish_at: Some("2999-01-01T00:00:00Z".parse().unwrap()),
+                ..quote(5, "Five", "Grace", None)
+            },
+        ];
+        assign_identifiers(&mut quotes);
+        Arc::new(Corpus::from_load(None, quotes, Utc::now()))
+    }
+
+    async fn run(query: &str) -> Value {
+        let response = execute(
+            &build_schema(),
+            corpus(),
+            Utc::now(),
+            async_graphql::Request::new(query),
+        )
+        .await;
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_quote_with_author_and_counts_in_one_query() {
+        let value = run(r#"{
+                quote(id: "1") {
+                    text
+                    author { name quoteCount quotes(excludeId: 1) { id } }
+                }
+                corpus { totalQuotes quoteCount authorCount }
+            }"#)
+        .await;
+        assert_eq!(value["errors"], Value::Null);
+        assert_eq!(
+            value["data"],
+            json!({
+                "quote": {
+                    "text": "One",
+                    "author": { "name": "Émile Zola", "quoteCount": 2, "quotes": [{ "id": 3 }] }
+                },
+                "corpus": { "totalQuotes": 5, "quoteCount": 3, "authorCount": 2 }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_lookup_errors_match_rest_rules() {
+        let value = run(r#"{ quote(id: "4") { id } }"#).await;
+        assert_eq!(value["errors"][0]["extensions"]["code"], "GONE");
+        assert_eq!(
+            value["errors"][0]["message"],
+            "Quote with ID: 4 has been deleted."
+        );
+        // Scheduled quotes are indistinguishable from missing ones.
+        let value = run(r#"{ quote(id: "5") { id } }"#).await;
+        assert_eq!(value["errors"][0]["extensions"]["code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_authors_are_derived_and_matched_loosely() {
+        let value =
+            run(r#"{ authors { name slug } author(name: "emile zola") { quoteCount } }"#).await;
+        assert_eq!(
+            value["data"]["authors"],
+            json!([{ "name": "Ada", "slug": "ada" }, { "name": "Émile Zola", "slug": "emile-zola" }])
+        );
+        assert_eq!(value["data"]["author"]["quoteCount"], 2);
+        let value = run(r#"{ quotes(author: "EMILE ZOLA", first: 1, offset: 1) { id } }"#).await;
+        assert_eq!(value["data"]["quotes"], json!([{ "id": 3 }]));
+    }
+
+    #[tokio::test]
+    async fn test_paging_arguments_are_validated() {
+        let value = run("{ quotes(first: 0) { id } }").await;
+        assert_eq!(value["errors"][0]["extensions"]["code"], "BAD_REQUEST");
+    }
+
+    #[tokio::test]
+    async fn test_depth_and_complexity_limits() {
+        let nested = format!(
+            "{{ randomQuote {}id{} }}",
+            "author { quotes(first: 1) { ".repeat(8),
+            " } }".repeat(8)
+        );
+        let value = run(&nested).await;
+        assert!(value["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("nested too deep"));
+
+        let value = run("{ quotes(first: 100) { author { quotes(first: 100) { text } } } }").await;
+        assert!(value["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("too complex"));
+    }
+
+    #[tokio::test]
+    async fn test_introspection_fits_within_limits() {
+        let value = run(
+            "{ __schema { types { name fields { name args { name type { ...TypeRef } } type { ...TypeRef } } } } }
+             fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name ofType { kind name
+               ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } } } } } }",
+        )
+        .await;
+        assert_eq!(value["errors"], Value::Null);
+    }
+}
@@ -0,0 +1,384 @@
+//! # gRPC API
+//!
+//! This module serves the `rustquote.v1.QuoteService` defined in
+//! `proto/quotes.proto` for internal backends, alongside the standard gRPC
+//! health checking and server reflection services. It runs on its own port
+//! but shares the [`AppState`] of the HTTP API, and goes through
+//! [`quote_service`] so both APIs apply the same lifecycle and publishing
+//! rules. [`AppError`] and [`QuoteServiceError`] are mapped to gRPC status
+//! codes, with the REST error code in the `error-code` metadata entry.
+
+// `tonic::Status` is large, but the generated service traits require it.
+#![allow(clippy::result_large_err)]
+
+use crate::errors::AppError;
+use crate::models::Quote;
+use crate::services::quote_service::{self, QuoteServiceError};
+use crate::services::quote_store::Corpus;
+use crate::utils::match_key;
+use crate::AppState;
+use chrono::{DateTime, SecondsFormat, Utc};
+use proto::quote_service_server::{QuoteService, QuoteServiceServer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::metadata::MetadataValue;
+use tonic::service::Routes;
+use tonic::{Code, Request, Response, Status};
+
+/// Code generated from `proto/quotes.proto`.
+pub mod proto {
+    tonic::include_proto!("rustquote.v1");
+
+    /// Encoded descriptors of `proto/quotes.proto`, served by reflection.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("rustquote_descriptor");
+}
+
+/// Page size used by `ListQuotes` when the request leaves it at 0.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// Largest accepted `ListQuotes` page size.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Metadata key carrying the REST error code of a failed call.
+pub const ERROR_CODE_METADATA: &str = "error-code";
+
+/// Builds a status with `error`'s message and its REST code as metadata.
+fn status_with_error_code(code: Code, error: &AppError) -> Status {
+    let mut status = Status::new(code, error.message());
+    status.metadata_mut().insert(
+        ERROR_CODE_METADATA,
+        MetadataValue::from_static(error.error_code()),
+    );
+    status
+}
+
+/// Maps an [`AppError`] to the closest gRPC status code.
+///
+/// gRPC has no equivalent of `410 Gone`, so deleted and removed quotes are
+/// reported as `NOT_FOUND`; the `error-code` metadata still says `GONE`.
+impl From<AppError> for Status {
+    fn from(error: AppError) -> Self {
+        let code = match &error {
+            AppError::NotFound(_) | AppError::Gone(_) => Code::NotFound,
+            AppError::BadRequest(_) | AppError::NotAcceptable(_) => Code::InvalidArgument,
+            AppError::Forbidden(_) => Code::PermissionDenied,
+            AppError::ServiceUnavailable(_) => Code::Unavailable,
+            AppError::InternalServerError(_) | AppError::QuoteSourcingError(_) => Code::Internal,
+        };
+        status_with_error_code(code, &error)
+    }
+}
+
+/// Maps a [`QuoteServiceError`] to a gRPC status. A missing or unreadable
+/// data file is reported as `UNAVAILABLE`, since it may be fixed without a
+/// client change; a malformed one as `INTERNAL`, and one refused for its
+/// near duplicates as `FAILED_PRECONDITION`.
+impl From<QuoteServiceError> for Status {
+    fn from(error: QuoteServiceError) -> Self {
+        let code = match &error {
+            QuoteServiceError::FileNotFound(_) | QuoteServiceError::FileReadError(_) => {
+                Code::Unavailable
+            }
+            QuoteServiceError::ParseError(_) => Code::Internal,
+            QuoteServiceError::TooManyDuplicates { .. } => Code::FailedPrecondition,
+        };
+        status_with_error_code(code, &AppError::from(error))
+    }
+}
+
+impl From<&Quote> for proto::Quote {
+    fn from(quote: &Quote) -> Self {
+        let timestamp = |at: DateTime<Utc>| at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        proto::Quote {
+            id: quote.id,
+            uid: quote.uid.clone(),
+            slug: quote.slug.clone(),
+            text: quote.text.clone(),
+            author: quote.author.clone(),
+            source: quote.source.clone(),
+            created_at: quote.created_at.map(timestamp),
+            updated_at: quote.updated_at.map(timestamp),
+        }
+    }
+}
+
+/// Implementation of `rustquote.v1.QuoteService`.
+#[derive(Clone)]
+pub struct QuoteGrpcService {
+    app_state: AppState,
+}
+
+impl QuoteGrpcService {
+    /// Creates the service over the shared application state.
+    pub fn new(app_state: AppState) -> Self {
+        QuoteGrpcService { app_state }
+    }
+
+    async fn corpus(&self) -> Result<Arc<Corpus>, Status> {
+        self.app_state
+            .quote_store
+            .current()
+            .await
+            .map_err(|service_error| {
+                tracing::error!("Failed to load quotes for gRPC: {}", service_error);
+                Status::from(service_error)
+            })
+    }
+}
+
+/// Returns the quotes that can be listed at `at`, optionally only those by
+/// `author` (matched ignoring case and accents).
+fn listable_by_author<'a>(
+    corpus: &'a Corpus,
+    author: Option<&str>,
+    at: DateTime<Utc>,
+) -> impl Iterator<Item = &'a Quote> {
+    let author = author.map(match_key);
+    quote_service::listable_quotes_at(&corpus.quotes, at)
+        .filter(move |q| author.as_ref().is_none_or(|a| match_key(&q.author) == *a))
+}
+
+/// Decodes a `ListQuotes` page token, which is the offset of the page.
+fn parse_page_token(token: &str) -> Result<usize, AppError> {
+    if token.is_empty() {
+        return Ok(0);
+    }
+    token
+        .parse::<usize>()
+        .map_err(|_| AppError::BadRequest(format!("Invalid page_token '{}'.", token)))
+}
+
+#[tonic::async_trait]
+impl QuoteService for QuoteGrpcService {
+    async fn get_quote(
+        &self,
+        request: Request<proto::GetQuoteRequest>,
+    ) -> Result<Response<proto::Quote>, Status> {
+        let id = request.into_inner().id;
+        let corpus = self.corpus().await?;
+        let quote = quote_service::find_servable_quote(&corpus, &id, Utc::now())
+            .map_err(|error| AppError::from_quote_lookup(&id, error))?;
+        Ok(Response::new(quote.into()))
+    }
+
+    async fn get_random_quote(
+        &self,
+        _request: Request<proto::GetRandomQuoteRequest>,
+    ) -> Result<Response<proto::Quote>, Status> {
+        let corpus = self.corpus().await?;
+        let quote = quote_service::get_random_quote(&corpus.quotes).ok_or_else(|| {
+            AppError::NotFound("No active quotes available in the data file.".to_string())
+        })?;
+        Ok(Response::new(quote.into()))
+    }
+
+    async fn list_quotes(
+        &self,
+        request: Request<proto::ListQuotesRequest>,
+    ) -> Result<Response<proto::ListQuotesResponse>, Status> {
+        let request = request.into_inner();
+        let page_size = match request.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size if size <= MAX_PAGE_SIZE => size,
+            size => {
+                return Err(AppError::BadRequest(format!(
+                    "page_size must be between 1 and {}, got {}.",
+                    MAX_PAGE_SIZE, size
+                ))
+                .into())
+            }
+        } as usize;
+        let offset = parse_page_token(&request.page_token)?;
+
+        let corpus = self.corpus().await?;
+        let matching: Vec<&Quote> =
+            listable_by_author(&corpus, request.author.as_deref(), Utc::now()).collect();
+        let quotes: Vec<proto::Quote> = matching
+            .iter()
+            .skip(offset)
+            .take(page_size)
+            .map(|&q| q.into())
+            .collect();
+        let next_offset = offset.saturating_add(page_size);
+        Ok(Response::new(proto::ListQuotesResponse {
+            quotes,
+            next_page_token: if next_offset < matching.len() {
+                next_offset.to_string()
+            } else {
+                String::new()
+            },
+            total_size: u32::try_from(matching.len()).unwrap_or(u32::MAX),
+        }))
+    }
+
+    type StreamQuotesStream = tokio_stream::Iter<std::vec::IntoIter<Result<proto::Quote, Status>>>;
+
+    async fn stream_quotes(
+        &self,
+        request: Request<proto::StreamQuotesRequest>,
+    ) -> Result<Response<Self::StreamQuotesStream>, Status> {
+        let author = request.into_inner().author;
+        let corpus = self.corpus().await?;
+        let quotes: Vec<Result<proto::Quote, Status>> =
+            listable_by_author(&corpus, author.as_deref(), Utc::now())
+                .map(|q| Ok(q.into()))
+                .collect();
+        Ok(Response::new(tokio_stream::iter(quotes)))
+    }
+}
+
+/// Builds the gRPC routes: the quote service, health checking (reporting
+/// the quote service as serving) and server reflection.
+pub async fn routes(app_state: AppState) -> Result<Routes, Box<dyn std::error::Error>> {
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_serving::<QuoteServiceServer<QuoteGrpcService>>()
+        .await;
+
+    let reflection = || {
+        tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+    };
+    let reflection_v1 = reflection().build_v1()?;
+    // Older clients such as grpcurl < 1.9 only speak v1alpha.
+    let reflection_v1alpha = reflection().build_v1alpha()?;
+
+    Ok(
+        Routes::new(QuoteServiceServer::new(QuoteGrpcService::new(app_state)))
+            .add_service(health_service)
+            .add_service(reflection_v1)
+            .add_service(reflection_v1alpha),
+    )
+}
+
+/// Serves the gRPC API on an already bound listener until shutdown is
+/// triggered on `app_state`.
+pub async fn serve(
+    listener: TcpListener,
+    app_state: AppState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let shutdown = app_state.shutdown.clone();
+    let routes = routes(app_state).await?;
+    tonic::transport::Server::builder()
+        .add_routes(routes)
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async move {
+            shutdown.wait().await
+        })
+        .await?;
+    Ok(())
+}
+
+/// Binds `grpc_address` and serves the gRPC API on it.
+pub async fn run_grpc_server(
+    grpc_address: SocketAddr,
+    app_state: AppState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(grpc_address).await?;
+    tracing::info!("Library: gRPC server bound to {}", grpc_address);
+    serve(listener, app_state).await?;
+    tracing::info!("Library: gRPC server finished");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn service(content: &str) -> (QuoteGrpcService, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quotes.json");
+        std::fs::write(&path, content).unwrap();
+        (QuoteGrpcService::new(AppState::new(path)), dir)
+    }
+
+    const QUOTES: &str = r#"[
+        {"id": 1, "quote": "One", "author": "Émile", "source": null},
+        {"id": 2, "quote": "Two", "author": "Ada", "source": "Notes"},
+        {"id": 3, "quote": "Three", "author": "Emile", "source": null},
+        {"id": 4, "quote": "Four", "author": "Ada", "source": null, "status": "deleted"}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_get_quote_maps_lookup_errors() {
+        let (service, _file) = service(QUOTES);
+        let quote = service
+            .get_quote(Request::new(proto::GetQuoteRequest { id: "2".into() }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(quote.text, "Two");
+        assert_eq!(quote.source.as_deref(), Some("Notes"));
+
+        let status = service
+            .get_quote(Request::new(proto::GetQuoteRequest { id: "4".into() }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "Quote with ID: 4 has been deleted.");
+        assert_eq!(status.metadata().get(ERROR_CODE_METADATA).unwrap(), "GONE");
+    }
+
+    #[tokio::test]
+    async fn test_list_quotes_pages_and_filters() {
+        let (service, _file) = service(QUOTES);
+        let list = |page_size, page_token: &str, author: Option<&str>| {
+            service.list_quotes(Request::new(proto::ListQuotesRequest {
+                page_size,
+                page_token: page_token.to_string(),
+                author: author.map(str::to_string),
+            }))
+        };
+        let first = list(2, "", None).await.unwrap().into_inner();
+        assert_eq!(
+            first.quotes.iter().map(|q| q.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(first.total_size, 3);
+        let second = list(2, &first.next_page_token, None)
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(second.quotes.len(), 1);
+        assert!(second.next_page_token.is_empty());
+
+        let emile = list(0, "", Some("EMILE")).await.unwrap().into_inner();
+        assert_eq!(emile.total_size, 2);
+
+        let status = list(500, "", None).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let status = list(1, "not-a-token", None).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_stream_quotes_yields_listable_quotes() {
+        use tokio_stream::StreamExt;
+        let (service, _file) = service(QUOTES);
+        let stream = service
+            .stream_quotes(Request::new(proto::StreamQuotesRequest { author: None }))
+            .await
+            .unwrap()
+            .into_inner();
+        let ids: Vec<u32> = stream.map(|q| q.unwrap().id).collect().await;
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_data_file_is_unavailable() {
+        let service = QuoteGrpcService::new(AppState::new("/nonexistent/quotes.json".into()));
+        let status = service
+            .get_random_quote(Request::new(proto::GetRandomQuoteRequest {}))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(
+            status.metadata().get(ERROR_CODE_METADATA).unwrap(),
+            "QUOTE_SOURCING_ERROR"
+        );
+    }
+}
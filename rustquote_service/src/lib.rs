@@ -5,38 +5,159 @@
 
 // Module declarations
 pub mod api_handler;
+pub mod caching;
+pub mod card;
 pub mod responses;
 // pub mod config_manager; // This is now a top-level crate module, not part of rustquote_service library
 pub mod errors;
+pub mod feeds;
+pub mod fieldsets;
+pub mod filter_query;
+pub mod graphql;
+pub mod grpc;
 pub mod models;
+pub mod negotiation;
+pub mod openapi;
 pub mod quote_generator;
 pub mod services;
+pub mod shutdown;
+pub mod stream;
 pub mod utils;
+pub mod versioning;
 
-use axum::{routing::get, Router}; // Removed unused State import here, it's used in api_handler
+use axum::{
+    middleware,
+    routing::{get, post, MethodRouter},
+    Router,
+}; // Removed unused State import here, it's used in api_handler
+use card::png::PngCardCache;
+use services::collection_service::{self, CollectionStore};
+use services::quote_store::QuoteStore;
+use services::suggest_index;
+use shutdown::Shutdown;
 use std::net::SocketAddr;
 use std::path::PathBuf; // Added for quotes_file_path type
 use std::sync::Arc; // For AppState
 use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
 
 /// Application state shared across handlers.
 #[derive(Clone)] // Must be Clone to be used as Axum state
 pub struct AppState {
     pub quotes_file_path: Arc<PathBuf>, // Changed to PathBuf
+    /// In-memory corpus for `quotes_file_path`, reloaded when the file changes.
+    pub quote_store: Arc<QuoteStore>,
+    /// Curated collections, from a file located next to the quotes file.
+    pub collection_store: Arc<CollectionStore>,
+    /// Token required for admin-only request parameters; `None` disables them.
+    pub admin_token: Option<Arc<str>>,
+    /// Rendered PNG quote cards.
+    pub card_cache: Arc<PngCardCache>,
+    /// Public URL of the service for absolute links, without a trailing slash.
+    pub public_base_url: Option<Arc<str>>,
+    /// Default number of upcoming days in the quote of the day calendar.
+    pub calendar_days: u32,
+    /// Schema served at `/graphql`.
+    pub graphql_schema: graphql::QuoteSchema,
+    /// Whether `GET /graphql` serves the GraphiQL IDE.
+    pub graphiql_enabled: bool,
+    /// Signalled when the process starts shutting down.
+    pub shutdown: Shutdown,
+    /// One permit per open quote stream.
+    pub stream_connections: Arc<Semaphore>,
+    /// Maximum number of concurrently open quote streams.
+    pub max_stream_connections: usize,
+    /// Reload events pushed to Server-Sent Events streams.
+    pub quote_changes: Arc<stream::changes::ChangeLog>,
+    /// Maximum number of suggestions returned by `/api/v1/suggest`.
+    pub suggest_max_results: usize,
+}
+
+/// Default maximum number of concurrently open quote streams.
+pub const DEFAULT_MAX_STREAM_CONNECTIONS: usize = 100;
+
+impl AppState {
+    /// Creates the application state for the given quotes data file.
+    pub fn new(quotes_file_path: PathBuf) -> Self {
+        AppState {
+            quote_store: Arc::new(QuoteStore::new(quotes_file_path.clone())),
+            collection_store: Arc::new(CollectionStore::new(
+                collection_service::collections_path_for(&quotes_file_path),
+            )),
+            quotes_file_path: Arc::new(quotes_file_path),
+            admin_token: None,
+            card_cache: Arc::new(PngCardCache::default()),
+            public_base_url: None,
+            calendar_days: feeds::ical::DEFAULT_CALENDAR_DAYS,
+            graphql_schema: graphql::build_schema(),
+            graphiql_enabled: false,
+            shutdown: Shutdown::default(),
+            stream_connections: Arc::new(Semaphore::new(DEFAULT_MAX_STREAM_CONNECTIONS)),
+            max_stream_connections: DEFAULT_MAX_STREAM_CONNECTIONS,
+            quote_changes: Arc::new(stream::changes::ChangeLog::default()),
+            suggest_max_results: suggest_index::DEFAULT_SUGGEST_MAX_RESULTS,
+        }
+    }
+
+    /// Sets the token that unlocks admin-only request parameters.
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token.map(Arc::from);
+        self
+    }
+
+    /// Sets the public URL used for absolute links, e.g. in feeds.
+    pub fn with_public_base_url(mut self, public_base_url: Option<String>) -> Self {
+        self.public_base_url = public_base_url.map(|url| Arc::from(url.trim_end_matches('/')));
+        self
+    }
+
+    /// Sets the default number of upcoming days in the quote of the day calendar.
+    pub fn with_calendar_days(mut self, calendar_days: u32) -> Self {
+        self.calendar_days = calendar_days;
+        self
+    }
+
+    /// Enables or disables the GraphiQL IDE at `GET /graphql`.
+    pub fn with_graphiql(mut self, enabled: bool) -> Self {
+        self.graphiql_enabled = enabled;
+        self
+    }
+
+    /// Sets the maximum number of concurrently open quote streams.
+    pub fn with_max_stream_connections(mut self, max_stream_connections: usize) -> Self {
+        self.stream_connections = Arc::new(Semaphore::new(max_stream_connections));
+        self.max_stream_connections = max_stream_connections;
+        self
+    }
+
+    /// Sets the maximum number of suggestions returned by `/api/v1/suggest`.
+    pub fn with_suggest_max_results(mut self, suggest_max_results: usize) -> Self {
+        self.suggest_max_results = suggest_max_results;
+        self
+    }
+
+    /// Refuses to load a quotes file in which more than `max_duplicate_rate`
+    /// of the quotes are near duplicates; `None` accepts any file.
+    pub fn with_max_duplicate_rate(mut self, max_duplicate_rate: Option<f64>) -> Self {
+        self.quote_store = Arc::new(
+            QuoteStore::new(self.quotes_file_path.to_path_buf())
+                .with_max_duplicate_rate(max_duplicate_rate),
+        );
+        self
+    }
 }
 
 /// Configures and runs the Axum web server.
 ///
 /// Initializes the tracing subscriber for logging, sets up the Axum router
 /// with API endpoints, and starts the HTTP server.
+/// The server stops accepting connections once shutdown is triggered on
+/// `app_state`, and returns after in-flight requests complete.
 pub async fn run_server(
     server_address: SocketAddr,
-    quotes_file_path: PathBuf,
+    app_state: AppState,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let app_state = AppState {
-        quotes_file_path: Arc::new(quotes_file_path),
-    };
-
+    let shutdown = app_state.shutdown.clone();
     // Build our application with routes
     let app = app(app_state); // Pass state to app()
 
@@ -44,21 +165,234 @@ pub async fn run_server(
 
     let listener = TcpListener::bind(server_address).await?;
     tracing::info!("Library: server bound to {}", server_address);
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .await?;
     tracing::info!("Library: server finished");
     Ok(())
 }
 
+/// A route of the versioned API, as registered by [`app`].
+pub struct Route {
+    /// The path, in the router's syntax, where `:id` is a path parameter.
+    pub path: &'static str,
+    /// Whether the route can also answer in text, HTML, Markdown or XML.
+    negotiated: bool,
+    method_router: fn() -> MethodRouter<AppState>,
+}
+
+/// Every route of the versioned API. [`app`] registers exactly these, and the
+/// test that checks the OpenAPI document against the router reads them here.
+pub const ROUTES: &[Route] = &[
+    Route {
+        path: "/test",
+        negotiated: false,
+        method_router: || get(|| async { "Hello from test!" }),
+    },
+    Route {
+        path: "/api/health",
+        negotiated: false,
+        method_router: || get(api_handler::health_check_handler),
+    },
+    Route {
+        path: "/api/openapi.json",
+        negotiated: false,
+        method_router: || get(openapi::openapi_json_handler),
+    },
+    Route {
+        path: "/api/docs",
+        negotiated: false,
+        method_router: || get(openapi::docs_handler),
+    },
+    Route {
+        path: "/api/v1/quote",
+        negotiated: true,
+        method_router: || {
+            get(api_handler::get_quote_handler)
+                .layer(middleware::from_fn(caching::no_store))
+                .layer(middleware::from_fn(versioning::deprecated_v1))
+        },
+    },
+    Route {
+        path: "/api/v1/quote/:id",
+        negotiated: true,
+        method_router: || {
+            get(api_handler::get_quote_by_id_handler)
+                .layer(middleware::from_fn(versioning::deprecated_v1))
+        },
+    },
+    Route {
+        path: "/api/v2/quote",
+        negotiated: true,
+        method_router: || {
+            get(api_handler::get_quote_v2_handler).layer(middleware::from_fn(caching::no_store))
+        },
+    },
+    Route {
+        path: "/api/v2/quote/:id",
+        negotiated: true,
+        method_router: || get(api_handler::get_quote_by_id_v2_handler),
+    },
+    Route {
+        path: "/api/v1/collections/:id/random",
+        negotiated: true,
+        method_router: || {
+            get(api_handler::get_random_collection_quote_handler)
+                .layer(middleware::from_fn(caching::no_store))
+        },
+    },
+    Route {
+        path: "/api/v1/quote/:id/card.svg",
+        negotiated: false,
+        method_router: || get(api_handler::get_quote_card_svg_handler),
+    },
+    Route {
+        path: "/api/v1/quote/random/card.svg",
+        negotiated: false,
+        method_router: || {
+            get(api_handler::get_random_quote_card_svg_handler)
+                .layer(middleware::from_fn(caching::no_store))
+        },
+    },
+    Route {
+        path: "/api/v1/quote/:id/card.png",
+        negotiated: false,
+        method_router: || get(api_handler::get_quote_card_png_handler),
+    },
+    Route {
+        path: "/api/v1/quote/random/card.png",
+        negotiated: false,
+        method_router: || {
+            get(api_handler::get_random_quote_card_png_handler)
+                .layer(middleware::from_fn(caching::no_store))
+        },
+    },
+    Route {
+        path: "/api/v1/quote/:id/revisions",
+        negotiated: false,
+        method_router: || get(api_handler::get_quote_revisions_handler),
+    },
+    Route {
+        path: "/api/v1/quote/:id/similar",
+        negotiated: false,
+        method_router: || get(api_handler::get_similar_quotes_handler),
+    },
+    Route {
+        path: "/api/v1/quotes",
+        negotiated: false,
+        method_router: || get(api_handler::list_quotes_by_ids_handler),
+    },
+    Route {
+        path: "/api/v1/quotes/count",
+        negotiated: false,
+        method_router: || get(api_handler::count_quotes_handler),
+    },
+    Route {
+        path: "/api/v1/quotes/facets",
+        negotiated: false,
+        method_router: || get(api_handler::get_facets_handler),
+    },
+    Route {
+        path: "/api/v1/stats",
+        negotiated: false,
+        method_router: || get(api_handler::get_stats_handler),
+    },
+    Route {
+        path: "/api/v1/diagnostics/duplicates",
+        negotiated: false,
+        method_router: || get(api_handler::get_duplicates_handler),
+    },
+    Route {
+        path: "/api/v1/suggest",
+        negotiated: false,
+        method_router: || get(api_handler::suggest_handler),
+    },
+    // matchit cannot escape `:`, so this registers a `batchGet` parameter after
+    // `/api/v1/quotes`; `batch_get_quotes_handler` rejects all but the exact path.
+    Route {
+        path: api_handler::BATCH_GET_PATH,
+        negotiated: false,
+        method_router: || post(api_handler::batch_get_quotes_handler),
+    },
+    Route {
+        path: "/api/v1/collections",
+        negotiated: false,
+        method_router: || get(api_handler::list_collections_handler),
+    },
+    Route {
+        path: "/api/v1/collections/:id",
+        negotiated: false,
+        method_router: || get(api_handler::get_collection_handler),
+    },
+    Route {
+        path: "/feeds/daily.rss",
+        negotiated: false,
+        method_router: || get(api_handler::daily_rss_feed_handler),
+    },
+    Route {
+        path: "/feeds/daily.atom",
+        negotiated: false,
+        method_router: || get(api_handler::daily_atom_feed_handler),
+    },
+    Route {
+        path: "/feeds/new.atom",
+        negotiated: false,
+        method_router: || get(api_handler::new_quotes_atom_feed_handler),
+    },
+    Route {
+        path: "/feeds/daily.ics",
+        negotiated: false,
+        method_router: || get(api_handler::daily_calendar_handler),
+    },
+    Route {
+        path: "/api/v1/stream/ws",
+        negotiated: false,
+        method_router: || get(stream::ws::stream_ws_handler),
+    },
+    Route {
+        path: "/api/v1/stream/sse",
+        negotiated: false,
+        method_router: || get(stream::sse::stream_sse_handler),
+    },
+    Route {
+        path: "/graphql",
+        negotiated: false,
+        method_router: || get(graphql::graphiql_handler).post(graphql::graphql_handler),
+    },
+];
+
+/// Unversioned aliases, served by whichever version the `Api-Version` header picks.
+pub const UNVERSIONED_ROUTES: &[&str] = &["/api/quote", "/api/quote/:id"];
+
 // Function to create the app router, useful for testing and main setup
 // Now accepts AppState
 pub fn app(app_state: AppState) -> Router {
-    Router::new()
-        .route("/test", get(|| async { "Hello from test!" }))
-        .route("/api/health", get(api_handler::health_check_handler))
-        .route("/api/v1/quote", get(api_handler::get_quote_handler))
-        .route(
-            "/api/v1/quote/:id",
-            get(api_handler::get_quote_by_id_handler),
-        )
-        .with_state(app_state) // Share AppState with handlers
+    let mut negotiated_routes = Router::new();
+    let mut versioned_routes = Router::new();
+    for route in ROUTES {
+        if route.negotiated {
+            negotiated_routes = negotiated_routes.route(route.path, (route.method_router)());
+        } else {
+            versioned_routes = versioned_routes.route(route.path, (route.method_router)());
+        }
+    }
+
+    let versioned_routes = versioned_routes
+        // Quote endpoints can also answer in text, HTML, Markdown or XML.
+        .merge(negotiated_routes.route_layer(middleware::from_fn(negotiation::negotiate_format)))
+        // Answers If-None-Match/If-Modified-Since for responses that carry an ETag.
+        .layer(middleware::from_fn(caching::conditional_get))
+        .layer(middleware::from_fn(versioning::api_version_header))
+        .with_state(app_state); // Share AppState with handlers
+
+    // Unversioned aliases pick the version from the Api-Version header.
+    let forward = {
+        let versioned_routes = versioned_routes.clone();
+        move |request| versioning::forward_unversioned(versioned_routes.clone(), request)
+    };
+    UNVERSIONED_ROUTES
+        .iter()
+        .fold(versioned_routes, |router, path| {
+            router.route(path, get(forward.clone()))
+        })
 }
@@ -0,0 +1,38 @@
+//! # Collection Data Model
+//!
+//! This module defines the `Collection` struct, a curated, ordered list of
+//! quote IDs such as a themed playlist.
+
+use serde::{Deserialize, Serialize};
+
+/// A named, ordered list of quote IDs.
+///
+/// Collections are defined in a data file next to the quotes file and refer to
+/// quotes by their numeric ID. IDs are kept as-is even if the quote no longer
+/// exists, so that missing entries can be reported rather than silently dropped.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Collection {
+    /// A unique, URL-safe identifier for the collection (e.g., "monday-motivation").
+    pub id: String,
+    /// The display name of the collection.
+    pub name: String,
+    /// A description of the collection's theme.
+    #[serde(default)]
+    pub description: String,
+    /// The quote IDs in the collection, in display order.
+    pub quote_ids: Vec<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_deserialization() {
+        let json_data = r#"{"id":"monday","name":"Monday motivation","quote_ids":[3,1]}"#;
+        let collection: Collection = serde_json::from_str(json_data).unwrap();
+        assert_eq!(collection.id, "monday");
+        assert_eq!(collection.description, "");
+        assert_eq!(collection.quote_ids, vec![3, 1]);
+    }
+}
@@ -4,5 +4,9 @@
 //! within the `models` directory. It re-exports or declares submodules
 //! containing specific model definitions.
 
+pub mod collection;
 pub mod quote;
-pub use self::quote::Quote; // Re-export Quote struct
+pub mod revision;
+pub use self::collection::Collection;
+pub use self::quote::{PublishWindow, Quote, QuoteStatus}; // Re-export Quote struct
+pub use self::revision::{QuoteRevision, RevisionKind};
@@ -3,19 +3,69 @@
 //! This module defines the `Quote` struct, which represents a quotation
 //! along with its author and optional source.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // WBS 2.4: Implement Quote Formatting Logic
 // For the MVP, the Quote struct is directly serialized for API responses.
 // The `text` field is renamed to `quote` to match the API specification.
 // Extra fields like `id` and `source` may be present in the response but are acceptable.
 
+/// Lifecycle state of a [`Quote`].
+///
+/// Editors set this in the data file instead of deleting entries, so that
+/// shared permalinks keep resolving.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStatus {
+    /// Served everywhere, including random selection.
+    #[default]
+    Active,
+    /// Still fetchable by id, but never picked at random.
+    Archived,
+    /// Retired; lookups report it as gone.
+    Deleted,
+}
+
+impl QuoteStatus {
+    /// Returns `true` for [`QuoteStatus::Active`].
+    pub fn is_active(&self) -> bool {
+        *self == QuoteStatus::Active
+    }
+}
+
+/// The interval in which a quote is published.
+///
+/// The window includes `publish_at` and excludes `expire_at`; a missing bound
+/// leaves that side open. Indexes keep it to evaluate windows at lookup time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PublishWindow {
+    pub publish_at: Option<DateTime<Utc>>,
+    pub expire_at: Option<DateTime<Utc>>,
+}
+
+impl PublishWindow {
+    /// Returns `true` if `at` falls within the window.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.publish_at.is_none_or(|p| p <= at) && self.expire_at.is_none_or(|e| at < e)
+    }
+
+    /// Returns `true` if neither bound is set, so the window is always open.
+    pub fn is_unbounded(&self) -> bool {
+        self.publish_at.is_none() && self.expire_at.is_none()
+    }
+}
+
 /// Represents a quote with its associated metadata.
 ///
 /// This struct is used for storing, retrieving, and serializing quote data.
 /// It includes fields for a unique ID, the quote text, the author, and an optional source.
 /// The `text` field is serialized as `quote` in JSON to match API specifications.
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+///
+/// `created_at` and `updated_at` may be set by editors in the data file; when they are
+/// absent, the quote store stamps them as it observes the quote being added or changed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, ToSchema)]
 pub struct Quote {
     /// A unique identifier for the quote.
     pub id: u32,
@@ -26,6 +76,29 @@ pub struct Quote {
     pub author: String,
     /// An optional source for the quote (e.g., book, speech).
     pub source: Option<String>,
+    /// A globally unique string identifier. Editors may pin one in the data file;
+    /// otherwise it is derived from a hash of the author and text when loaded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub uid: String,
+    /// A human-readable, URL-safe identifier derived from the author and text
+    /// unless set in the data file.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slug: String,
+    /// Lifecycle state of the quote. Omitted from JSON when active.
+    #[serde(default, skip_serializing_if = "QuoteStatus::is_active")]
+    pub status: QuoteStatus,
+    /// The quote is unavailable before this instant, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// The quote is unavailable from this instant on, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_at: Option<DateTime<Utc>>,
+    /// When the quote was first added to the corpus.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the quote's content last changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Quote {
@@ -47,8 +120,53 @@ impl Quote {
             text,
             author,
             source,
+            ..Default::default()
+        }
+    }
+
+    /// The quote's publishing window.
+    pub fn publish_window(&self) -> PublishWindow {
+        PublishWindow {
+            publish_at: self.publish_at,
+            expire_at: self.expire_at,
         }
     }
+
+    /// Returns `true` if `at` falls within the quote's publishing window.
+    ///
+    /// See [`PublishWindow`] for how the bounds apply.
+    pub fn is_published_at(&self, at: DateTime<Utc>) -> bool {
+        self.publish_window().contains(at)
+    }
+
+    /// Returns `true` if both quotes have the same editorial content.
+    ///
+    /// Timestamps are ignored, so a quote that was only re-stamped is not
+    /// considered changed.
+    pub fn content_eq(&self, other: &Quote) -> bool {
+        let strip = |q: &Quote| Quote {
+            created_at: None,
+            updated_at: None,
+            ..q.clone()
+        };
+        strip(self) == strip(other)
+    }
+}
+
+/// Quotes for unit tests across the crate.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::Quote;
+
+    /// An active quote with no publishing window or timestamps.
+    pub(crate) fn quote(id: u32, text: &str, author: &str, source: Option<&str>) -> Quote {
+        Quote::new(
+            id,
+            text.to_string(),
+            author.to_string(),
+            source.map(str::to_string),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -62,6 +180,7 @@ mod tests {
             text: "This is a test quote.".to_string(),
             author: "Test Author".to_string(),
             source: Some("Test Source".to_string()),
+            ..Default::default()
         };
         let serialized = serde_json::to_string(&quote).unwrap();
         let expected_json = r#"{"id":1,"quote":"This is a test quote.","author":"Test Author","source":"Test Source"}"#;
@@ -78,6 +197,7 @@ mod tests {
             text: "Another test.".to_string(),
             author: "Another Author".to_string(),
             source: None,
+            ..Default::default()
         };
         assert_eq!(deserialized, expected_quote);
     }
@@ -92,6 +212,7 @@ mod tests {
             text: "With source.".to_string(),
             author: "Source Author".to_string(),
             source: Some("The Source".to_string()),
+            ..Default::default()
         };
         assert_eq!(deserialized, expected_quote);
     }
@@ -108,5 +229,65 @@ mod tests {
         assert_eq!(quote.text, "Constructed quote");
         assert_eq!(quote.author, "Constructor");
         assert_eq!(quote.source, Some("Source of Construction".to_string()));
+        assert_eq!(quote.created_at, None);
+        assert_eq!(quote.updated_at, None);
+    }
+
+    #[test]
+    fn test_quote_deserialization_with_timestamps() {
+        let json_data = r#"{"id":5,"quote":"Dated.","author":"Clock","source":null,"created_at":"2024-01-02T03:04:05Z"}"#;
+        let deserialized: Quote = serde_json::from_str(json_data).unwrap();
+        assert_eq!(
+            deserialized.created_at,
+            Some("2024-01-02T03:04:05Z".parse::<DateTime<Utc>>().unwrap())
+        );
+        assert_eq!(deserialized.updated_at, None);
+    }
+
+    #[test]
+    fn test_quote_status_defaults_to_active() {
+        let deserialized: Quote =
+            serde_json::from_str(r#"{"id":7,"quote":"Q","author":"A"}"#).unwrap();
+        assert_eq!(deserialized.status, QuoteStatus::Active);
+        let archived: Quote =
+            serde_json::from_str(r#"{"id":8,"quote":"Q","author":"A","status":"archived"}"#)
+                .unwrap();
+        assert_eq!(archived.status, QuoteStatus::Archived);
+        assert!(serde_json::to_string(&archived)
+            .unwrap()
+            .contains(r#""status":"archived""#));
+    }
+
+    #[test]
+    fn test_is_published_at_window() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let seasonal = Quote {
+            publish_at: Some(at("2024-12-01T00:00:00Z")),
+            expire_at: Some(at("2024-12-27T00:00:00Z")),
+            ..Quote::new(9, "Season's greetings.".to_string(), "A".to_string(), None)
+        };
+        assert!(!seasonal.is_published_at(at("2024-11-30T23:59:59Z")));
+        assert!(seasonal.is_published_at(at("2024-12-01T00:00:00Z")));
+        assert!(seasonal.is_published_at(at("2024-12-26T23:59:59Z")));
+        assert!(!seasonal.is_published_at(at("2024-12-27T00:00:00Z")));
+
+        let open = Quote::new(10, "Always.".to_string(), "A".to_string(), None);
+        assert!(open.is_published_at(at("1970-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_content_eq_ignores_timestamps() {
+        let original = Quote::new(6, "Same.".to_string(), "Author".to_string(), None);
+        let stamped = Quote {
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            ..original.clone()
+        };
+        let edited = Quote {
+            text: "Different.".to_string(),
+            ..original.clone()
+        };
+        assert!(original.content_eq(&stamped));
+        assert!(!original.content_eq(&edited));
     }
 }
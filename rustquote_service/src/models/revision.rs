@@ -0,0 +1,65 @@
+//! # Quote Revision Model
+//!
+//! This module defines the `QuoteRevision` struct, which records a single change
+//! to a quote as observed between two loads of the quotes data file.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::quote::Quote;
+
+/// The kind of change captured by a [`QuoteRevision`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionKind {
+    /// The quote appeared in the data file.
+    Created,
+    /// The quote's content changed.
+    Updated,
+    /// The quote disappeared from the data file.
+    Removed,
+}
+
+/// A single entry in a quote's revision log.
+///
+/// `before` is `None` for created quotes and `after` is `None` for removed quotes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct QuoteRevision {
+    /// Sequence number of this revision for the quote, starting at 1.
+    pub revision: u32,
+    /// The corpus version in which the change was observed.
+    pub corpus_version: u64,
+    /// When the change was observed.
+    pub changed_at: DateTime<Utc>,
+    /// What kind of change this was.
+    pub kind: RevisionKind,
+    /// The quote as it was before the change.
+    pub before: Option<Quote>,
+    /// The quote as it is after the change.
+    pub after: Option<Quote>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    #[test]
+    fn test_revision_serialization() {
+        let changed_at = "2024-05-06T07:08:09Z".parse::<DateTime<Utc>>().unwrap();
+        let revision = QuoteRevision {
+            revision: 1,
+            corpus_version: 2,
+            changed_at,
+            kind: RevisionKind::Created,
+            before: None,
+            after: Some(quote(1, "New.", "Author", None)),
+        };
+        let value = serde_json::to_value(&revision).unwrap();
+        assert_eq!(value["kind"], "created");
+        assert_eq!(value["changed_at"], "2024-05-06T07:08:09Z");
+        assert!(value["before"].is_null());
+        assert_eq!(value["after"]["quote"], "New.");
+    }
+}
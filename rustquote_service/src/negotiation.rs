@@ -0,0 +1,504 @@
+//! # Content Negotiation
+//!
+//! This module lets quote endpoints answer in formats other than JSON.
+//! The [`negotiate_format`] middleware picks a [`ResponseFormat`] from the
+//! `format` query parameter or the `Accept` header, lets the handler produce
+//! its usual JSON response, and re-renders that body in the chosen format.
+//! Because it works on the response body, errors produced by
+//! `AppError::into_response` are rendered the same way as successful quotes.
+
+use crate::errors::AppError;
+use crate::utils::escape_xml;
+use axum::{
+    body::{self, Body},
+    extract::{Query, Request},
+    http::{
+        header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, VARY},
+        HeaderMap, HeaderValue, Uri,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use utoipa::IntoParams;
+
+/// Maximum size of a handler response that will be re-rendered.
+const MAX_RENDER_BODY_BYTES: usize = 1024 * 1024;
+
+/// Documents the `format` query parameter of negotiated endpoints in the
+/// OpenAPI spec; [`negotiate_format`] reads it from the URI.
+#[derive(Debug, Default, serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResponseFormatParams {
+    /// Response format: json, text, html, markdown or xml. Overrides `Accept`.
+    pub format: Option<String>,
+}
+
+/// The representations quote endpoints can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// `application/json`, the default.
+    Json,
+    /// `text/plain`.
+    Text,
+    /// `text/html`.
+    Html,
+    /// `text/markdown`.
+    Markdown,
+    /// `application/xml`.
+    Xml,
+}
+
+impl ResponseFormat {
+    /// Values accepted by the `format` query parameter, for error messages.
+    pub const FORMAT_NAMES: &'static str = "json, text, html, markdown, xml";
+
+    /// The `Content-Type` header value for this format.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "application/json",
+            ResponseFormat::Text => "text/plain; charset=utf-8",
+            ResponseFormat::Html => "text/html; charset=utf-8",
+            ResponseFormat::Markdown => "text/markdown; charset=utf-8",
+            ResponseFormat::Xml => "application/xml; charset=utf-8",
+        }
+    }
+
+    /// Parses a `format` query parameter value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ResponseFormat::Json),
+            "text" | "txt" | "plain" => Some(ResponseFormat::Text),
+            "html" => Some(ResponseFormat::Html),
+            "markdown" | "md" => Some(ResponseFormat::Markdown),
+            "xml" => Some(ResponseFormat::Xml),
+            _ => None,
+        }
+    }
+
+    /// Maps a media range from an `Accept` header to a format.
+    ///
+    /// Wildcards resolve to the default for their type.
+    fn from_media_range(range: &str) -> Option<Self> {
+        match range.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(ResponseFormat::Json),
+            "text/plain" | "text/*" => Some(ResponseFormat::Text),
+            "text/html" => Some(ResponseFormat::Html),
+            "text/markdown" => Some(ResponseFormat::Markdown),
+            "application/xml" | "text/xml" => Some(ResponseFormat::Xml),
+            _ => None,
+        }
+    }
+
+    /// Picks the best format for an `Accept` header value.
+    ///
+    /// Media ranges are ranked by their `q` parameter, with ties going to the
+    /// one listed first. Ranges with `q=0` are never chosen. Returns `None`
+    /// when no listed range is supported.
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        let mut best: Option<(f32, Self)> = None;
+        for entry in accept.split(',') {
+            let mut parts = entry.split(';');
+            let range = parts.next().unwrap_or("").trim();
+            let quality = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            if let Some(format) = Self::from_media_range(range) {
+                if best.is_none_or(|(q, _)| quality > q) {
+                    best = Some((quality, format));
+                }
+            }
+        }
+        best.map(|(_, format)| format)
+    }
+
+    /// Negotiates the format for a request. The `format` query parameter takes
+    /// precedence over the `Accept` header; without either, JSON is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::NotAcceptable`] if the requested format is not supported.
+    pub fn negotiate(uri: &Uri, headers: &HeaderMap) -> Result<Self, AppError> {
+        let params: HashMap<String, String> = Query::try_from_uri(uri)
+            .map(|Query(params)| params)
+            .unwrap_or_default();
+        if let Some(name) = params.get("format") {
+            return Self::from_name(name).ok_or_else(|| {
+                AppError::NotAcceptable(format!(
+                    "Unsupported format '{}'. Supported formats: {}.",
+                    name,
+                    Self::FORMAT_NAMES
+                ))
+            });
+        }
+
+        let Some(accept) = headers.get(ACCEPT) else {
+            return Ok(ResponseFormat::Json);
+        };
+        let accept = accept.to_str().unwrap_or("");
+        if accept.trim().is_empty() {
+            return Ok(ResponseFormat::Json);
+        }
+        Self::from_accept(accept).ok_or_else(|| {
+            AppError::NotAcceptable(format!(
+                "None of the accepted media types '{}' are supported. Supported types: application/json, text/plain, text/html, text/markdown, application/xml.",
+                accept
+            ))
+        })
+    }
+}
+
+/// Axum middleware that renders JSON responses in the negotiated format.
+///
+/// Unsupported formats are answered with `406 Not Acceptable` before the
+/// handler runs. Every response gets `Vary: Accept`.
+pub async fn negotiate_format(request: Request, next: Next) -> Response {
+    let format = match ResponseFormat::negotiate(request.uri(), request.headers()) {
+        Ok(format) => format,
+        Err(err) => {
+            tracing::info!("Rejected request for {}: {}", request.uri(), err);
+            return with_vary(err.into_response());
+        }
+    };
+
+    let response = next.run(request).await;
+    if format == ResponseFormat::Json {
+        return with_vary(response);
+    }
+    with_vary(render_response(response, format).await)
+}
+
+fn with_vary(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// Re-renders a JSON response body in `format`. Responses that are not JSON
+/// are passed through unchanged.
+async fn render_response(response: Response, format: ResponseFormat) -> Response {
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match body::to_bytes(body, MAX_RENDER_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::error!("Failed to buffer response body for rendering: {}", err);
+            return AppError::InternalServerError("Failed to render response.".to_string())
+                .into_response();
+        }
+    };
+    let value: Value = match serde_json::from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    let rendered = render(&value, format);
+    parts.headers.remove(CONTENT_LENGTH);
+    parts.headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    Response::from_parts(parts, Body::from(rendered))
+}
+
+/// The shape of a JSON body, which decides how it is laid out.
+enum Shape<'a> {
+    Quote(&'a Map<String, Value>),
+    Error(&'a Map<String, Value>),
+    Other(&'a Value),
+}
+
+fn shape(value: &Value) -> Shape<'_> {
+    match value {
+        Value::Object(map) if map.contains_key("error_code") && map.contains_key("message") => {
+            Shape::Error(map)
+        }
+        Value::Object(map) if map.contains_key("quote") && map.contains_key("author") => {
+            Shape::Quote(map)
+        }
+        other => Shape::Other(other),
+    }
+}
+
+fn field(map: &Map<String, Value>, key: &str) -> String {
+    match map.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Renders a JSON value in a non-JSON format.
+pub fn render(value: &Value, format: ResponseFormat) -> String {
+    match format {
+        ResponseFormat::Json => value.to_string(),
+        ResponseFormat::Text => render_text(value),
+        ResponseFormat::Html => render_html(value),
+        ResponseFormat::Markdown => render_markdown(value),
+        ResponseFormat::Xml => render_xml(value),
+    }
+}
+
+fn render_text(value: &Value) -> String {
+    match shape(value) {
+        Shape::Quote(map) => format!(
+            "\"{}\"\n    -- {}\n",
+            field(map, "quote"),
+            field(map, "author")
+        ),
+        Shape::Error(map) => format!("{}: {}\n", field(map, "error_code"), field(map, "message")),
+        Shape::Other(other) => format!("{}\n", other),
+    }
+}
+
+fn render_markdown(value: &Value) -> String {
+    match shape(value) {
+        Shape::Quote(map) => {
+            let mut markdown: String = field(map, "quote")
+                .lines()
+                .map(|line| match escape_markdown(line) {
+                    line if line.is_empty() => ">\n".to_string(),
+                    line => format!("> {}\n", line),
+                })
+                .collect();
+            markdown.push_str(&format!(
+                ">\n> — *{}*\n",
+                escape_markdown(&field(map, "author").replace('\n', " "))
+            ));
+            markdown
+        }
+        Shape::Error(map) => format!(
+            "**{}**: {}\n",
+            escape_markdown(&field(map, "error_code")),
+            escape_markdown(&field(map, "message"))
+        ),
+        Shape::Other(other) => format!("```json\n{}\n```\n", other),
+    }
+}
+
+/// Backslash-escapes the characters of one line of text that Markdown would
+/// otherwise read as formatting, including list markers at its start.
+fn escape_markdown(line: &str) -> String {
+    let line = line.trim();
+    let mut escaped = String::with_capacity(line.len());
+    let marker_end = line
+        .find(|c: char| !c.is_ascii_digit())
+        .filter(|&i| i > 0 && line[i..].starts_with(['.', ')']));
+    for (i, c) in line.char_indices() {
+        let list_marker = (i == 0 && matches!(c, '-' | '+' | '=')) || Some(i) == marker_end;
+        if list_marker || "\\`*_{}[]<>#|~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn render_html(value: &Value) -> String {
+    let (title, body) = match shape(value) {
+        Shape::Quote(map) => (
+            "Quote".to_string(),
+            format!(
+                "<figure>\n<blockquote><p>{}</p></blockquote>\n<figcaption>— {}</figcaption>\n</figure>",
+                escape_xml(&field(map, "quote")),
+                escape_xml(&field(map, "author"))
+            ),
+        ),
+        Shape::Error(map) => (
+            escape_xml(&field(map, "error_code")),
+            format!(
+                "<h1>{}</h1>\n<p>{}</p>",
+                escape_xml(&field(map, "error_code")),
+                escape_xml(&field(map, "message"))
+            ),
+        ),
+        Shape::Other(other) => (
+            "Response".to_string(),
+            format!("<pre>{}</pre>", escape_xml(&other.to_string())),
+        ),
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        title, body
+    )
+}
+
+fn render_xml(value: &Value) -> String {
+    let root = match shape(value) {
+        Shape::Quote(_) => "quote",
+        Shape::Error(_) => "error",
+        Shape::Other(Value::Array(_)) => "items",
+        Shape::Other(_) => "response",
+    };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    write_xml_element(&mut xml, root, value);
+    xml.push('\n');
+    xml
+}
+
+fn write_xml_element(xml: &mut String, name: &str, value: &Value) {
+    let name = xml_name(name);
+    match value {
+        Value::Null => {
+            xml.push_str(&format!("<{}/>", name));
+        }
+        Value::Object(map) => {
+            xml.push_str(&format!("<{}>", name));
+            for (key, child) in map {
+                write_xml_element(xml, key, child);
+            }
+            xml.push_str(&format!("</{}>", name));
+        }
+        Value::Array(items) => {
+            xml.push_str(&format!("<{}>", name));
+            for item in items {
+                write_xml_element(xml, "item", item);
+            }
+            xml.push_str(&format!("</{}>", name));
+        }
+        Value::String(s) => {
+            xml.push_str(&format!("<{}>{}</{}>", name, escape_xml(s), name));
+        }
+        other => {
+            xml.push_str(&format!("<{}>{}</{}>", name, other, name));
+        }
+    }
+}
+
+/// Turns a JSON key into a valid XML element name.
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_accept_prefers_highest_quality() {
+        assert_eq!(
+            ResponseFormat::from_accept("text/html;q=0.5, text/markdown"),
+            Some(ResponseFormat::Markdown)
+        );
+        assert_eq!(
+            ResponseFormat::from_accept("application/xml, text/plain"),
+            Some(ResponseFormat::Xml)
+        );
+        assert_eq!(
+            ResponseFormat::from_accept("image/png, */*;q=0.1"),
+            Some(ResponseFormat::Json)
+        );
+        assert_eq!(ResponseFormat::from_accept("text/html;q=0"), None);
+        assert_eq!(ResponseFormat::from_accept("image/png"), None);
+    }
+
+    #[test]
+    fn test_negotiate_query_overrides_accept() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("text/html"));
+        let uri: Uri = "/api/v1/quote?format=md".parse().unwrap();
+        assert_eq!(
+            ResponseFormat::negotiate(&uri, &headers).unwrap(),
+            ResponseFormat::Markdown
+        );
+
+        let uri: Uri = "/api/v1/quote?format=pdf".parse().unwrap();
+        assert!(matches!(
+            ResponseFormat::negotiate(&uri, &headers),
+            Err(AppError::NotAcceptable(_))
+        ));
+
+        let uri: Uri = "/api/v1/quote".parse().unwrap();
+        assert_eq!(
+            ResponseFormat::negotiate(&uri, &HeaderMap::new()).unwrap(),
+            ResponseFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_render_quote_formats() {
+        let quote = json!({"id": 1, "quote": "Less is <more> & more", "author": "Mies"});
+        assert_eq!(
+            render(&quote, ResponseFormat::Text),
+            "\"Less is <more> & more\"\n    -- Mies\n"
+        );
+        assert_eq!(
+            render(&quote, ResponseFormat::Markdown),
+            "> Less is \\<more\\> & more\n>\n> — *Mies*\n"
+        );
+        let html = render(&quote, ResponseFormat::Html);
+        assert!(html.contains("<blockquote><p>Less is &lt;more&gt; &amp; more</p></blockquote>"));
+        assert_eq!(
+            render(&quote, ResponseFormat::Xml),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<quote><id>1</id><quote>Less is &lt;more&gt; &amp; more</quote><author>Mies</author></quote>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_quotes_every_line_and_escapes_formatting() {
+        let quote = json!({
+            "id": 1,
+            "quote": "Roses are *red*,\n# violets_are blue\n\n- 1. [link](x)\n2. done",
+            "author": "A_*Poet*"
+        });
+        assert_eq!(
+            render(&quote, ResponseFormat::Markdown),
+            concat!(
+                "> Roses are \\*red\\*,\n",
+                "> \\# violets\\_are blue\n",
+                ">\n",
+                "> \\- 1. \\[link\\](x)\n",
+                "> 2\\. done\n",
+                ">\n",
+                "> — *A\\_\\*Poet\\**\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_error_formats() {
+        let error = json!({"error_code": "NOT_FOUND", "message": "Quote with ID: 9 not found."});
+        assert_eq!(
+            render(&error, ResponseFormat::Text),
+            "NOT_FOUND: Quote with ID: 9 not found.\n"
+        );
+        assert_eq!(
+            render(&error, ResponseFormat::Xml),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<error><error_code>NOT_FOUND</error_code><message>Quote with ID: 9 not found.</message></error>\n"
+        );
+    }
+
+    #[test]
+    fn test_xml_name_sanitizes_keys() {
+        assert_eq!(xml_name("quote_count"), "quote_count");
+        assert_eq!(xml_name("1st key"), "_1st_key");
+    }
+}
@@ -0,0 +1,244 @@
+//! # OpenAPI Document
+//!
+//! Builds the OpenAPI document for the HTTP API from the `#[utoipa::path]`
+//! annotations on the handlers and the `ToSchema` response types, so it
+//! cannot drift from the code. It is served at `/api/openapi.json`, with a
+//! viewer at `/api/docs`.
+
+use crate::errors::AppError;
+use crate::responses::{ErrorResponse, QuoteResource};
+use crate::{api_handler, graphql, stream, versioning};
+use axum::{
+    http::header,
+    response::{Html, IntoResponse, Response},
+};
+use std::sync::OnceLock;
+use utoipa::openapi::{Deprecated, OpenApi as OpenApiDocument};
+use utoipa::OpenApi;
+
+/// The viewer page, with the viewer from `assets/docs` embedded so that
+/// viewing the docs loads nothing from other hosts. The spec URL is relative
+/// so the page also works behind a path prefix.
+const DOCS_PAGE: &str = concat!(
+    r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>RustQuote API</title>
+    <style>
+"#,
+    include_str!("../assets/docs/viewer.css"),
+    r#"    </style>
+  </head>
+  <body>
+    <main id="docs" data-spec-url="openapi.json">Loading the OpenAPI document…</main>
+    <script>
+"#,
+    include_str!("../assets/docs/viewer.js"),
+    r#"    </script>
+  </body>
+</html>
+"#
+);
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "RustQuote Service",
+        description = "Random and curated quotes over REST, with cards, feeds and live streams. \
+                       Versioning and deprecation rules are described in docs/api_endpoints.md."
+    ),
+    paths(
+        api_handler::health_check_handler,
+        openapi_json_handler,
+        docs_handler,
+        api_handler::get_quote_handler,
+        api_handler::get_quote_by_id_handler,
+        api_handler::get_quote_v2_handler,
+        api_handler::get_quote_by_id_v2_handler,
+        unversioned_random_quote,
+        unversioned_quote_by_id,
+        api_handler::get_quote_revisions_handler,
+        api_handler::get_similar_quotes_handler,
+        api_handler::list_quotes_by_ids_handler,
+        api_handler::batch_get_quotes_handler,
+        api_handler::count_quotes_handler,
+        api_handler::get_facets_handler,
+        api_handler::get_stats_handler,
+        api_handler::get_duplicates_handler,
+        api_handler::suggest_handler,
+        api_handler::get_quote_card_svg_handler,
+        api_handler::get_random_quote_card_svg_handler,
+        api_handler::get_quote_card_png_handler,
+        api_handler::get_random_quote_card_png_handler,
+        api_handler::list_collections_handler,
+        api_handler::get_collection_handler,
+        api_handler::get_random_collection_quote_handler,
+        api_handler::daily_rss_feed_handler,
+        api_handler::daily_atom_feed_handler,
+        api_handler::new_quotes_atom_feed_handler,
+        api_handler::daily_calendar_handler,
+        stream::sse::stream_sse_handler,
+        stream::ws::stream_ws_handler,
+        graphql::graphql_handler,
+        graphql::graphiql_handler,
+    ),
+    components(schemas(ErrorResponse)),
+    tags(
+        (name = "Status", description = "Service status."),
+        (name = "Quotes", description = "Quotes by id and at random."),
+        (name = "Cards", description = "Quotes rendered as images."),
+        (name = "Collections", description = "Curated collections of quotes."),
+        (name = "Feeds", description = "RSS, Atom and iCalendar feeds."),
+        (name = "Streams", description = "Live quote streams."),
+        (name = "GraphQL", description = "The GraphQL endpoint."),
+        (name = "Diagnostics", description = "Data quality reports for the quotes file."),
+        (name = "Documentation", description = "This document and its viewer."),
+    )
+)]
+struct ApiDoc;
+
+/// Builds the OpenAPI document.
+pub fn openapi() -> OpenApiDocument {
+    let mut document = ApiDoc::openapi();
+    // utoipa fills this from the package manifest, which declares no license.
+    document.info.license = None;
+    for path in versioning::DEPRECATED_V1_PATHS {
+        let operation = document
+            .paths
+            .paths
+            .get_mut(*path)
+            .and_then(|item| item.get.as_mut());
+        if let Some(operation) = operation {
+            operation.deprecated = Some(Deprecated::True);
+        }
+    }
+    document
+}
+
+fn openapi_json() -> &'static str {
+    static JSON: OnceLock<String> = OnceLock::new();
+    JSON.get_or_init(|| openapi().to_json().unwrap_or_default())
+}
+
+/// Handles requests to the `/api/openapi.json` endpoint.
+///
+/// # Errors
+///
+/// Returns an [`AppError::InternalServerError`] if the document could not be serialized.
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "Documentation",
+    operation_id = "getOpenApi",
+    summary = "OpenAPI document",
+    description = "This document.",
+    responses(
+        (status = 200, description = "The OpenAPI document", body = Object),
+        (status = 500, description = "The document could not be built", body = ErrorResponse),
+    )
+)]
+pub async fn openapi_json_handler() -> Result<Response, AppError> {
+    let json = openapi_json();
+    if json.is_empty() {
+        return Err(AppError::InternalServerError(
+            "Failed to build the OpenAPI document.".to_string(),
+        ));
+    }
+    Ok(([(header::CONTENT_TYPE, "application/json")], json).into_response())
+}
+
+/// Handles requests to the `/api/docs` endpoint with a viewer for the
+/// OpenAPI document.
+#[utoipa::path(
+    get,
+    path = "/api/docs",
+    tag = "Documentation",
+    operation_id = "getApiDocs",
+    summary = "API documentation viewer",
+    description = "Renders this document in the browser.",
+    responses(
+        (status = 200, description = "The viewer page", content_type = "text/html", body = String),
+    )
+)]
+pub async fn docs_handler() -> Html<&'static str> {
+    Html(DOCS_PAGE)
+}
+
+// The unversioned aliases are served by a forwarding closure, so there is no
+// handler to annotate; these stand-ins document them.
+
+#[utoipa::path(
+    get,
+    path = "/api/quote",
+    tag = "Quotes",
+    operation_id = "getRandomQuoteUnversioned",
+    summary = "Get a random quote in the requested version",
+    description = "Serves `/api/v1/quote` or `/api/v2/quote` according to the `Api-Version` header, \
+                   defaulting to v2. The body has the shape of the chosen version.",
+    params(
+        ("Api-Version" = Option<String>, Header, description = "1 or 2; defaults to 2"),
+        api_handler::QuoteQueryParams,
+    ),
+    responses(
+        (status = 200, description = "A random quote", body = QuoteResource),
+        (status = 400, description = "Unsupported API version", body = ErrorResponse),
+        (status = 404, description = "No active quotes are available", body = ErrorResponse),
+    )
+)]
+#[allow(dead_code)]
+fn unversioned_random_quote() {}
+
+#[utoipa::path(
+    get,
+    path = "/api/quote/{id}",
+    tag = "Quotes",
+    operation_id = "getQuoteUnversioned",
+    summary = "Get a quote in the requested version",
+    description = "Serves `/api/v1/quote/{id}` or `/api/v2/quote/{id}` according to the \
+                   `Api-Version` header, defaulting to v2. The body has the shape of the chosen version.",
+    params(
+        ("id" = String, Path, description = "Numeric id, uid or slug of the quote"),
+        ("Api-Version" = Option<String>, Header, description = "1 or 2; defaults to 2"),
+        api_handler::QuoteQueryParams,
+    ),
+    responses(
+        (status = 200, description = "The quote", body = QuoteResource),
+        (status = 400, description = "Unsupported API version", body = ErrorResponse),
+        (status = 404, description = "No such quote", body = ErrorResponse),
+        (status = 410, description = "The quote was deleted", body = ErrorResponse),
+    )
+)]
+#[allow(dead_code)]
+fn unversioned_quote_by_id() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_has_schemas_and_deprecations() {
+        let document = openapi();
+        let schemas = &document.components.as_ref().unwrap().schemas;
+        for name in [
+            "QuoteResponse",
+            "QuoteResource",
+            "HealthStatus",
+            "ErrorResponse",
+        ] {
+            assert!(schemas.contains_key(name), "missing schema {}", name);
+        }
+        let get = |path: &str| {
+            document.paths.paths[path]
+                .get
+                .as_ref()
+                .unwrap()
+                .deprecated
+                .clone()
+        };
+        assert!(matches!(get("/api/v1/quote/{id}"), Some(Deprecated::True)));
+        assert!(!matches!(get("/api/v2/quote/{id}"), Some(Deprecated::True)));
+        assert!(!openapi_json().is_empty());
+    }
+}
@@ -3,21 +3,26 @@
 //! This module defines the standard structures used for serializing API responses.
 //! These structs are used by the API handlers to provide consistent JSON outputs.
 
+use crate::models::{Quote, QuoteRevision, QuoteStatus};
+use crate::services::suggest_index::{SuggestKind, Suggestion};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize}; // Add Deserialize
+use utoipa::ToSchema;
 
 /// Represents the health status of the service.
 ///
 /// Used as the response for the `/api/health` endpoint.
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct HealthStatus {
     /// A string indicating the current status, e.g., "healthy".
+    #[schema(example = "healthy")]
     pub status: String,
 }
 
 /// Represents a quote response.
 ///
 /// Used as the success response for the `/api/v1/quote` endpoint.
-#[derive(Serialize, Deserialize)] // Add Deserialize
+#[derive(Serialize, Deserialize, ToSchema)] // Add Deserialize
 pub struct QuoteResponse {
     /// The unique identifier of the quote.
     pub id: u32,
@@ -27,12 +32,225 @@ pub struct QuoteResponse {
     pub author: String,
 }
 
+impl From<&Quote> for QuoteResponse {
+    fn from(quote: &Quote) -> Self {
+        QuoteResponse {
+            id: quote.id,
+            quote: quote.text.clone(),
+            author: quote.author.clone(),
+        }
+    }
+}
+
+/// Represents the full quote resource.
+///
+/// Used as the success response for the `/api/v2/quote` endpoints. Unlike
+/// [`QuoteResponse`], which `/api/v1` keeps unchanged, it includes the source,
+/// links to related resources and lifecycle metadata.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuoteResource {
+    /// The unique identifier of the quote.
+    pub id: u32,
+    /// The globally unique string identifier of the quote.
+    pub uid: String,
+    /// The human-readable, URL-safe identifier of the quote.
+    pub slug: String,
+    /// The text of the quote.
+    pub quote: String,
+    /// The author of the quote.
+    pub author: String,
+    /// Where the quote comes from, e.g. a book or speech, if known.
+    pub source: Option<String>,
+    /// Absolute links to this quote and related resources.
+    pub links: QuoteLinks,
+    /// Lifecycle information about the quote.
+    pub metadata: QuoteMetadata,
+}
+
+/// Links from a [`QuoteResource`] to related resources.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuoteLinks {
+    /// This quote under `/api/v2`.
+    #[serde(rename = "self")]
+    pub self_link: String,
+    /// The quote's revision history.
+    pub revisions: String,
+    /// The quote rendered as an SVG card.
+    pub card_svg: String,
+    /// The quote rendered as a PNG card.
+    pub card_png: String,
+}
+
+/// Lifecycle information in a [`QuoteResource`].
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuoteMetadata {
+    /// `active` or `archived`.
+    pub status: QuoteStatus,
+    /// When the quote was first added to the corpus.
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the quote's content last changed.
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Start of the quote's publishing window, if set.
+    pub publish_at: Option<DateTime<Utc>>,
+    /// End of the quote's publishing window, if set.
+    pub expire_at: Option<DateTime<Utc>>,
+    /// The corpus version the quote was served from.
+    pub corpus_version: u64,
+}
+
+impl QuoteResource {
+    /// Builds the resource for `quote`, with links under `base_url`.
+    pub fn new(quote: &Quote, corpus_version: u64, base_url: &str) -> Self {
+        let v1 = format!("{}/api/v1/quote/{}", base_url, quote.id);
+        QuoteResource {
+            id: quote.id,
+            uid: quote.uid.clone(),
+            slug: quote.slug.clone(),
+            quote: quote.text.clone(),
+            author: quote.author.clone(),
+            source: quote.source.clone(),
+            links: QuoteLinks {
+                self_link: format!("{}/api/v2/quote/{}", base_url, quote.id),
+                revisions: format!("{}/revisions", v1),
+                card_svg: format!("{}/card.svg", v1),
+                card_png: format!("{}/card.png", v1),
+            },
+            metadata: QuoteMetadata {
+                status: quote.status,
+                created_at: quote.created_at,
+                updated_at: quote.updated_at,
+                publish_at: quote.publish_at,
+                expire_at: quote.expire_at,
+                corpus_version,
+            },
+        }
+    }
+}
+
+/// Summarises a curated collection.
+///
+/// Used as the item type for the `/api/v1/collections` endpoint.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CollectionSummary {
+    /// The identifier of the collection.
+    pub id: String,
+    /// The display name of the collection.
+    pub name: String,
+    /// A description of the collection's theme.
+    pub description: String,
+    /// The number of quotes in the collection that resolve in the corpus.
+    pub quote_count: usize,
+    /// Quote IDs listed by the collection that are missing from the corpus.
+    pub missing_ids: Vec<u32>,
+}
+
+/// Represents a curated collection with its quotes.
+///
+/// Used as the success response for the `/api/v1/collections/:id` endpoint.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CollectionResponse {
+    /// The identifier of the collection.
+    pub id: String,
+    /// The display name of the collection.
+    pub name: String,
+    /// A description of the collection's theme.
+    pub description: String,
+    /// The quotes in collection order.
+    pub quotes: Vec<QuoteResponse>,
+    /// Quote IDs listed by the collection that are missing from the corpus.
+    pub missing_ids: Vec<u32>,
+}
+
+/// Represents the revision history of a single quote.
+///
+/// Used as the success response for the `/api/v1/quote/:id/revisions` endpoint.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct QuoteRevisionsResponse {
+    /// The identifier of the quote.
+    pub id: u32,
+    /// When the quote was first added, if it is still in the corpus.
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the quote last changed, if it is still in the corpus.
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The recorded revisions, oldest first.
+    pub revisions: Vec<QuoteRevision>,
+}
+
+/// The outcome of looking up one id in a bulk quote fetch.
+#[derive(Serialize, ToSchema)]
+pub struct BatchQuoteItem {
+    /// The id as requested: a numeric id, uid or slug.
+    pub id: String,
+    /// The quote, if it could be served.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<QuoteResponse>,
+    /// Why the quote could not be served, e.g. `NOT_FOUND` or `GONE`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// Represents the result of a bulk quote fetch.
+///
+/// Used as the success response for `/api/v1/quotes` and
+/// `/api/v1/quotes:batchGet`.
+#[derive(Serialize, ToSchema)]
+pub struct BatchQuotesResponse {
+    /// One item per requested id, in request order.
+    pub items: Vec<BatchQuoteItem>,
+    /// The requested ids that could not be served, in request order.
+    pub missing: Vec<String>,
+}
+
+/// A quote recommended for its similarity to another.
+#[derive(Serialize, ToSchema)]
+pub struct SimilarQuote {
+    /// Cosine similarity of the texts, from 0 to 1, plus any same-author boost.
+    pub score: f64,
+    /// The recommended quote.
+    pub quote: QuoteResponse,
+}
+
+/// Represents quotes similar to a given one.
+///
+/// Used as the success response for the `/api/v1/quote/:id/similar` endpoint.
+#[derive(Serialize, ToSchema)]
+pub struct SimilarQuotesResponse {
+    /// The identifier of the quote the others are similar to.
+    pub id: u32,
+    /// The most similar quotes, best first.
+    pub similar: Vec<SimilarQuote>,
+}
+
+/// Represents type-ahead suggestions.
+///
+/// Used as the success response for the `/api/v1/suggest` endpoint.
+#[derive(Serialize, ToSchema)]
+pub struct SuggestResponse {
+    /// The prefix as requested.
+    pub prefix: String,
+    /// What the suggestions complete.
+    pub kind: SuggestKind,
+    /// Matching authors or terms, most frequent first.
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// The number of quotes matching a filter.
+///
+/// Used as the success response for the `/api/v1/quotes/count` endpoint.
+#[derive(Serialize, ToSchema)]
+pub struct QuoteCountResponse {
+    /// The filter as requested, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// How many active, published quotes match it.
+    pub count: usize,
+}
+
 /// Represents a standardized error response for the API.
 ///
 /// This structure is used to provide consistent error messages to clients.
-/// Note: This is currently superseded by `AppError`'s `IntoResponse` implementation
-/// for direct error handling in Axum, but kept for potential future use or reference.
-#[derive(Serialize)]
+/// `AppError`'s `IntoResponse` implementation serializes every error with it.
+#[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
     /// A machine-readable error code.
     pub error_code: String,
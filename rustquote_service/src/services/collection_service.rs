@@ -0,0 +1,354 @@
+//! # Collection Service
+//!
+//! This module provides services for curated quote collections: loading them
+//! from their data file and resolving their quote IDs against the corpus.
+
+use crate::models::{Collection, Quote, QuoteStatus};
+use crate::services::quote_service::{self, QuoteServiceError};
+use crate::services::quote_store::{FileFingerprint, FileStamp};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// File name of the collections data file, which lives next to the quotes file.
+pub const COLLECTIONS_FILE_NAME: &str = "collections.json";
+
+/// Returns the collections file path for a given quotes file path.
+pub fn collections_path_for(quotes_file_path: &Path) -> PathBuf {
+    quotes_file_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(COLLECTIONS_FILE_NAME)
+}
+
+/// Loads collections from a JSON file.
+///
+/// A missing file is not an error: it simply means no collections are defined.
+///
+/// # Errors
+///
+/// Returns `QuoteServiceError` if the file exists but cannot be read or parsed.
+pub fn load_collections_from_file(file_path: &Path) -> Result<Vec<Collection>, QuoteServiceError> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file_content = fs::read_to_string(file_path).map_err(QuoteServiceError::FileReadError)?;
+
+    serde_json::from_str(&file_content).map_err(QuoteServiceError::ParseError)
+}
+
+#[derive(Debug)]
+struct Loaded {
+    /// `None` when the file did not exist.
+    stamp: Option<FileStamp>,
+    /// `None` when the file did not exist.
+    fingerprint: Option<FileFingerprint>,
+    collections: Arc<Vec<Collection>>,
+}
+
+/// Caches the collections of a data file and parses them again only when
+/// the file changes, the way the quote store does for quotes: the file is
+/// only read when its metadata changed.
+#[derive(Debug)]
+pub struct CollectionStore {
+    file_path: PathBuf,
+    loaded: RwLock<Option<Loaded>>,
+}
+
+impl CollectionStore {
+    /// Creates a store for the given collections data file. Nothing is read
+    /// until [`CollectionStore::current`] is first called.
+    pub fn new(file_path: PathBuf) -> Self {
+        CollectionStore {
+            file_path,
+            loaded: RwLock::new(None),
+        }
+    }
+
+    /// The path of the collections data file backing this store.
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Returns the current collections, parsing the data file again if it
+    /// changed since the last load. A missing file means no collections.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuoteServiceError`] if the file exists but cannot be read or
+    /// parsed.
+    pub async fn current(self: &Arc<Self>) -> Result<Arc<Vec<Collection>>, QuoteServiceError> {
+        let store = Arc::clone(self);
+        tokio::task::spawn_blocking(move || store.load_current())
+            .await
+            .map_err(|err| QuoteServiceError::FileReadError(io::Error::other(err)))?
+    }
+
+    /// The blocking part of [`CollectionStore::current`].
+    fn load_current(&self) -> Result<Arc<Vec<Collection>>, QuoteServiceError> {
+        let stamp = match fs::metadata(&self.file_path) {
+            Ok(metadata) => Some(FileStamp::of(&metadata)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(QuoteServiceError::FileReadError(err)),
+        };
+        if let Some(cached) = self
+            .loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|l| match (&stamp, &l.stamp) {
+                (Some(stamp), Some(earlier)) => stamp.unchanged_since(earlier),
+                (None, None) => true,
+                _ => false,
+            })
+        {
+            return Ok(Arc::clone(&cached.collections));
+        }
+
+        let content = match fs::read_to_string(&self.file_path) {
+            Ok(content) => Some(content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(QuoteServiceError::FileReadError(err)),
+        };
+        let fingerprint = content.as_deref().map(FileFingerprint::of);
+        let unchanged = self
+            .loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|l| l.fingerprint == fingerprint)
+            .map(|l| Arc::clone(&l.collections));
+        if let Some(collections) = unchanged {
+            // Touched without changes: remember the new stamp.
+            self.install(stamp, fingerprint, Arc::clone(&collections));
+            return Ok(collections);
+        }
+
+        let collections = match content {
+            Some(content) => {
+                serde_json::from_str(&content).map_err(QuoteServiceError::ParseError)?
+            }
+            None => Vec::new(),
+        };
+        let collections = Arc::new(collections);
+        self.install(stamp, fingerprint, Arc::clone(&collections));
+        Ok(collections)
+    }
+
+    fn install(
+        &self,
+        stamp: Option<FileStamp>,
+        fingerprint: Option<FileFingerprint>,
+        collections: Arc<Vec<Collection>>,
+    ) {
+        *self.loaded.write().unwrap_or_else(|e| e.into_inner()) = Some(Loaded {
+            stamp,
+            fingerprint,
+            collections,
+        });
+    }
+}
+
+/// Finds a collection by its ID.
+pub fn get_collection_by_id<'a>(collections: &'a [Collection], id: &str) -> Option<&'a Collection> {
+    collections.iter().find(|c| c.id == id)
+}
+
+/// A collection's quote IDs resolved against the corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedCollection {
+    /// The quotes that exist, in collection order.
+    pub quotes: Vec<Quote>,
+    /// IDs that are not in the corpus, whose quote is deleted, or whose quote
+    /// is outside its publishing window, in collection order.
+    pub missing_ids: Vec<u32>,
+}
+
+/// Resolves a collection's quote IDs against the loaded quotes, evaluating
+/// publishing windows at instant `at`.
+///
+/// Archived quotes are kept, since they remain fetchable by ID; deleted,
+/// unpublished, expired and unknown IDs are reported in
+/// [`ResolvedCollection::missing_ids`], as fetching them by ID would fail.
+pub fn resolve_collection(
+    collection: &Collection,
+    quotes: &[Quote],
+    at: DateTime<Utc>,
+) -> ResolvedCollection {
+    let mut resolved = ResolvedCollection {
+        quotes: Vec::with_capacity(collection.quote_ids.len()),
+        missing_ids: Vec::new(),
+    };
+    for &id in &collection.quote_ids {
+        match quote_service::get_quote_by_id(quotes, id) {
+            Some(quote) if quote.status != QuoteStatus::Deleted && quote.is_published_at(at) => {
+                resolved.quotes.push(quote.clone())
+            }
+            _ => resolved.missing_ids.push(id),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn quote(id: u32, status: QuoteStatus) -> Quote {
+        Quote {
+            status,
+            ..fixtures::quote(id, &format!("Quote {}", id), "Author", None)
+        }
+    }
+
+    #[test]
+    fn test_collections_path_for() {
+        assert_eq!(
+            collections_path_for(Path::new("data/quotes.json")),
+            PathBuf::from("data/collections.json")
+        );
+        assert_eq!(
+            collections_path_for(Path::new("quotes.json")),
+            PathBuf::from("collections.json")
+        );
+    }
+
+    #[test]
+    fn test_load_collections_successfully() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"[{{"id":"monday","name":"Monday","description":"Start strong","quote_ids":[2,1]}}]"#
+        )
+        .unwrap();
+        let collections = load_collections_from_file(file.path()).unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(collections[0].quote_ids, vec![2, 1]);
+        assert!(get_collection_by_id(&collections, "monday").is_some());
+        assert!(get_collection_by_id(&collections, "friday").is_none());
+    }
+
+    #[test]
+    fn test_load_collections_missing_file_is_empty() {
+        let collections =
+            load_collections_from_file(Path::new("non_existent_collections.json")).unwrap();
+        assert!(collections.is_empty());
+    }
+
+    #[test]
+    fn test_load_collections_invalid_json() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "[{{").unwrap();
+        assert!(matches!(
+            load_collections_from_file(file.path()),
+            Err(QuoteServiceError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_collection_store_reparses_only_when_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(COLLECTIONS_FILE_NAME);
+        let store = CollectionStore::new(path.clone());
+        assert!(store.load_current().unwrap().is_empty());
+
+        std::fs::write(
+            &path,
+            r#"[{"id":"monday","name":"Monday","description":"","quote_ids":[1]}]"#,
+        )
+        .unwrap();
+        let first = store.load_current().unwrap();
+        let again = store.load_current().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(first[0].quote_ids, vec![1]);
+
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let set_modified = |at| {
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(at)
+                .unwrap()
+        };
+        // Touching the file without changing it keeps the parsed collections.
+        set_modified(modified + std::time::Duration::from_secs(1));
+        assert!(Arc::ptr_eq(&first, &store.load_current().unwrap()));
+
+        std::fs::write(
+            &path,
+            r#"[{"id":"monday","name":"Monday","description":"","quote_ids":[2]}]"#,
+        )
+        .unwrap();
+        set_modified(modified + std::time::Duration::from_secs(2));
+        assert_eq!(store.load_current().unwrap()[0].quote_ids, vec![2]);
+
+        std::fs::write(&path, "[{").unwrap();
+        assert!(matches!(
+            store.load_current(),
+            Err(QuoteServiceError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_collection_reports_missing_ids_in_order() {
+        let quotes = vec![
+            quote(1, QuoteStatus::Active),
+            quote(2, QuoteStatus::Archived),
+            quote(3, QuoteStatus::Deleted),
+        ];
+        let collection = Collection {
+            id: "mixed".to_string(),
+            name: "Mixed".to_string(),
+            description: String::new(),
+            quote_ids: vec![2, 9, 1, 3],
+        };
+        let resolved = resolve_collection(&collection, &quotes, Utc::now());
+        assert_eq!(
+            resolved.quotes.iter().map(|q| q.id).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(resolved.missing_ids, vec![9, 3]);
+    }
+
+    #[test]
+    fn test_resolve_collection_respects_publishing_windows() {
+        let now = Utc::now();
+        let quotes = vec![
+            quote(1, QuoteStatus::Active),
+            Quote {
+                publish_at: Some(now + chrono::Duration::days(1)),
+                ..quote(2, QuoteStatus::Active)
+            },
+            Quote {
+                expire_at: Some(now),
+                ..quote(3, QuoteStatus::Active)
+            },
+        ];
+        let collection = Collection {
+            id: "scheduled".to_string(),
+            name: "Scheduled".to_string(),
+            description: String::new(),
+            quote_ids: vec![2, 1, 3],
+        };
+
+        let resolved = resolve_collection(&collection, &quotes, now);
+        assert_eq!(
+            resolved.quotes.iter().map(|q| q.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(resolved.missing_ids, vec![2, 3]);
+
+        let preview = resolve_collection(&collection, &quotes, now + chrono::Duration::days(2));
+        assert_eq!(
+            preview.quotes.iter().map(|q| q.id).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+}
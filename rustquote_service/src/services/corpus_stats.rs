@@ -0,0 +1,241 @@
+//! # Corpus Statistics
+//!
+//! Summarises a quote corpus for editors: how many quotes there are and in
+//! which state, who wrote them, how long they are, which lack a source and
+//! which texts appear more than once. The quote store computes this once per
+//! load of the quotes file, and `/api/v1/stats` serves it as is.
+
+use crate::models::{Quote, QuoteStatus};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Percentiles reported for quote lengths.
+const PERCENTILES: [u8; 5] = [25, 50, 75, 90, 99];
+
+/// Statistics about one version of the corpus.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CorpusStats {
+    /// The corpus version these statistics describe.
+    pub corpus_version: u64,
+    /// When that version of the corpus was loaded.
+    pub loaded_at: DateTime<Utc>,
+    /// Every quote in the data file, whatever its status.
+    pub total_quotes: usize,
+    /// Quotes per lifecycle status.
+    pub by_status: StatusCounts,
+    /// Quotes per author, most prolific first.
+    pub authors: Vec<AuthorCount>,
+    /// Distribution of quote lengths in characters.
+    pub length: LengthStats,
+    /// Quotes without a source, or with a blank one.
+    pub missing_source: usize,
+    /// Quotes whose text also appears in another quote.
+    pub duplicates: DuplicateStats,
+}
+
+/// Quote counts per [`QuoteStatus`].
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct StatusCounts {
+    pub active: usize,
+    pub archived: usize,
+    pub deleted: usize,
+}
+
+/// The number of quotes by one author.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuthorCount {
+    pub author: String,
+    pub quotes: usize,
+}
+
+/// Distribution of quote lengths, in characters. All values are 0 for an
+/// empty corpus.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct LengthStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// Nearest-rank percentiles, keyed `p25`, `p50`, `p75`, `p90` and `p99`.
+    #[schema(example = json!({"p25": 42, "p50": 67, "p75": 98, "p90": 140, "p99": 260}))]
+    pub percentiles: BTreeMap<String, usize>,
+}
+
+/// Quotes sharing the same text.
+///
+/// Only ids are reported: the statistics are public and cover deleted and
+/// scheduled quotes, whose texts must not be revealed.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct DuplicateStats {
+    /// Number of distinct texts that appear more than once.
+    pub groups: usize,
+    /// Number of quotes in those groups.
+    pub quotes: usize,
+    /// The ids of the quotes in each group, in file order; largest group first.
+    #[schema(example = json!([[1, 2]]))]
+    pub ids: Vec<Vec<u32>>,
+}
+
+impl CorpusStats {
+    /// Computes the statistics for `quotes`, loaded as `corpus_version` at `loaded_at`.
+    pub fn compute(quotes: &[Quote], corpus_version: u64, loaded_at: DateTime<Utc>) -> Self {
+        let mut by_status = StatusCounts::default();
+        for quote in quotes {
+            match quote.status {
+                QuoteStatus::Active => by_status.active += 1,
+                QuoteStatus::Archived => by_status.archived += 1,
+                QuoteStatus::Deleted => by_status.deleted += 1,
+            }
+        }
+
+        CorpusStats {
+            corpus_version,
+            loaded_at,
+            total_quotes: quotes.len(),
+            by_status,
+            authors: author_counts(quotes),
+            length: length_stats(quotes),
+            missing_source: quotes
+                .iter()
+                .filter(|q| q.source.as_deref().is_none_or(|s| s.trim().is_empty()))
+                .count(),
+            duplicates: duplicate_stats(quotes),
+        }
+    }
+}
+
+fn author_counts(quotes: &[Quote]) -> Vec<AuthorCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for quote in quotes {
+        *counts.entry(quote.author.trim()).or_default() += 1;
+    }
+    let mut authors: Vec<AuthorCount> = counts
+        .into_iter()
+        .map(|(author, quotes)| AuthorCount {
+            author: author.to_string(),
+            quotes,
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.quotes
+            .cmp(&a.quotes)
+            .then_with(|| a.author.cmp(&b.author))
+    });
+    authors
+}
+
+fn length_stats(quotes: &[Quote]) -> LengthStats {
+    let mut lengths: Vec<usize> = quotes.iter().map(|q| q.text.chars().count()).collect();
+    if lengths.is_empty() {
+        return LengthStats {
+            percentiles: PERCENTILES.iter().map(|p| (format!("p{}", p), 0)).collect(),
+            ..LengthStats::default()
+        };
+    }
+    lengths.sort_unstable();
+    let percentiles = PERCENTILES
+        .iter()
+        .map(|&p| (format!("p{}", p), nearest_rank(&lengths, p)))
+        .collect();
+    LengthStats {
+        min: lengths[0],
+        max: lengths[lengths.len() - 1],
+        mean: lengths.iter().sum::<usize>() as f64 / lengths.len() as f64,
+        percentiles,
+    }
+}
+
+/// The nearest-rank `percentile` of non-empty, sorted `values`.
+fn nearest_rank(values: &[usize], percentile: u8) -> usize {
+    let rank = (usize::from(percentile) * values.len()).div_ceil(100);
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+/// Texts are compared ignoring case and runs of whitespace.
+fn duplicate_stats(quotes: &[Quote]) -> DuplicateStats {
+    let mut groups: Vec<Vec<u32>> = Vec::new();
+    let mut by_text: HashMap<String, usize> = HashMap::new();
+    for quote in quotes {
+        let key = quote
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        match by_text.get(&key) {
+            Some(&index) => groups[index].push(quote.id),
+            None => {
+                by_text.insert(key, groups.len());
+                groups.push(vec![quote.id]);
+            }
+        }
+    }
+
+    let mut ids: Vec<Vec<u32>> = groups.into_iter().filter(|g| g.len() > 1).collect();
+    // Stable, so equally large groups stay in file order.
+    ids.sort_by_key(|g| std::cmp::Reverse(g.len()));
+    DuplicateStats {
+        groups: ids.len(),
+        quotes: ids.iter().map(Vec::len).sum(),
+        ids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    #[test]
+    fn test_compute_stats() {
+        let now = Utc::now();
+        let quotes = vec![
+            quote(1, "Less is more.", "Mies", Some("Interview")),
+            quote(2, "less  is MORE.", "Mies", None),
+            quote(3, "Stay hungry, stay foolish.", "Steve Jobs", Some(" ")),
+            Quote {
+                status: QuoteStatus::Archived,
+                ..quote(4, "Less is more.", "Browning", Some("Andrea del Sarto"))
+            },
+        ];
+        let stats = CorpusStats::compute(&quotes, 3, now);
+
+        assert_eq!(stats.corpus_version, 3);
+        assert_eq!(stats.total_quotes, 4);
+        assert_eq!(stats.by_status.active, 3);
+        assert_eq!(stats.by_status.archived, 1);
+        let authors: Vec<(&str, usize)> = stats
+            .authors
+            .iter()
+            .map(|a| (a.author.as_str(), a.quotes))
+            .collect();
+        assert_eq!(authors, [("Mies", 2), ("Browning", 1), ("Steve Jobs", 1)]);
+        assert_eq!(stats.missing_source, 2);
+
+        assert_eq!(stats.length.min, 13);
+        assert_eq!(stats.length.max, 26);
+        assert_eq!(stats.length.percentiles["p50"], 13);
+        assert_eq!(stats.length.percentiles["p75"], 14);
+        assert_eq!(stats.length.percentiles["p99"], 26);
+
+        assert_eq!(stats.duplicates.groups, 1);
+        assert_eq!(stats.duplicates.quotes, 3);
+        assert_eq!(stats.duplicates.ids, [vec![1, 2, 4]]);
+    }
+
+    #[test]
+    fn test_nearest_rank_and_empty_corpus() {
+        let values = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(nearest_rank(&values, 25), 3);
+        assert_eq!(nearest_rank(&values, 50), 5);
+        assert_eq!(nearest_rank(&values, 99), 10);
+        assert_eq!(nearest_rank(&[7], 25), 7);
+
+        let stats = CorpusStats::compute(&[], 1, Utc::now());
+        assert_eq!(stats.total_quotes, 0);
+        assert_eq!(stats.length.max, 0);
+        assert_eq!(stats.length.percentiles["p90"], 0);
+        assert!(stats.authors.is_empty());
+    }
+}
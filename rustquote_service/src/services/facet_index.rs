@@ -0,0 +1,359 @@
+//! # Facet Index
+//!
+//! Counts for filter sidebars: how many quotes matching a search are by each
+//! author, come from each source and fall in each length bucket. The quote
+//! store builds a [`FacetIndex`] once per load of the quotes file, so a query
+//! intersects precomputed posting lists and tallies compact per-quote facet
+//! ids instead of re-reading every quote.
+//!
+//! Only active quotes are indexed; publishing windows are checked when
+//! counting, against the instant asked for.
+
+use crate::models::{PublishWindow, Quote};
+use crate::utils::{match_key, words};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Number of values listed per facet when no limit is requested.
+pub const DEFAULT_FACET_LIMIT: usize = 10;
+
+/// Maximum number of values listed per facet.
+pub const MAX_FACET_LIMIT: usize = 100;
+
+/// Length buckets as inclusive `(min, max)` character counts; the last one
+/// is open-ended.
+const LENGTH_BUCKETS: [(usize, Option<usize>); 4] =
+    [(0, Some(49)), (50, Some(99)), (100, Some(199)), (200, None)];
+
+/// What to count: quotes containing every word of `text`, by `author` and
+/// between `min_len` and `max_len` characters, all optional.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FacetQuery<'a> {
+    pub text: Option<&'a str>,
+    pub author: Option<&'a str>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+}
+
+/// Facet counts over the quotes matching a [`FacetQuery`].
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Facets {
+    /// The number of matching quotes.
+    pub total: usize,
+    /// Matching quotes per author.
+    pub authors: FacetCounts,
+    /// Matching quotes per source; a `null` value counts quotes without one.
+    pub sources: FacetCounts,
+    /// Matching quotes per length bucket, shortest first, including empty buckets.
+    pub lengths: Vec<LengthBucket>,
+}
+
+/// The values of one facet.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FacetCounts {
+    /// How many values have at least one matching quote.
+    pub distinct: usize,
+    /// The values with the most matching quotes, ties in alphabetical order.
+    pub values: Vec<FacetValue>,
+}
+
+/// A facet value and its number of matching quotes.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FacetValue {
+    pub value: Option<String>,
+    pub count: usize,
+}
+
+/// A range of quote lengths, in characters, and its number of matching quotes.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct LengthBucket {
+    pub min: usize,
+    /// Inclusive; `null` for the open-ended last bucket.
+    pub max: Option<usize>,
+    pub count: usize,
+}
+
+/// Facet ids of one indexed quote.
+#[derive(Debug, Clone)]
+struct Entry {
+    author: usize,
+    source: Option<usize>,
+    len: usize,
+    window: PublishWindow,
+}
+
+/// Precomputed facet data for the active quotes of a corpus.
+#[derive(Debug, Clone, Default)]
+pub struct FacetIndex {
+    entries: Vec<Entry>,
+    /// Display names by author id, as first written.
+    authors: Vec<String>,
+    /// Display names by source id, as first written.
+    sources: Vec<String>,
+    /// Entries by folded author, in ascending order.
+    by_author: HashMap<String, Vec<usize>>,
+    /// Entries by folded word of their text, in ascending order.
+    by_word: HashMap<String, Vec<usize>>,
+    /// `(length, entry)` pairs sorted by length.
+    by_length: Vec<(usize, usize)>,
+}
+
+impl FacetIndex {
+    /// Indexes the active quotes in `quotes`.
+    pub fn build(quotes: &[Quote]) -> Self {
+        let mut index = FacetIndex::default();
+        let mut author_ids: HashMap<String, usize> = HashMap::new();
+        let mut source_ids: HashMap<String, usize> = HashMap::new();
+        for quote in quotes.iter().filter(|q| q.status.is_active()) {
+            let entry = index.entries.len();
+            let author_key = match_key(&quote.author);
+            let author = *author_ids.entry(author_key.clone()).or_insert_with(|| {
+                index.authors.push(quote.author.trim().to_string());
+                index.authors.len() - 1
+            });
+            index.by_author.entry(author_key).or_default().push(entry);
+
+            let source = quote
+                .source
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|source| {
+                    *source_ids.entry(match_key(source)).or_insert_with(|| {
+                        index.sources.push(source.to_string());
+                        index.sources.len() - 1
+                    })
+                });
+
+            for word in words(&quote.text) {
+                let postings = index.by_word.entry(word).or_default();
+                if postings.last() != Some(&entry) {
+                    postings.push(entry);
+                }
+            }
+
+            let len = quote.text.chars().count();
+            index.by_length.push((len, entry));
+            index.entries.push(Entry {
+                author,
+                source,
+                len,
+                window: quote.publish_window(),
+            });
+        }
+        index.by_length.sort_unstable();
+        index
+    }
+
+    /// Counts the quotes matching `query` that are published at `at`,
+    /// listing up to `limit` values per author and source facet.
+    pub fn facets(&self, query: &FacetQuery, at: DateTime<Utc>, limit: usize) -> Facets {
+        let min_len = query.min_len.unwrap_or(0);
+        let max_len = query.max_len.unwrap_or(usize::MAX);
+
+        let mut candidates: Option<Vec<usize>> = None;
+        if let Some(author) = query.author {
+            let postings = self.by_author.get(&match_key(author));
+            candidates = Some(postings.cloned().unwrap_or_default());
+        }
+        for word in query.text.map(words).unwrap_or_default() {
+            let postings = self.by_word.get(&word).map_or(&[][..], Vec::as_slice);
+            candidates = Some(match candidates {
+                Some(current) => intersect(&current, postings),
+                None => postings.to_vec(),
+            });
+        }
+        let candidates = candidates.unwrap_or_else(|| {
+            let start = self.by_length.partition_point(|&(len, _)| len < min_len);
+            let end = self.by_length.partition_point(|&(len, _)| len <= max_len);
+            self.by_length
+                .get(start..end.max(start))
+                .unwrap_or_default()
+                .iter()
+                .map(|&(_, entry)| entry)
+                .collect()
+        });
+
+        let mut total = 0;
+        let mut author_counts = vec![0; self.authors.len()];
+        let mut source_counts = vec![0; self.sources.len()];
+        let mut no_source = 0;
+        let mut lengths: Vec<LengthBucket> = LENGTH_BUCKETS
+            .iter()
+            .map(|&(min, max)| LengthBucket { min, max, count: 0 })
+            .collect();
+        for entry in candidates.into_iter().map(|i| &self.entries[i]) {
+            if entry.len < min_len || entry.len > max_len || !entry.window.contains(at) {
+                continue;
+            }
+            total += 1;
+            author_counts[entry.author] += 1;
+            match entry.source {
+                Some(source) => source_counts[source] += 1,
+                None => no_source += 1,
+            }
+            if let Some(bucket) = lengths
+                .iter_mut()
+                .rev()
+                .find(|bucket| bucket.min <= entry.len)
+            {
+                bucket.count += 1;
+            }
+        }
+
+        let authors = author_counts
+            .into_iter()
+            .enumerate()
+            .map(|(id, count)| (Some(self.authors[id].clone()), count));
+        let sources = source_counts
+            .into_iter()
+            .enumerate()
+            .map(|(id, count)| (Some(self.sources[id].clone()), count))
+            .chain(std::iter::once((None, no_source)));
+        Facets {
+            total,
+            authors: facet_counts(authors, limit),
+            sources: facet_counts(sources, limit),
+            lengths,
+        }
+    }
+}
+
+/// Keeps the values with a count, most frequent first, ties alphabetically
+/// with `null` last.
+fn facet_counts(
+    counts: impl Iterator<Item = (Option<String>, usize)>,
+    limit: usize,
+) -> FacetCounts {
+    let mut values: Vec<FacetValue> = counts
+        .filter(|&(_, count)| count > 0)
+        .map(|(value, count)| FacetValue { value, count })
+        .collect();
+    values.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.value.is_none().cmp(&b.value.is_none()))
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    let distinct = values.len();
+    values.truncate(limit);
+    FacetCounts { distinct, values }
+}
+
+/// The entries present in both ascending lists.
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut both = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                both.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    both
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
+    use chrono::Duration;
+
+    fn values(counts: &FacetCounts) -> Vec<(Option<&str>, usize)> {
+        counts
+            .values
+            .iter()
+            .map(|v| (v.value.as_deref(), v.count))
+            .collect()
+    }
+
+    fn corpus() -> Vec<Quote> {
+        vec![
+            quote(
+                1,
+                "Stay hungry, stay foolish.",
+                "Steve Jobs",
+                Some("Stanford"),
+            ),
+            quote(
+                2,
+                "Simplicity is the ultimate sophistication.",
+                "Steve Jobs",
+                None,
+            ),
+            quote(3, "Stay the course.", "émile zola", Some("stanford")),
+            quote(4, &"Long ".repeat(30), "Émile Zola", Some(" ")),
+            Quote {
+                status: QuoteStatus::Archived,
+                ..quote(5, "Stay away.", "Archived", None)
+            },
+        ]
+    }
+
+    #[test]
+    fn test_facets_without_filters() {
+        let index = FacetIndex::build(&corpus());
+        let facets = index.facets(&FacetQuery::default(), Utc::now(), 10);
+
+        assert_eq!(facets.total, 4);
+        assert_eq!(
+            values(&facets.authors),
+            [(Some("Steve Jobs"), 2), (Some("émile zola"), 2)]
+        );
+        // Sources are matched ignoring case; blank ones count as missing.
+        assert_eq!(values(&facets.sources), [(Some("Stanford"), 2), (None, 2)]);
+        let lengths: Vec<usize> = facets.lengths.iter().map(|b| b.count).collect();
+        assert_eq!(lengths, [3, 0, 1, 0]);
+        let limited = index.facets(&FacetQuery::default(), Utc::now(), 1);
+        assert_eq!(limited.authors.distinct, 2);
+        assert_eq!(limited.authors.values.len(), 1);
+    }
+
+    #[test]
+    fn test_facets_with_filters() {
+        let mut quotes = corpus();
+        quotes[0].publish_at = Some(Utc::now() + Duration::days(1));
+        let index = FacetIndex::build(&quotes);
+        let now = Utc::now();
+
+        let query = FacetQuery {
+            text: Some("STAY"),
+            ..FacetQuery::default()
+        };
+        let facets = index.facets(&query, now, 10);
+        assert_eq!(facets.total, 1);
+        assert_eq!(values(&facets.authors), [(Some("émile zola"), 1)]);
+        // Scheduled quotes count once published.
+        assert_eq!(index.facets(&query, now + Duration::days(2), 10).total, 2);
+
+        let query = FacetQuery {
+            author: Some("Emile Zola"),
+            min_len: Some(20),
+            ..FacetQuery::default()
+        };
+        assert_eq!(index.facets(&query, now, 10).total, 1);
+
+        let query = FacetQuery {
+            min_len: Some(10),
+            max_len: Some(50),
+            ..FacetQuery::default()
+        };
+        let facets = index.facets(&query, now, 10);
+        assert_eq!(facets.total, 2);
+        assert_eq!(values(&facets.sources), [(Some("Stanford"), 1), (None, 1)]);
+
+        let query = FacetQuery {
+            text: Some("stay nowhere"),
+            ..FacetQuery::default()
+        };
+        assert_eq!(index.facets(&query, now, 10).total, 0);
+    }
+}
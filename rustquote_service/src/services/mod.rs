@@ -3,4 +3,11 @@
 //! This module encapsulates the business logic of the application.
 //! It contains sub-modules for different services, such as quote handling.
 
+pub mod collection_service;
+pub mod corpus_stats;
+pub mod facet_index;
+pub mod near_duplicates;
 pub mod quote_service;
+pub mod quote_store;
+pub mod similarity;
+pub mod suggest_index;
@@ -0,0 +1,304 @@
+//! # Near-Duplicate Detection
+//!
+//! Finds quotes that are the same quote written slightly differently, as
+//! happens when corpora from several sources are merged: different
+//! punctuation, casing, accents or a changed word or two.
+//!
+//! Texts are folded into their words (see [`words`]) and compared as sets of
+//! character trigrams. Two quotes are near duplicates when the Jaccard
+//! similarity of their trigram sets reaches [`NEAR_DUPLICATE_SIMILARITY`].
+//! To avoid comparing every pair, candidates are found with MinHash
+//! signatures and locality-sensitive hashing; each candidate pair is then
+//! checked against the exact similarity. Near duplicates are grouped into
+//! clusters, so a quote similar to either member of a pair joins their
+//! cluster.
+//!
+//! The quote store builds a [`DuplicateReport`] once per load of the quotes
+//! file, and `/api/v1/diagnostics/duplicates` serves it as is to admins.
+
+use crate::models::Quote;
+use crate::utils::words;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use utoipa::ToSchema;
+
+/// Jaccard similarity of trigram sets from which two quotes are near duplicates.
+pub const NEAR_DUPLICATE_SIMILARITY: f64 = 0.7;
+
+/// Characters per shingle.
+const SHINGLE_LEN: usize = 3;
+
+/// Signature bands; quotes agreeing on all rows of any band are candidates.
+const BANDS: usize = 16;
+
+/// Signature rows per band. With 16 bands of 4 rows, a pair at the
+/// similarity threshold becomes a candidate with a probability above 98%.
+const ROWS: usize = 4;
+
+/// Near-duplicate clusters in one version of the corpus.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct DuplicateReport {
+    /// The corpus version this report describes.
+    pub corpus_version: u64,
+    /// Similarity from which two quotes count as near duplicates.
+    pub similarity_threshold: f64,
+    /// Every quote in the data file, whatever its status.
+    pub total_quotes: usize,
+    /// Quotes that duplicate another one: every quote in a cluster but the first.
+    pub duplicate_quotes: usize,
+    /// `duplicate_quotes` as a fraction of `total_quotes`, 0 for an empty corpus.
+    pub duplicate_rate: f64,
+    /// The clusters, largest first.
+    pub clusters: Vec<DuplicateCluster>,
+}
+
+/// Quotes that are near duplicates of each other.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateCluster {
+    /// The lowest similarity found between two quotes of the cluster.
+    pub similarity: f64,
+    /// The quotes in the cluster, in file order.
+    pub quotes: Vec<DuplicateMember>,
+}
+
+/// A quote in a [`DuplicateCluster`].
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateMember {
+    pub id: u32,
+    pub quote: String,
+    pub author: String,
+}
+
+impl DuplicateReport {
+    /// Finds the near duplicates among `quotes`, loaded as `corpus_version`.
+    pub fn detect(quotes: &[Quote], corpus_version: u64) -> Self {
+        let shingles: Vec<HashSet<u64>> = quotes.iter().map(|q| shingles(&q.text)).collect();
+        let signatures: Vec<Vec<u64>> = shingles.iter().map(signature).collect();
+
+        let mut clusters = UnionFind::new(quotes.len());
+        let mut links: Vec<(usize, f64)> = Vec::new();
+        let mut compared: HashSet<(usize, usize)> = HashSet::new();
+        for band in 0..BANDS {
+            let rows = band * ROWS..(band + 1) * ROWS;
+            let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+            for (index, signature) in signatures.iter().enumerate() {
+                if !shingles[index].is_empty() {
+                    buckets
+                        .entry(&signature[rows.clone()])
+                        .or_default()
+                        .push(index);
+                }
+            }
+            for bucket in buckets.values().filter(|b| b.len() > 1) {
+                for (i, &a) in bucket.iter().enumerate() {
+                    for &b in &bucket[i + 1..] {
+                        if !compared.insert((a, b)) {
+                            continue;
+                        }
+                        let similarity = jaccard(&shingles[a], &shingles[b]);
+                        if similarity >= NEAR_DUPLICATE_SIMILARITY {
+                            clusters.union(a, b);
+                            links.push((a, similarity));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut lowest_similarity: HashMap<usize, f64> = HashMap::new();
+        for (index, similarity) in links {
+            let lowest = lowest_similarity
+                .entry(clusters.find(index))
+                .or_insert(similarity);
+            *lowest = lowest.min(similarity);
+        }
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for index in 0..quotes.len() {
+            members.entry(clusters.find(index)).or_default().push(index);
+        }
+        let mut clusters: Vec<DuplicateCluster> = members
+            .into_iter()
+            .filter(|(_, indexes)| indexes.len() > 1)
+            .map(|(root, indexes)| DuplicateCluster {
+                similarity: lowest_similarity.get(&root).copied().unwrap_or(1.0),
+                quotes: indexes
+                    .into_iter()
+                    .map(|i| DuplicateMember {
+                        id: quotes[i].id,
+                        quote: quotes[i].text.clone(),
+                        author: quotes[i].author.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        clusters.sort_by(|a, b| {
+            b.quotes
+                .len()
+                .cmp(&a.quotes.len())
+                .then_with(|| a.quotes[0].id.cmp(&b.quotes[0].id))
+        });
+
+        let duplicate_quotes: usize = clusters.iter().map(|c| c.quotes.len() - 1).sum();
+        DuplicateReport {
+            corpus_version,
+            similarity_threshold: NEAR_DUPLICATE_SIMILARITY,
+            total_quotes: quotes.len(),
+            duplicate_quotes,
+            duplicate_rate: if quotes.is_empty() {
+                0.0
+            } else {
+                duplicate_quotes as f64 / quotes.len() as f64
+            },
+            clusters,
+        }
+    }
+}
+
+/// Hashes of the character trigrams of the folded words of `text`. A text
+/// shorter than a trigram is a single shingle.
+fn shingles(text: &str) -> HashSet<u64> {
+    let chars: Vec<char> = words(text).join(" ").chars().collect();
+    if chars.is_empty() {
+        return HashSet::new();
+    }
+    chars
+        .windows(SHINGLE_LEN.min(chars.len()))
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// The MinHash signature of a shingle set: for each of `BANDS * ROWS` hash
+/// functions, the smallest hash of any shingle.
+fn signature(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..BANDS * ROWS)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|&shingle| mix(shingle ^ (seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// The SplitMix64 finalizer, a cheap bijective scramble of 64 bits.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Disjoint sets of quote positions, merged as near duplicates are found.
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    fn cluster_ids(report: &DuplicateReport) -> Vec<Vec<u32>> {
+        report
+            .clusters
+            .iter()
+            .map(|c| c.quotes.iter().map(|q| q.id).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_punctuation_and_wording_variants() {
+        let quotes = [
+            quote(
+                1,
+                "The only way to do great work is to love what you do.",
+                "Author",
+                None,
+            ),
+            quote(2, "Stay hungry, stay foolish.", "Author", None),
+            quote(3, "Stay hungry; stay FOOLISH!", "Author", None),
+            quote(
+                4,
+                "The only way to do great work is loving what you do",
+                "Author",
+                None,
+            ),
+            quote(
+                5,
+                "Simplicity is the ultimate sophistication.",
+                "Author",
+                None,
+            ),
+            quote(
+                6,
+                "The only way to do great work is to love what you do",
+                "Author",
+                None,
+            ),
+        ];
+        let report = DuplicateReport::detect(&quotes, 2);
+
+        assert_eq!(report.corpus_version, 2);
+        assert_eq!(cluster_ids(&report), [vec![1, 4, 6], vec![2, 3]]);
+        assert!(report.clusters[0].similarity >= NEAR_DUPLICATE_SIMILARITY);
+        assert!(report.clusters[0].similarity < 1.0);
+        assert_eq!(report.clusters[1].similarity, 1.0);
+        assert_eq!(
+            report.clusters[1].quotes[1].quote,
+            "Stay hungry; stay FOOLISH!"
+        );
+        assert_eq!(report.duplicate_quotes, 3);
+        assert_eq!(report.duplicate_rate, 0.5);
+    }
+
+    #[test]
+    fn test_distinct_and_empty_corpora() {
+        let report = DuplicateReport::detect(
+            &[
+                quote(1, "Less is more.", "Author", None),
+                quote(2, "More is less.", "Author", None),
+                quote(3, "?!", "Author", None),
+                quote(4, "...", "Author", None),
+            ],
+            1,
+        );
+        assert!(report.clusters.is_empty());
+        assert_eq!(report.duplicate_rate, 0.0);
+
+        let report = DuplicateReport::detect(&[], 1);
+        assert_eq!(report.total_quotes, 0);
+        assert_eq!(report.duplicate_rate, 0.0);
+    }
+}
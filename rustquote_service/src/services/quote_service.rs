@@ -4,8 +4,13 @@
 //! It includes functionality for loading quotes from a data source,
 //! and fetching specific or random quotes.
 
-use crate::models::quote::Quote;
+use crate::models::quote::{Quote, QuoteStatus};
+use crate::services::quote_store::Corpus;
+use crate::utils::slugify;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::io;
 use std::path::PathBuf; // Removed unused Path
@@ -16,6 +21,12 @@ pub enum QuoteServiceError {
     FileNotFound(String),
     FileReadError(io::Error),
     ParseError(serde_json::Error),
+    /// More of the quotes are near duplicates than the configured maximum allows.
+    TooManyDuplicates {
+        rate: f64,
+        max_rate: f64,
+        clusters: usize,
+    },
 }
 
 impl std::fmt::Display for QuoteServiceError {
@@ -28,6 +39,17 @@ impl std::fmt::Display for QuoteServiceError {
                 write!(f, "Error reading quote data file: {}", err)
             }
             QuoteServiceError::ParseError(err) => write!(f, "Error parsing quote data: {}", err),
+            QuoteServiceError::TooManyDuplicates {
+                rate,
+                max_rate,
+                clusters,
+            } => write!(
+                f,
+                "Refusing to load quote data: {:.1}% of quotes are near duplicates ({} clusters), above the maximum of {:.1}%",
+                rate * 100.0,
+                clusters,
+                max_rate * 100.0
+            ),
         }
     }
 }
@@ -69,30 +91,165 @@ pub fn load_quotes_from_file(file_path: &PathBuf) -> Result<Vec<Quote>, QuoteSer
 
     let file_content = fs::read_to_string(file_path).map_err(QuoteServiceError::FileReadError)?;
 
-    let quotes: Vec<Quote> =
-        serde_json::from_str(&file_content).map_err(QuoteServiceError::ParseError)?;
+    parse_quotes(&file_content)
+}
+
+/// Parses the contents of a quotes JSON file and assigns identifiers.
+///
+/// # Errors
+///
+/// Returns `QuoteServiceError::ParseError` if the content is not a valid
+/// quotes array.
+pub fn parse_quotes(content: &str) -> Result<Vec<Quote>, QuoteServiceError> {
+    let mut quotes: Vec<Quote> =
+        serde_json::from_str(content).map_err(QuoteServiceError::ParseError)?;
+    assign_identifiers(&mut quotes);
 
     Ok(quotes)
 }
 
+/// Maximum length of a derived slug, in bytes.
+const MAX_SLUG_LEN: usize = 80;
+
+/// Number of words of quote text used when deriving a slug.
+const SLUG_WORDS: usize = 6;
+
+/// Fills in the `uid` and `slug` of every quote that does not already have one.
+///
+/// Derived uids are `q-` followed by a truncated SHA-256 of the author and text,
+/// so the same quote gets the same uid in every file and across restarts.
+/// Derived slugs combine the author with the first few words of the text.
+/// Collisions with identifiers already in use are resolved by appending the
+/// numeric id. Identifiers set in the data file are kept as-is.
+pub fn assign_identifiers(quotes: &mut [Quote]) {
+    let mut uids: HashSet<String> = quotes
+        .iter()
+        .filter(|q| !q.uid.is_empty())
+        .map(|q| q.uid.clone())
+        .collect();
+    let mut slugs: HashSet<String> = quotes
+        .iter()
+        .filter(|q| !q.slug.is_empty())
+        .map(|q| q.slug.clone())
+        .collect();
+
+    for quote in quotes.iter_mut() {
+        if quote.uid.is_empty() {
+            let uid = content_uid(quote);
+            quote.uid = if uids.contains(&uid) {
+                format!("{}-{}", uid, quote.id)
+            } else {
+                uid
+            };
+            uids.insert(quote.uid.clone());
+        }
+        if quote.slug.is_empty() {
+            let slug = derive_slug(quote);
+            quote.slug = if slug.is_empty() || slugs.contains(&slug) {
+                format!("{}-{}", slug, quote.id)
+                    .trim_start_matches('-')
+                    .to_string()
+            } else {
+                slug
+            };
+            slugs.insert(quote.slug.clone());
+        }
+    }
+}
+
+/// Derives the content-hash uid for a quote, ignoring any uid already set.
+pub fn content_uid(quote: &Quote) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(quote.author.as_bytes());
+    hasher.update([0x1f]);
+    hasher.update(quote.text.as_bytes());
+    let digest = hasher.finalize();
+    let hex: String = digest[..10].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("q-{}", hex)
+}
+
+fn derive_slug(quote: &Quote) -> String {
+    let lead: Vec<&str> = quote.text.split_whitespace().take(SLUG_WORDS).collect();
+    let slug = slugify(
+        &format!("{} {}", quote.author, lead.join(" ")),
+        MAX_SLUG_LEN,
+    );
+    // Keep slugs distinguishable from numeric ids.
+    if slug.parse::<u32>().is_ok() {
+        format!("quote-{}", slug)
+    } else {
+        slug
+    }
+}
+
 /// Returns a random quote from a slice of [`Quote`]s.
 ///
+/// Only [`QuoteStatus::Active`](crate::models::QuoteStatus::Active) quotes that
+/// are within their publishing window right now are eligible; archived and
+/// deleted quotes are never picked.
+///
 /// # Arguments
 ///
 /// * `quotes` - A slice of [`Quote`] structs from which to select a random quote.
 ///
 /// # Returns
 ///
-/// An `Option` containing a reference to a random [`Quote`] if the slice is not empty,
-/// otherwise `None`.
+/// An `Option` containing a reference to a random eligible [`Quote`], or `None`
+/// if the slice has none.
 pub fn get_random_quote(quotes: &[Quote]) -> Option<&Quote> {
-    if quotes.is_empty() {
-        None
-    } else {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        quotes.choose(&mut rng)
+    get_random_quote_at(quotes, Utc::now())
+}
+
+/// Like [`get_random_quote`], but evaluates publishing windows at `at`
+/// instead of the current time.
+pub fn get_random_quote_at(quotes: &[Quote], at: DateTime<Utc>) -> Option<&Quote> {
+    get_random_quote_where(quotes, at, |_| true)
+}
+
+/// Like [`get_random_quote_at`], but only picks among the eligible quotes
+/// for which `predicate` holds, e.g. those matching a filter.
+pub fn get_random_quote_where(
+    quotes: &[Quote],
+    at: DateTime<Utc>,
+    predicate: impl Fn(&Quote) -> bool,
+) -> Option<&Quote> {
+    use rand::seq::IteratorRandom;
+    let mut rng = rand::thread_rng();
+    listable_quotes_at(quotes, at)
+        .filter(|q| predicate(q))
+        .choose(&mut rng)
+}
+
+/// Picks the quote of the day for `date` (a UTC calendar day).
+///
+/// The pick is deterministic: it depends only on the date and on the quotes
+/// eligible at the start of that day (active and within their publishing
+/// window), taken in id order. The same corpus therefore yields the same quote
+/// for a given day on every request and every server.
+pub fn get_quote_of_the_day(quotes: &[Quote], date: NaiveDate) -> Option<&Quote> {
+    get_quote_of_the_day_among(quotes, date)
+}
+
+/// Like [`get_quote_of_the_day`], but picks among an arbitrary set of
+/// candidate quotes, e.g. ones already filtered by the caller.
+pub fn get_quote_of_the_day_among<'a>(
+    quotes: impl IntoIterator<Item = &'a Quote>,
+    date: NaiveDate,
+) -> Option<&'a Quote> {
+    let at = date.and_hms_opt(0, 0, 0)?.and_utc();
+    let mut eligible: Vec<&Quote> = quotes
+        .into_iter()
+        .filter(|q| q.status.is_active() && q.is_published_at(at))
+        .collect();
+    if eligible.is_empty() {
+        return None;
     }
+    eligible.sort_by_key(|q| q.id);
+    let digest = Sha256::digest(format!("rustquote:daily:{}", date).as_bytes());
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[..8]);
+    let index = u64::from_be_bytes(seed) % eligible.len() as u64;
+    Some(eligible[index as usize])
 }
 
 /// Finds and returns a reference to a quote by its ID from a slice of [`Quote`]s.
@@ -109,9 +266,61 @@ pub fn get_quote_by_id(quotes: &[Quote], id: u32) -> Option<&Quote> {
     quotes.iter().find(|q| q.id == id)
 }
 
+/// Why a quote addressed by key cannot be served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteLookupError {
+    /// The quote was removed from the data file by a reload.
+    Removed,
+    /// The corpus has no quotes at all.
+    NoQuotes,
+    /// No quote matches the key, or it is outside its publishing window.
+    NotFound,
+    /// The quote is marked as deleted.
+    Deleted,
+}
+
+/// Finds the quote addressed by `key` (numeric id, uid or slug) for serving
+/// by id, applying the lifecycle and publishing rules at instant `at`.
+///
+/// Archived quotes are still served. Unpublished and expired quotes are
+/// reported as [`QuoteLookupError::NotFound`], so they are indistinguishable
+/// from missing ones.
+pub fn find_servable_quote<'a>(
+    corpus: &'a Corpus,
+    key: &str,
+    at: DateTime<Utc>,
+) -> Result<&'a Quote, QuoteLookupError> {
+    let Some(quote) = corpus.find(key) else {
+        return Err(if corpus.find_removed(key).is_some() {
+            QuoteLookupError::Removed
+        } else if corpus.quotes.is_empty() {
+            QuoteLookupError::NoQuotes
+        } else {
+            QuoteLookupError::NotFound
+        });
+    };
+    if quote.status == QuoteStatus::Deleted {
+        return Err(QuoteLookupError::Deleted);
+    }
+    if !quote.is_published_at(at) {
+        return Err(QuoteLookupError::NotFound);
+    }
+    Ok(quote)
+}
+
+/// Returns the quotes that can be listed or picked at instant `at`: active
+/// and within their publishing window, in file order.
+pub fn listable_quotes_at(quotes: &[Quote], at: DateTime<Utc>) -> impl Iterator<Item = &Quote> {
+    quotes
+        .iter()
+        .filter(move |q| q.status.is_active() && q.is_published_at(at))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -182,6 +391,7 @@ mod tests {
             text: "Only quote".to_string(),
             author: "Author".to_string(),
             source: None,
+            ..Default::default()
         }];
         let random_quote = get_random_quote(&quotes);
         assert!(random_quote.is_some());
@@ -196,18 +406,21 @@ mod tests {
                 text: "Quote 1".to_string(),
                 author: "Author 1".to_string(),
                 source: None,
+                ..Default::default()
             },
             Quote {
                 id: 2,
                 text: "Quote 2".to_string(),
                 author: "Author 2".to_string(),
                 source: None,
+                ..Default::default()
             },
             Quote {
                 id: 3,
                 text: "Quote 3".to_string(),
                 author: "Author 3".to_string(),
                 source: None,
+                ..Default::default()
             },
         ];
         // Run multiple times to increase chance of catching non-random behavior (though not a perfect test for randomness)
@@ -231,12 +444,14 @@ mod tests {
                 text: "Quote 1".to_string(),
                 author: "Author 1".to_string(),
                 source: None,
+                ..Default::default()
             },
             Quote {
                 id: 2,
                 text: "Quote 2".to_string(),
                 author: "Author 2".to_string(),
                 source: Some("Source 2".to_string()),
+                ..Default::default()
             },
         ];
         let quote = get_quote_by_id(&quotes, 2);
@@ -252,6 +467,7 @@ mod tests {
             text: "Quote 1".to_string(),
             author: "Author 1".to_string(),
             source: None,
+            ..Default::default()
         }];
         let quote = get_quote_by_id(&quotes, 99);
         assert!(quote.is_none());
@@ -266,8 +482,20 @@ mod tests {
     #[test]
     fn test_get_quote_by_id_zero() {
         let quotes = vec![
-            Quote { id: 0, text: "Quote 0".to_string(), author: "Author 0".to_string(), source: None },
-            Quote { id: 1, text: "Quote 1".to_string(), author: "Author 1".to_string(), source: None },
+            Quote {
+                id: 0,
+                text: "Quote 0".to_string(),
+                author: "Author 0".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 1,
+                text: "Quote 1".to_string(),
+                author: "Author 1".to_string(),
+                source: None,
+                ..Default::default()
+            },
         ];
         let quote = get_quote_by_id(&quotes, 0);
         assert!(quote.is_some());
@@ -277,8 +505,20 @@ mod tests {
     #[test]
     fn test_get_quote_by_id_large_id_not_found() {
         let quotes = vec![
-            Quote { id: 1, text: "Quote 1".to_string(), author: "Author 1".to_string(), source: None },
-            Quote { id: 2, text: "Quote 2".to_string(), author: "Author 2".to_string(), source: None },
+            Quote {
+                id: 1,
+                text: "Quote 1".to_string(),
+                author: "Author 1".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 2,
+                text: "Quote 2".to_string(),
+                author: "Author 2".to_string(),
+                source: None,
+                ..Default::default()
+            },
         ];
         let quote = get_quote_by_id(&quotes, u32::MAX);
         assert!(quote.is_none());
@@ -287,9 +527,27 @@ mod tests {
     #[test]
     fn test_get_quote_by_id_middle_of_list() {
         let quotes = vec![
-            Quote { id: 1, text: "Quote 1".to_string(), author: "Author 1".to_string(), source: None },
-            Quote { id: 2, text: "Quote 2".to_string(), author: "Author 2".to_string(), source: None },
-            Quote { id: 3, text: "Quote 3".to_string(), author: "Author 3".to_string(), source: None },
+            Quote {
+                id: 1,
+                text: "Quote 1".to_string(),
+                author: "Author 1".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 2,
+                text: "Quote 2".to_string(),
+                author: "Author 2".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 3,
+                text: "Quote 3".to_string(),
+                author: "Author 3".to_string(),
+                source: None,
+                ..Default::default()
+            },
         ];
         let quote = get_quote_by_id(&quotes, 2);
         assert!(quote.is_some());
@@ -299,8 +557,20 @@ mod tests {
     #[test]
     fn test_get_quote_by_id_first_in_list() {
         let quotes = vec![
-            Quote { id: 1, text: "Quote 1".to_string(), author: "Author 1".to_string(), source: None },
-            Quote { id: 2, text: "Quote 2".to_string(), author: "Author 2".to_string(), source: None },
+            Quote {
+                id: 1,
+                text: "Quote 1".to_string(),
+                author: "Author 1".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 2,
+                text: "Quote 2".to_string(),
+                author: "Author 2".to_string(),
+                source: None,
+                ..Default::default()
+            },
         ];
         let quote = get_quote_by_id(&quotes, 1);
         assert!(quote.is_some());
@@ -310,11 +580,165 @@ mod tests {
     #[test]
     fn test_get_quote_by_id_last_in_list() {
         let quotes = vec![
-            Quote { id: 1, text: "Quote 1".to_string(), author: "Author 1".to_string(), source: None },
-            Quote { id: 2, text: "Quote 2".to_string(), author: "Author 2".to_string(), source: None },
+            Quote {
+                id: 1,
+                text: "Quote 1".to_string(),
+                author: "Author 1".to_string(),
+                source: None,
+                ..Default::default()
+            },
+            Quote {
+                id: 2,
+                text: "Quote 2".to_string(),
+                author: "Author 2".to_string(),
+                source: None,
+                ..Default::default()
+            },
         ];
         let quote = get_quote_by_id(&quotes, 2);
         assert!(quote.is_some());
         assert_eq!(quote.unwrap().id, 2);
     }
+
+    #[test]
+    fn test_load_quotes_assigns_identifiers() {
+        let sample_json = r#"[
+            { "id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs" },
+            { "id": 2, "quote": "Pinned.", "author": "Editor", "uid": "custom-uid", "slug": "pinned" }
+        ]"#;
+        let temp_file = create_temp_json_file(sample_json);
+        let quotes = load_quotes_from_file(&temp_file.path().to_path_buf()).unwrap();
+        assert!(quotes[0].uid.starts_with("q-"));
+        assert_eq!(quotes[0].slug, "steve-jobs-stay-hungry-stay-foolish");
+        assert_eq!(quotes[1].uid, "custom-uid");
+        assert_eq!(quotes[1].slug, "pinned");
+    }
+
+    #[test]
+    fn test_content_uid_is_stable_across_ids() {
+        let a = quote(1, "Same text", "Same", None);
+        let b = quote(100, "Same text", "Same", None);
+        let c = quote(1, "Other text", "Same", None);
+        assert_eq!(content_uid(&a), content_uid(&b));
+        assert_ne!(content_uid(&a), content_uid(&c));
+    }
+
+    #[test]
+    fn test_assign_identifiers_resolves_collisions() {
+        let mut quotes = vec![
+            quote(1, "Same text", "Same", None),
+            quote(2, "Same text", "Same", None),
+        ];
+        assign_identifiers(&mut quotes);
+        assert_ne!(quotes[0].uid, quotes[1].uid);
+        assert_eq!(quotes[1].uid, format!("{}-2", quotes[0].uid));
+        assert_eq!(quotes[0].slug, "same-same-text");
+        assert_eq!(quotes[1].slug, "same-same-text-2");
+    }
+
+    #[test]
+    fn test_find_servable_quote_prefers_live_quote_over_removed_one() {
+        let loaded = |id: u32| {
+            let mut quotes = vec![quote(id, "Again", "Author", None)];
+            assign_identifiers(&mut quotes);
+            quotes
+        };
+        let now = Utc::now();
+        let v1 = Corpus::from_load(None, loaded(1), now);
+        let uid = v1.quotes[0].uid.clone();
+        let v2 = Corpus::from_load(Some(&v1), Vec::new(), now);
+        assert_eq!(
+            find_servable_quote(&v2, &uid, now),
+            Err(QuoteLookupError::Removed)
+        );
+
+        let v3 = Corpus::from_load(Some(&v2), loaded(7), now);
+        assert_eq!(find_servable_quote(&v3, &uid, now).unwrap().id, 7);
+        assert_eq!(find_servable_quote(&v3, "author-again", now).unwrap().id, 7);
+        assert_eq!(
+            find_servable_quote(&v3, "1", now),
+            Err(QuoteLookupError::Removed)
+        );
+    }
+
+    #[test]
+    fn test_get_random_quote_skips_archived_and_deleted() {
+        let quotes = vec![
+            Quote {
+                status: QuoteStatus::Archived,
+                ..quote(1, "Archived", "A", None)
+            },
+            quote(2, "Active", "A", None),
+            Quote {
+                status: QuoteStatus::Deleted,
+                ..quote(3, "Deleted", "A", None)
+            },
+        ];
+        for _ in 0..50 {
+            assert_eq!(get_random_quote(&quotes).unwrap().id, 2);
+        }
+        assert!(get_random_quote(&quotes[..1]).is_none());
+    }
+
+    #[test]
+    fn test_get_random_quote_at_respects_publishing_window() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let quotes = vec![Quote {
+            publish_at: Some(at("2030-01-01T00:00:00Z")),
+            ..quote(1, "Future", "A", None)
+        }];
+        assert!(get_random_quote_at(&quotes, at("2029-12-31T00:00:00Z")).is_none());
+        assert_eq!(
+            get_random_quote_at(&quotes, at("2030-01-02T00:00:00Z"))
+                .unwrap()
+                .id,
+            1
+        );
+    }
+
+    #[test]
+    fn test_get_quote_of_the_day_is_deterministic() {
+        let day = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let quotes: Vec<Quote> = (1..=20)
+            .map(|id| quote(id, &format!("Quote {}", id), "A", None))
+            .collect();
+        let first = get_quote_of_the_day(&quotes, day("2025-06-01")).unwrap();
+        // File order does not matter, only ids.
+        let mut reversed = quotes.clone();
+        reversed.reverse();
+        assert_eq!(
+            get_quote_of_the_day(&reversed, day("2025-06-01"))
+                .unwrap()
+                .id,
+            first.id
+        );
+        let picks: HashSet<u32> = (1..=30)
+            .map(|d| {
+                let date = NaiveDate::from_ymd_opt(2025, 6, d).unwrap();
+                get_quote_of_the_day(&quotes, date).unwrap().id
+            })
+            .collect();
+        assert!(picks.len() > 1, "Expected different quotes across days");
+        assert!(get_quote_of_the_day(&[], day("2025-06-01")).is_none());
+    }
+
+    #[test]
+    fn test_get_quote_of_the_day_skips_ineligible_quotes() {
+        let quotes = vec![
+            Quote {
+                status: QuoteStatus::Archived,
+                ..quote(1, "Archived", "A", None)
+            },
+            Quote {
+                publish_at: Some("2025-06-02T00:00:00Z".parse().unwrap()),
+                ..quote(2, "Scheduled", "A", None)
+            },
+        ];
+        let day = |s: &str| s.parse::<NaiveDate>().unwrap();
+        assert!(get_quote_of_the_day(&quotes, day("2025-06-01")).is_none());
+        assert_eq!(
+            get_quote_of_the_day(&quotes, day("2025-06-02")).unwrap().id,
+            2
+        );
+    }
 }
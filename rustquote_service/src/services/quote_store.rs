@@ -0,0 +1,1065 @@
+//! # Quote Store
+//!
+//! This module keeps the most recently loaded quote corpus in memory and
+//! reloads it when the quotes data file changes on disk. Each reload that
+//! changes the corpus bumps its version and appends entries to the revision
+//! log of every quote that was created, updated or removed.
+//!
+//! The version, the stamped quotes and the revision log are saved to a
+//! history file next to the data file (see [`history_path_for`]) after every
+//! load that changes them, and a restarted service continues from there: its
+//! first load is compared against the saved quotes instead of starting over.
+
+use crate::models::{Quote, QuoteRevision, RevisionKind};
+use crate::services::corpus_stats::CorpusStats;
+use crate::services::facet_index::FacetIndex;
+use crate::services::near_duplicates::DuplicateReport;
+use crate::services::quote_service::{self, QuoteServiceError};
+use crate::services::similarity::SimilarityIndex;
+use crate::services::suggest_index::SuggestIndex;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+/// Maximum number of revisions kept per quote; older entries are dropped first.
+pub const MAX_REVISIONS_PER_QUOTE: usize = 100;
+
+/// Appended to the stem of the quotes data file to name its history file.
+pub const HISTORY_FILE_SUFFIX: &str = ".history.json";
+
+/// Returns the path of the history file kept for a quotes data file:
+/// `quotes.json` keeps its history in `quotes.history.json`.
+pub fn history_path_for(quotes_path: &Path) -> PathBuf {
+    let stem = quotes_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    quotes_path.with_file_name(format!("{stem}{HISTORY_FILE_SUFFIX}"))
+}
+
+/// An immutable snapshot of the quotes data file.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    /// Incremented every time a reload changes the corpus content.
+    pub version: u64,
+    /// When this version of the corpus was loaded.
+    pub loaded_at: DateTime<Utc>,
+    /// The quotes in file order, with timestamps filled in.
+    pub quotes: Vec<Quote>,
+    /// Statistics about `quotes`, computed when the corpus was built.
+    pub stats: CorpusStats,
+    /// Type-ahead suggestions for `quotes`, built with the corpus.
+    pub suggest: SuggestIndex,
+    /// TF-IDF vectors for `quotes`, built with the corpus.
+    pub similarity: SimilarityIndex,
+    /// Near-duplicate clusters among `quotes`, found when the corpus was built.
+    pub duplicates: DuplicateReport,
+    /// Facet data for the active `quotes`, built with the corpus.
+    pub facets: FacetIndex,
+    keys: KeyIndex,
+    /// The last version of every quote whose latest revision removed it, by id.
+    removed: Vec<Quote>,
+    removed_keys: KeyIndex,
+    revisions: HashMap<u32, Vec<QuoteRevision>>,
+}
+
+impl Corpus {
+    /// Builds the next corpus version from freshly loaded `quotes`.
+    ///
+    /// Timestamps and revision history are carried over from `previous`.
+    /// Without a previous corpus, every quote is stamped with `now` and no
+    /// revisions are recorded, since there is nothing to compare against.
+    pub fn from_load(previous: Option<&Corpus>, quotes: Vec<Quote>, now: DateTime<Utc>) -> Self {
+        let Some(previous) = previous else {
+            let quotes = quotes
+                .into_iter()
+                .map(|q| stamp(q, None, now, false))
+                .collect();
+            return Corpus::build(1, now, quotes, HashMap::new());
+        };
+        Corpus::next(
+            previous.version,
+            &previous.quotes,
+            previous.revisions.clone(),
+            quotes,
+            now,
+        )
+    }
+
+    /// Builds the first corpus of a restarted service from freshly loaded
+    /// `quotes`, continuing the saved `history`. If the quotes did not change
+    /// while the service was stopped, the saved version is restored as is.
+    fn from_history(history: History, quotes: Vec<Quote>, now: DateTime<Utc>) -> Self {
+        if same_content(&history.quotes, &quotes) {
+            return Corpus::build(history.version, now, history.quotes, history.revisions);
+        }
+        Corpus::next(
+            history.version,
+            &history.quotes,
+            history.revisions,
+            quotes,
+            now,
+        )
+    }
+
+    /// Builds the version after `version`, whose quotes were `previous`,
+    /// appending the changes found in `quotes` to `revisions`.
+    ///
+    /// Where quotes share an id, the first in file order is the one served by
+    /// id (see [`KeyIndex`]), so it is the one diffed and logged; later copies
+    /// are stamped against it but add no revisions.
+    fn next(
+        version: u64,
+        previous: &[Quote],
+        mut revisions: HashMap<u32, Vec<QuoteRevision>>,
+        quotes: Vec<Quote>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let version = version + 1;
+        let mut previous_by_id: HashMap<u32, &Quote> = HashMap::new();
+        for quote in previous {
+            previous_by_id.entry(quote.id).or_insert(quote);
+        }
+
+        let mut stamped = Vec::with_capacity(quotes.len());
+        let mut logged_ids: HashSet<u32> = HashSet::new();
+        for quote in quotes {
+            let before = previous_by_id.get(&quote.id).copied();
+            let changed = !before.is_some_and(|b| b.content_eq(&quote));
+            let quote = stamp(quote, before, now, changed);
+            if logged_ids.insert(quote.id) && changed {
+                let kind = if before.is_some() {
+                    RevisionKind::Updated
+                } else {
+                    RevisionKind::Created
+                };
+                push_revision(
+                    &mut revisions,
+                    quote.id,
+                    version,
+                    now,
+                    kind,
+                    before.cloned(),
+                    Some(quote.clone()),
+                );
+            }
+            stamped.push(quote);
+        }
+
+        let loaded_ids: HashSet<u32> = stamped.iter().map(|q| q.id).collect();
+        for removed in previous.iter().filter(|p| !loaded_ids.contains(&p.id)) {
+            push_revision(
+                &mut revisions,
+                removed.id,
+                version,
+                now,
+                RevisionKind::Removed,
+                Some(removed.clone()),
+                None,
+            );
+        }
+
+        Corpus::build(version, now, stamped, revisions)
+    }
+
+    /// Assembles a corpus and computes everything derived from its quotes.
+    fn build(
+        version: u64,
+        loaded_at: DateTime<Utc>,
+        quotes: Vec<Quote>,
+        revisions: HashMap<u32, Vec<QuoteRevision>>,
+    ) -> Self {
+        let mut removed: Vec<Quote> = revisions
+            .values()
+            .filter_map(|log| log.last())
+            .filter(|r| r.kind == RevisionKind::Removed)
+            .filter_map(|r| r.before.clone())
+            .collect();
+        removed.sort_by_key(|q| q.id);
+        Corpus {
+            removed_keys: KeyIndex::build(&removed),
+            removed,
+            stats: CorpusStats::compute(&quotes, version, loaded_at),
+            suggest: SuggestIndex::build(&quotes),
+            similarity: SimilarityIndex::build(&quotes),
+            duplicates: DuplicateReport::detect(&quotes, version),
+            facets: FacetIndex::build(&quotes),
+            keys: KeyIndex::build(&quotes),
+            version,
+            loaded_at,
+            quotes,
+            revisions,
+        }
+    }
+
+    /// Finds a quote by numeric id, uid or slug.
+    ///
+    /// Keys that parse as a `u32` are treated as numeric ids so existing
+    /// clients keep working; anything else is matched against uids and then
+    /// slugs. Where quotes share a key, the first in file order wins.
+    pub fn find(&self, key: &str) -> Option<&Quote> {
+        self.position(key).map(|index| &self.quotes[index])
+    }
+
+    /// The position in `quotes` of the quote [`Corpus::find`] returns.
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.keys.position(key)
+    }
+
+    /// Returns the revision log for the quote with the given ID, oldest first.
+    pub fn revisions_for(&self, id: u32) -> Option<&[QuoteRevision]> {
+        self.revisions.get(&id).map(Vec::as_slice)
+    }
+
+    /// Finds a quote that was removed from the data file by a reload, using the
+    /// same numeric id, uid or slug keys as [`Corpus::find`].
+    ///
+    /// Returns the last version of the quote before it was removed.
+    pub fn find_removed(&self, key: &str) -> Option<&Quote> {
+        self.removed_keys
+            .position(key)
+            .map(|index| &self.removed[index])
+    }
+
+    /// Returns the `Created` revisions recorded by reloads, newest first.
+    ///
+    /// Quotes present when the history was started have no such revision, so
+    /// this lists only the quotes that appeared in the data file since.
+    pub fn created_revisions(&self) -> Vec<&QuoteRevision> {
+        let mut created: Vec<&QuoteRevision> = self
+            .revisions
+            .values()
+            .flatten()
+            .filter(|r| r.kind == RevisionKind::Created)
+            .collect();
+        created.sort_by(|a, b| {
+            b.corpus_version.cmp(&a.corpus_version).then_with(|| {
+                b.after
+                    .as_ref()
+                    .map(|q| q.id)
+                    .cmp(&a.after.as_ref().map(|q| q.id))
+            })
+        });
+        created
+    }
+
+    /// Returns the revisions recorded by reloads after corpus `version`, in
+    /// the order they were observed: by corpus version, then by quote id.
+    pub fn revisions_since(&self, version: u64) -> Vec<&QuoteRevision> {
+        let mut revisions: Vec<&QuoteRevision> = self
+            .revisions
+            .values()
+            .flatten()
+            .filter(|r| r.corpus_version > version)
+            .collect();
+        revisions.sort_by_key(|r| (r.corpus_version, revision_quote_id(r)));
+        revisions
+    }
+
+    /// Returns `true` if the corpus has the same quotes, ignoring timestamps.
+    fn same_content(&self, quotes: &[Quote]) -> bool {
+        same_content(&self.quotes, quotes)
+    }
+
+    /// The part of the corpus saved to the history file.
+    fn history(&self) -> History {
+        History {
+            version: self.version,
+            quotes: self.quotes.clone(),
+            revisions: self.revisions.clone(),
+        }
+    }
+}
+
+/// Positions of the quotes in a corpus by each key they can be looked up by,
+/// with the rules described on [`Corpus::find`].
+#[derive(Debug, Clone, Default)]
+struct KeyIndex {
+    ids: HashMap<u32, usize>,
+    uids: HashMap<String, usize>,
+    slugs: HashMap<String, usize>,
+}
+
+impl KeyIndex {
+    fn build(quotes: &[Quote]) -> Self {
+        let mut keys = KeyIndex::default();
+        for (index, quote) in quotes.iter().enumerate() {
+            keys.ids.entry(quote.id).or_insert(index);
+            if !quote.uid.is_empty() {
+                keys.uids.entry(quote.uid.clone()).or_insert(index);
+            }
+            if !quote.slug.is_empty() {
+                keys.slugs.entry(quote.slug.clone()).or_insert(index);
+            }
+        }
+        keys
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        match key.parse::<u32>() {
+            Ok(id) => self.ids.get(&id),
+            Err(_) => self.uids.get(key).or_else(|| self.slugs.get(key)),
+        }
+        .copied()
+    }
+}
+
+/// What the history file keeps of the last loaded corpus.
+#[derive(Debug, Serialize, Deserialize)]
+struct History {
+    version: u64,
+    quotes: Vec<Quote>,
+    revisions: HashMap<u32, Vec<QuoteRevision>>,
+}
+
+/// Returns `true` if both lists have the same quotes in the same order,
+/// ignoring timestamps.
+fn same_content(current: &[Quote], loaded: &[Quote]) -> bool {
+    current.len() == loaded.len()
+        && current
+            .iter()
+            .zip(loaded)
+            .all(|(current, loaded)| current.content_eq(loaded))
+}
+
+/// The id of the quote a revision belongs to.
+fn revision_quote_id(revision: &QuoteRevision) -> Option<u32> {
+    revision
+        .after
+        .as_ref()
+        .or(revision.before.as_ref())
+        .map(|q| q.id)
+}
+
+/// Fills in `created_at`/`updated_at` unless editors already set them.
+fn stamp(mut quote: Quote, before: Option<&Quote>, now: DateTime<Utc>, changed: bool) -> Quote {
+    if quote.created_at.is_none() {
+        quote.created_at = before.and_then(|b| b.created_at).or(Some(now));
+    }
+    if quote.updated_at.is_none() {
+        quote.updated_at = match before {
+            Some(_) if changed => Some(now),
+            Some(b) => b.updated_at.or(quote.created_at),
+            None => quote.created_at,
+        };
+    }
+    quote
+}
+
+fn push_revision(
+    revisions: &mut HashMap<u32, Vec<QuoteRevision>>,
+    id: u32,
+    corpus_version: u64,
+    changed_at: DateTime<Utc>,
+    kind: RevisionKind,
+    before: Option<Quote>,
+    after: Option<Quote>,
+) {
+    let log = revisions.entry(id).or_default();
+    let revision = log.last().map_or(1, |r| r.revision + 1);
+    log.push(QuoteRevision {
+        revision,
+        corpus_version,
+        changed_at,
+        kind,
+        before,
+        after,
+    });
+    if log.len() > MAX_REVISIONS_PER_QUOTE {
+        let excess = log.len() - MAX_REVISIONS_PER_QUOTE;
+        log.drain(..excess);
+    }
+}
+
+/// The length and modification time of a data file, read from its metadata.
+///
+/// Checking it is cheap enough for every request; the file is only read and
+/// fingerprinted when it differs from the one seen at the last load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub(crate) fn of(metadata: &fs::Metadata) -> Self {
+        FileStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+
+    /// Returns `true` if the file is known to be unchanged since `earlier`.
+    /// Without a modification time, that cannot be known.
+    pub(crate) fn unchanged_since(&self, earlier: &FileStamp) -> bool {
+        self.modified.is_some() && self == earlier
+    }
+}
+
+/// Identifies a particular state of the data file by its content, so that a
+/// file touched or rewritten without changes does not produce a new corpus
+/// version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileFingerprint {
+    len: usize,
+    digest: [u8; 32],
+}
+
+impl FileFingerprint {
+    pub(crate) fn of(content: &str) -> Self {
+        FileFingerprint {
+            len: content.len(),
+            digest: Sha256::digest(content.as_bytes()).into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Loaded {
+    stamp: FileStamp,
+    fingerprint: FileFingerprint,
+    corpus: Arc<Corpus>,
+}
+
+/// Caches the quote corpus for a data file and reloads it when the file changes.
+///
+/// Each call only checks the file's metadata (see [`FileStamp`]); the file is
+/// read and hashed when that changed, and rebuilt when its content did.
+///
+/// Reading the file and rebuilding the corpus happen on the blocking thread
+/// pool. The current corpus sits behind a lock that is only held to clone or
+/// swap its `Arc`, so requests keep being served from the previous version
+/// while a reload is built; concurrent reloads wait on each other instead of
+/// building the same version twice.
+#[derive(Debug)]
+pub struct QuoteStore {
+    file_path: PathBuf,
+    history_path: PathBuf,
+    max_duplicate_rate: Option<f64>,
+    loaded: RwLock<Option<Loaded>>,
+    reload: Mutex<()>,
+}
+
+impl QuoteStore {
+    /// Creates a store for the given quotes data file. Nothing is read until
+    /// [`QuoteStore::current`] is first called.
+    pub fn new(file_path: PathBuf) -> Self {
+        QuoteStore {
+            history_path: history_path_for(&file_path),
+            file_path,
+            max_duplicate_rate: None,
+            loaded: RwLock::new(None),
+            reload: Mutex::new(()),
+        }
+    }
+
+    /// Refuses to load a data file in which more than `max_duplicate_rate`
+    /// of the quotes are near duplicates of another quote. `None` loads any
+    /// file.
+    ///
+    /// A refused first load is an error. A refused reload is logged and the
+    /// previous corpus stays in service until the file changes again.
+    pub fn with_max_duplicate_rate(mut self, max_duplicate_rate: Option<f64>) -> Self {
+        self.max_duplicate_rate = max_duplicate_rate;
+        self
+    }
+
+    /// The path of the quotes data file backing this store.
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Returns the current corpus, reloading the data file if it changed since
+    /// the last load.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuoteServiceError`] if the file cannot be found, read, or
+    /// parsed, or if its near-duplicate rate exceeds the configured maximum.
+    pub async fn current(self: &Arc<Self>) -> Result<Arc<Corpus>, QuoteServiceError> {
+        let store = Arc::clone(self);
+        tokio::task::spawn_blocking(move || store.load_current())
+            .await
+            .map_err(|err| QuoteServiceError::FileReadError(io::Error::other(err)))?
+    }
+
+    /// The blocking part of [`QuoteStore::current`].
+    fn load_current(&self) -> Result<Arc<Corpus>, QuoteServiceError> {
+        let stamp = self.stat_file()?;
+        if let Some(corpus) = self.cached_by_stamp(&stamp) {
+            return Ok(corpus);
+        }
+
+        let _reload = self.reload.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(corpus) = self.cached_by_stamp(&stamp) {
+            return Ok(corpus);
+        }
+
+        let content = self.read_file()?;
+        let fingerprint = FileFingerprint::of(&content);
+        if let Some(corpus) = self.cached(&fingerprint) {
+            // Touched without changes: remember the new stamp so the file is
+            // not read again until it changes.
+            self.install(stamp, fingerprint, Arc::clone(&corpus));
+            return Ok(corpus);
+        }
+
+        let quotes = quote_service::parse_quotes(&content)?;
+        let previous = self
+            .loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|l| Arc::clone(&l.corpus));
+        let corpus = match previous {
+            Some(previous) if previous.same_content(&quotes) => previous,
+            previous => {
+                let now = Utc::now();
+                let corpus = match (previous.as_deref(), self.read_history()) {
+                    (Some(previous), _) => Corpus::from_load(Some(previous), quotes, now),
+                    (None, Some(history)) => Corpus::from_history(history, quotes, now),
+                    (None, None) => Corpus::from_load(None, quotes, now),
+                };
+                let report = &corpus.duplicates;
+                let refusal = self
+                    .max_duplicate_rate
+                    .filter(|&max_rate| report.duplicate_rate > max_rate)
+                    .map(|max_rate| QuoteServiceError::TooManyDuplicates {
+                        rate: report.duplicate_rate,
+                        max_rate,
+                        clusters: report.clusters.len(),
+                    });
+                if let Some(refusal) = refusal {
+                    // Only a first load fails; a bad edit must not take down a
+                    // running service. The refused file's fingerprint is kept
+                    // with the previous corpus, so it is not rebuilt on every
+                    // request until the file changes again.
+                    let Some(previous) = previous else {
+                        return Err(refusal);
+                    };
+                    tracing::error!(
+                        "{} in {}; still serving quote corpus version {}",
+                        refusal,
+                        self.file_path.display(),
+                        previous.version
+                    );
+                    self.install(stamp, fingerprint, Arc::clone(&previous));
+                    return Ok(previous);
+                }
+                if report.duplicate_quotes > 0 {
+                    tracing::warn!(
+                        "Quote corpus version {} has {} near-duplicate quotes in {} clusters",
+                        corpus.version,
+                        report.duplicate_quotes,
+                        report.clusters.len()
+                    );
+                }
+                tracing::info!(
+                    "Loaded quote corpus version {} ({} quotes) from {}",
+                    corpus.version,
+                    corpus.quotes.len(),
+                    self.file_path.display()
+                );
+                self.write_history(&corpus);
+                Arc::new(corpus)
+            }
+        };
+
+        self.install(stamp, fingerprint, Arc::clone(&corpus));
+        Ok(corpus)
+    }
+
+    /// Makes `corpus` the current one, as loaded from a file with `stamp`
+    /// and `fingerprint`.
+    fn install(&self, stamp: FileStamp, fingerprint: FileFingerprint, corpus: Arc<Corpus>) {
+        *self.loaded.write().unwrap_or_else(|e| e.into_inner()) = Some(Loaded {
+            stamp,
+            fingerprint,
+            corpus,
+        });
+    }
+
+    /// The loaded corpus, if the file is unchanged since it was loaded.
+    fn cached_by_stamp(&self, stamp: &FileStamp) -> Option<Arc<Corpus>> {
+        let loaded = self.loaded.read().unwrap_or_else(|e| e.into_inner());
+        loaded
+            .as_ref()
+            .filter(|l| stamp.unchanged_since(&l.stamp))
+            .map(|l| Arc::clone(&l.corpus))
+    }
+
+    /// The loaded corpus, if it was loaded from a file with `fingerprint`.
+    fn cached(&self, fingerprint: &FileFingerprint) -> Option<Arc<Corpus>> {
+        let loaded = self.loaded.read().unwrap_or_else(|e| e.into_inner());
+        loaded
+            .as_ref()
+            .filter(|l| l.fingerprint == *fingerprint)
+            .map(|l| Arc::clone(&l.corpus))
+    }
+
+    /// Reads the saved history, if there is a usable one.
+    fn read_history(&self) -> Option<History> {
+        let content = match fs::read_to_string(&self.history_path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to read quote history {}, starting a new one: {}",
+                    self.history_path.display(),
+                    err
+                );
+                return None;
+            }
+        };
+        serde_json::from_str(&content)
+            .map_err(|err| {
+                tracing::warn!(
+                    "Failed to parse quote history {}, starting a new one: {}",
+                    self.history_path.display(),
+                    err
+                );
+            })
+            .ok()
+    }
+
+    /// Saves the history of `corpus`, replacing the history file atomically.
+    /// Failures are logged: the service keeps running with its in-memory
+    /// history.
+    fn write_history(&self, corpus: &Corpus) {
+        let temp_path = self.history_path.with_extension("json.tmp");
+        let result = serde_json::to_vec(&corpus.history())
+            .map_err(io::Error::from)
+            .and_then(|bytes| fs::write(&temp_path, bytes))
+            .and_then(|()| fs::rename(&temp_path, &self.history_path));
+        if let Err(err) = result {
+            tracing::warn!(
+                "Failed to save quote history to {}: {}",
+                self.history_path.display(),
+                err
+            );
+        }
+    }
+
+    fn stat_file(&self) -> Result<FileStamp, QuoteServiceError> {
+        fs::metadata(&self.file_path)
+            .map(|metadata| FileStamp::of(&metadata))
+            .map_err(|err| self.file_error(err))
+    }
+
+    fn read_file(&self) -> Result<String, QuoteServiceError> {
+        fs::read_to_string(&self.file_path).map_err(|err| self.file_error(err))
+    }
+
+    fn file_error(&self, err: io::Error) -> QuoteServiceError {
+        if err.kind() == io::ErrorKind::NotFound {
+            QuoteServiceError::FileNotFound(self.file_path.to_string_lossy().into_owned())
+        } else {
+            QuoteServiceError::FileReadError(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_initial_load_stamps_quotes_without_revisions() {
+        let now = Utc::now();
+        let corpus = Corpus::from_load(None, vec![quote(1, "One", "Author", None)], now);
+        assert_eq!(corpus.version, 1);
+        assert_eq!(corpus.quotes[0].created_at, Some(now));
+        assert_eq!(corpus.quotes[0].updated_at, Some(now));
+        assert!(corpus.revisions_for(1).is_none());
+    }
+
+    #[test]
+    fn test_initial_load_keeps_editor_timestamps() {
+        let edited = "2020-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let q = Quote {
+            created_at: Some(edited),
+            ..quote(1, "One", "Author", None)
+        };
+        let corpus = Corpus::from_load(None, vec![q], Utc::now());
+        assert_eq!(corpus.quotes[0].created_at, Some(edited));
+        assert_eq!(corpus.quotes[0].updated_at, Some(edited));
+    }
+
+    #[test]
+    fn test_reload_records_created_updated_and_removed() {
+        let first_load = Utc::now();
+        let second_load = first_load + Duration::minutes(5);
+        let first = Corpus::from_load(
+            None,
+            vec![
+                quote(1, "One", "Author", None),
+                quote(2, "Two", "Author", None),
+                quote(3, "Three", "Author", None),
+            ],
+            first_load,
+        );
+        let second = Corpus::from_load(
+            Some(&first),
+            vec![
+                quote(1, "One", "Author", None),
+                quote(2, "Two, edited", "Author", None),
+                quote(4, "Four", "Author", None),
+            ],
+            second_load,
+        );
+
+        assert_eq!(second.version, 2);
+        assert!(second.revisions_for(1).is_none());
+        assert_eq!(second.quotes[0].updated_at, Some(first_load));
+
+        let updated = second.revisions_for(2).unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].kind, RevisionKind::Updated);
+        assert_eq!(updated[0].before.as_ref().unwrap().text, "Two");
+        assert_eq!(updated[0].after.as_ref().unwrap().text, "Two, edited");
+        assert_eq!(second.quotes[1].created_at, Some(first_load));
+        assert_eq!(second.quotes[1].updated_at, Some(second_load));
+
+        let removed = second.revisions_for(3).unwrap();
+        assert_eq!(removed[0].kind, RevisionKind::Removed);
+        assert!(removed[0].after.is_none());
+
+        let created = second.revisions_for(4).unwrap();
+        assert_eq!(created[0].kind, RevisionKind::Created);
+        assert_eq!(created[0].corpus_version, 2);
+        assert_eq!(second.quotes[2].created_at, Some(second_load));
+    }
+
+    #[test]
+    fn test_find_removed_quote() {
+        let now = Utc::now();
+        let v1 = Corpus::from_load(
+            None,
+            vec![quote(1, "a", "Author", None), quote(2, "b", "Author", None)],
+            now,
+        );
+        let v2 = Corpus::from_load(Some(&v1), vec![quote(2, "b", "Author", None)], now);
+        assert_eq!(v2.find_removed("1").unwrap().text, "a");
+        assert!(v2.find_removed("2").is_none());
+
+        let v3 = Corpus::from_load(
+            Some(&v2),
+            vec![quote(1, "a", "Author", None), quote(2, "b", "Author", None)],
+            now,
+        );
+        assert!(v3.find_removed("1").is_none());
+    }
+
+    #[test]
+    fn test_find_by_id_uid_and_slug() {
+        let mut quotes = vec![
+            quote(1, "One", "Author", None),
+            quote(2, "Two", "Author", None),
+            quote(2, "Shadowed", "Author", None),
+        ];
+        quotes[1].uid = "q-two".to_string();
+        quotes[1].slug = "author-two".to_string();
+        quotes[2].slug = "author-two".to_string();
+        let corpus = Corpus::from_load(None, quotes, Utc::now());
+
+        assert_eq!(corpus.find("2").unwrap().text, "Two");
+        assert_eq!(corpus.position("q-two"), Some(1));
+        assert_eq!(corpus.find("author-two").unwrap().text, "Two");
+        assert!(corpus.find("3").is_none());
+        assert!(corpus.find("missing").is_none());
+    }
+
+    #[test]
+    fn test_find_by_generated_identifiers_prefers_uid_over_slug() {
+        let mut quotes = vec![
+            quote(1, "First", "Author", None),
+            quote(2, "Second", "Author", None),
+        ];
+        quote_service::assign_identifiers(&mut quotes);
+        let uid = quotes[1].uid.clone();
+        // A slug that is also another quote's uid finds the uid's quote.
+        quotes[0].slug = uid.clone();
+        let corpus = Corpus::from_load(None, quotes, Utc::now());
+
+        assert_eq!(corpus.find(&uid).unwrap().id, 2);
+        assert_eq!(corpus.find("author-second").unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_created_revisions_newest_first() {
+        let now = Utc::now();
+        let v1 = Corpus::from_load(None, vec![quote(1, "a", "Author", None)], now);
+        assert!(v1.created_revisions().is_empty());
+        let v2 = Corpus::from_load(
+            Some(&v1),
+            vec![quote(1, "a", "Author", None), quote(2, "b", "Author", None)],
+            now,
+        );
+        let v3 = Corpus::from_load(
+            Some(&v2),
+            vec![
+                quote(1, "a edited", "Author", None),
+                quote(2, "b", "Author", None),
+                quote(3, "c", "Author", None),
+            ],
+            now,
+        );
+        let created: Vec<u32> = v3
+            .created_revisions()
+            .iter()
+            .map(|r| r.after.as_ref().unwrap().id)
+            .collect();
+        assert_eq!(created, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_revision_numbers_increase_per_quote() {
+        let now = Utc::now();
+        let v1 = Corpus::from_load(None, vec![quote(1, "a", "Author", None)], now);
+        let v2 = Corpus::from_load(Some(&v1), vec![quote(1, "b", "Author", None)], now);
+        let v3 = Corpus::from_load(Some(&v2), vec![quote(1, "c", "Author", None)], now);
+        let log = v3.revisions_for(1).unwrap();
+        assert_eq!(
+            log.iter().map(|r| r.revision).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(log[1].corpus_version, 3);
+    }
+
+    /// Writes `content` to a quotes file in `dir`, where its history file goes too.
+    fn quotes_file(dir: &TempDir, content: &str) -> PathBuf {
+        let path = dir.path().join("quotes.json");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_history_path_for() {
+        assert_eq!(
+            history_path_for(Path::new("data/quotes.json")),
+            PathBuf::from("data/quotes.history.json")
+        );
+    }
+
+    #[test]
+    fn test_store_reloads_only_when_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(&dir, r#"[{"id":1,"quote":"One","author":"A"}]"#);
+        let store = QuoteStore::new(path.clone());
+
+        let first = store.load_current().unwrap();
+        let again = store.load_current().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        std::fs::write(
+            &path,
+            r#"[{"id":1,"quote":"One, but longer","author":"A"}]"#,
+        )
+        .unwrap();
+        let second = store.load_current().unwrap();
+        assert_eq!(second.version, 2);
+        assert_eq!(second.revisions_for(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_ids_diff_the_first_quote_in_file_order() {
+        let now = Utc::now();
+        let v1 = Corpus::from_load(
+            None,
+            vec![
+                quote(1, "One", "Author", None),
+                quote(1, "Copy", "Author", None),
+            ],
+            now,
+        );
+        let later = now + Duration::minutes(1);
+        let v2 = Corpus::from_load(
+            Some(&v1),
+            vec![
+                quote(1, "One", "Author", None),
+                quote(1, "Copy", "Author", None),
+                quote(2, "Two", "Author", None),
+            ],
+            later,
+        );
+        assert_eq!(v2.find("1").unwrap().text, "One");
+        assert!(v2.revisions_for(1).is_none());
+        assert_eq!(v2.quotes[0].updated_at, Some(now));
+
+        let v3 = Corpus::from_load(
+            Some(&v2),
+            vec![
+                quote(1, "Uno", "Author", None),
+                quote(1, "Copy", "Author", None),
+            ],
+            later + Duration::minutes(1),
+        );
+        let log = v3.revisions_for(1).unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].before.as_ref().unwrap().text, "One");
+        assert_eq!(log[0].after.as_ref().unwrap().text, "Uno");
+    }
+
+    #[test]
+    fn test_store_reloads_same_length_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(&dir, r#"[{"id":1,"quote":"One","author":"A"}]"#);
+        let store = QuoteStore::new(path.clone());
+        let first = store.load_current().unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let set_modified = |at| {
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(at)
+                .unwrap()
+        };
+
+        std::fs::write(&path, r#"[{"id":1,"quote":"Uno","author":"A"}]"#).unwrap();
+        set_modified(modified + std::time::Duration::from_secs(1));
+        let second = store.load_current().unwrap();
+        assert_eq!(first.version, 1);
+        assert_eq!(second.version, 2);
+        assert_eq!(second.quotes[0].text, "Uno");
+
+        // Touching the file without changing it keeps the version.
+        set_modified(modified + std::time::Duration::from_secs(2));
+        assert!(Arc::ptr_eq(&second, &store.load_current().unwrap()));
+    }
+
+    #[test]
+    fn test_store_reads_file_only_when_metadata_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(&dir, r#"[{"id":1,"quote":"One","author":"A"}]"#);
+        let store = QuoteStore::new(path.clone());
+        let first = store.load_current().unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+        // Same length and modification time: the content is not read again.
+        std::fs::write(&path, r#"[{"id":1,"quote":"Uno","author":"A"}]"#).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &store.load_current().unwrap()));
+    }
+
+    #[test]
+    fn test_store_keeps_history_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(&dir, r#"[{"id":1,"quote":"One","author":"A"}]"#);
+        let first = QuoteStore::new(path.clone()).load_current().unwrap();
+
+        let restarted = QuoteStore::new(path.clone()).load_current().unwrap();
+        assert_eq!(restarted.version, 1);
+        assert_eq!(restarted.quotes[0].created_at, first.quotes[0].created_at);
+
+        std::fs::write(
+            &path,
+            r#"[{"id":1,"quote":"Uno","author":"A"},{"id":2,"quote":"Two","author":"B"}]"#,
+        )
+        .unwrap();
+        let restarted = QuoteStore::new(path.clone()).load_current().unwrap();
+        assert_eq!(restarted.version, 2);
+        assert_eq!(restarted.quotes[0].created_at, first.quotes[0].created_at);
+        let log = restarted.revisions_for(1).unwrap();
+        assert_eq!(log[0].kind, RevisionKind::Updated);
+        assert_eq!(log[0].before.as_ref().unwrap().text, "One");
+        assert_eq!(restarted.created_revisions().len(), 1);
+    }
+
+    #[test]
+    fn test_store_starts_over_from_unreadable_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(&dir, r#"[{"id":1,"quote":"One","author":"A"}]"#);
+        std::fs::write(history_path_for(&path), "not json").unwrap();
+
+        let corpus = QuoteStore::new(path.clone()).load_current().unwrap();
+        assert_eq!(corpus.version, 1);
+        assert!(corpus.revisions_for(1).is_none());
+    }
+
+    #[test]
+    fn test_store_missing_file() {
+        let store = QuoteStore::new(PathBuf::from("non_existent_store_file.json"));
+        assert!(matches!(
+            store.load_current(),
+            Err(QuoteServiceError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_store_refuses_too_many_near_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(
+            &dir,
+            r#"[{"id":1,"quote":"Stay hungry, stay foolish.","author":"A"},
+               {"id":2,"quote":"Stay hungry; stay foolish!","author":"A"},
+               {"id":3,"quote":"Less is more.","author":"B"},
+               {"id":4,"quote":"Simplicity is the ultimate sophistication.","author":"C"}]"#,
+        );
+
+        let corpus = QuoteStore::new(path.clone())
+            .with_max_duplicate_rate(Some(0.25))
+            .load_current()
+            .unwrap();
+        assert_eq!(corpus.duplicates.duplicate_rate, 0.25);
+
+        let store = QuoteStore::new(path).with_max_duplicate_rate(Some(0.2));
+        assert!(matches!(
+            store.load_current(),
+            Err(QuoteServiceError::TooManyDuplicates { clusters: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_store_keeps_previous_corpus_when_reload_has_too_many_near_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = quotes_file(
+            &dir,
+            r#"[{"id":1,"quote":"Stay hungry, stay foolish.","author":"A"},
+               {"id":2,"quote":"Less is more.","author":"B"}]"#,
+        );
+        let store = QuoteStore::new(path.clone()).with_max_duplicate_rate(Some(0.2));
+        let v1 = store.load_current().unwrap();
+
+        fs::write(
+            &path,
+            r#"[{"id":1,"quote":"Stay hungry, stay foolish.","author":"A"},
+               {"id":2,"quote":"Less is more.","author":"B"},
+               {"id":3,"quote":"Stay hungry; stay foolish!","author":"A"}]"#,
+        )
+        .unwrap();
+        let refused = store.load_current().unwrap();
+        assert!(Arc::ptr_eq(&v1, &refused));
+        // The refused file is remembered, not rebuilt on every request.
+        assert_eq!(
+            store.loaded.read().unwrap().as_ref().unwrap().fingerprint,
+            FileFingerprint::of(&fs::read_to_string(&path).unwrap())
+        );
+        assert!(Arc::ptr_eq(&v1, &store.load_current().unwrap()));
+
+        fs::write(
+            &path,
+            r#"[{"id":1,"quote":"Stay hungry, stay foolish.","author":"A"},
+               {"id":2,"quote":"Less is more.","author":"B"},
+               {"id":3,"quote":"Simplicity is the ultimate sophistication.","author":"C"}]"#,
+        )
+        .unwrap();
+        let v2 = store.load_current().unwrap();
+        assert_eq!(v2.version, v1.version + 1);
+        assert_eq!(v2.quotes.len(), 3);
+    }
+}
@@ -0,0 +1,240 @@
+//! # Quote Similarity
+//!
+//! Ranks quotes by textual similarity for "you might also like"
+//! recommendations. Each quote is represented by a TF-IDF vector over the
+//! words of its text, without stopwords, normalized to unit length so that
+//! the dot product of two vectors is their cosine similarity.
+//!
+//! The quote store builds a [`SimilarityIndex`] once per load of the quotes
+//! file. It keeps, for every term, the quotes containing it with their
+//! weights, so scoring a quote only touches the quotes it shares a term with.
+//! Authors are folded with [`match_key`] at build time and grouped the same
+//! way, so the author boost only touches the author's other quotes.
+
+use crate::models::Quote;
+use crate::utils::{is_stopword, match_key, words};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Number of similar quotes returned when no limit is requested.
+pub const DEFAULT_SIMILAR_LIMIT: usize = 5;
+
+/// Maximum number of similar quotes returned per request.
+pub const MAX_SIMILAR_LIMIT: usize = 50;
+
+/// Precomputed TF-IDF vectors for the quotes of a corpus.
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityIndex {
+    /// Per quote, in corpus order: `(term, weight)` pairs of its unit vector.
+    vectors: Vec<Vec<(usize, f64)>>,
+    /// Per term: `(quote, weight)` pairs for the quotes containing it.
+    postings: Vec<Vec<(usize, f64)>>,
+    /// Per quote, in corpus order: the id of its folded author.
+    authors: Vec<usize>,
+    /// Per author id: the quotes by that author.
+    author_quotes: Vec<Vec<usize>>,
+}
+
+impl SimilarityIndex {
+    /// Computes the vectors for `quotes`. Quotes are referred to by their
+    /// position in `quotes` afterwards.
+    pub fn build(quotes: &[Quote]) -> Self {
+        let mut term_ids: HashMap<String, usize> = HashMap::new();
+        let mut document_frequency: Vec<usize> = Vec::new();
+        let term_counts: Vec<HashMap<usize, usize>> = quotes
+            .iter()
+            .map(|quote| {
+                let mut counts: HashMap<usize, usize> = HashMap::new();
+                for word in words(&quote.text).into_iter().filter(|w| !is_stopword(w)) {
+                    let next_id = term_ids.len();
+                    let term = *term_ids.entry(word).or_insert(next_id);
+                    if term == document_frequency.len() {
+                        document_frequency.push(0);
+                    }
+                    let count = counts.entry(term).or_default();
+                    if *count == 0 {
+                        document_frequency[term] += 1;
+                    }
+                    *count += 1;
+                }
+                counts
+            })
+            .collect();
+
+        // Smoothed IDF, so a term found in every quote still counts a little.
+        let total = quotes.len() as f64;
+        let idf: Vec<f64> = document_frequency
+            .iter()
+            .map(|&df| ((1.0 + total) / (1.0 + df as f64)).ln() + 1.0)
+            .collect();
+
+        let mut postings: Vec<Vec<(usize, f64)>> = vec![Vec::new(); idf.len()];
+        let vectors = term_counts
+            .into_iter()
+            .enumerate()
+            .map(|(quote, counts)| {
+                let mut vector: Vec<(usize, f64)> = counts
+                    .into_iter()
+                    .map(|(term, count)| (term, (1.0 + (count as f64).ln()) * idf[term]))
+                    .collect();
+                let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+                for (term, weight) in &mut vector {
+                    *weight /= norm;
+                    postings[*term].push((quote, *weight));
+                }
+                vector
+            })
+            .collect();
+
+        let mut author_ids: HashMap<String, usize> = HashMap::new();
+        let mut author_quotes: Vec<Vec<usize>> = Vec::new();
+        let authors = quotes
+            .iter()
+            .enumerate()
+            .map(|(quote, q)| {
+                let next_id = author_ids.len();
+                let author = *author_ids.entry(match_key(&q.author)).or_insert(next_id);
+                if author == author_quotes.len() {
+                    author_quotes.push(Vec::new());
+                }
+                author_quotes[author].push(quote);
+                author
+            })
+            .collect();
+
+        SimilarityIndex {
+            vectors,
+            postings,
+            authors,
+            author_quotes,
+        }
+    }
+
+    /// Cosine similarity between the quote at `index` and every other quote
+    /// sharing a term with it. Quotes not listed have similarity 0.
+    pub fn cosine_scores(&self, index: usize) -> HashMap<usize, f64> {
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for &(term, weight) in self.vectors.get(index).into_iter().flatten() {
+            for &(other, other_weight) in &self.postings[term] {
+                if other != index {
+                    *scores.entry(other).or_default() += weight * other_weight;
+                }
+            }
+        }
+        scores
+    }
+
+    /// Ranks the quotes that could be recommended alongside the quote at
+    /// `index`: active quotes, published at `at`, other than itself.
+    ///
+    /// Quotes by the same author get `author_boost` added to their score, so
+    /// a positive boost can surface them even without shared words. Quotes
+    /// scoring 0 are left out. Returns at most `limit` `(quote, score)` pairs,
+    /// best first, ties by id.
+    pub fn similar<'a>(
+        &self,
+        quotes: &'a [Quote],
+        index: usize,
+        author_boost: f64,
+        at: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<(&'a Quote, f64)> {
+        let mut scores = self.cosine_scores(index);
+        if author_boost > 0.0 {
+            if let Some(&author) = self.authors.get(index) {
+                for &other in &self.author_quotes[author] {
+                    if other != index {
+                        *scores.entry(other).or_default() += author_boost;
+                    }
+                }
+            }
+        }
+
+        let mut ranked: Vec<(&Quote, f64)> = scores
+            .into_iter()
+            .filter(|&(_, score)| score > 0.0)
+            .filter_map(|(other, score)| quotes.get(other).map(|quote| (quote, score)))
+            .filter(|(quote, _)| quote.status.is_active() && quote.is_published_at(at))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
+
+    fn ids(ranked: &[(&Quote, f64)]) -> Vec<u32> {
+        ranked.iter().map(|(q, _)| q.id).collect()
+    }
+
+    #[test]
+    fn test_cosine_scores() {
+        let quotes = [
+            quote(
+                1,
+                "The only way to do great work is to love what you do.",
+                "A",
+                None,
+            ),
+            quote(2, "Love the work you do.", "B", None),
+            quote(3, "Great things take time.", "C", None),
+            quote(4, "Nothing in common here.", "D", None),
+        ];
+        let index = SimilarityIndex::build(&quotes);
+
+        let scores = index.cosine_scores(0);
+        assert!(scores[&1] > scores[&2]);
+        assert!(!scores.contains_key(&3));
+        assert!(!scores.contains_key(&0));
+        // Identical texts are fully similar.
+        let twins = SimilarityIndex::build(&[
+            quote(1, "Same words.", "A", None),
+            quote(2, "same WORDS", "B", None),
+        ]);
+        assert!((twins.cosine_scores(0)[&1] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_similar_filters_and_boosts() {
+        let archived = Quote {
+            status: QuoteStatus::Archived,
+            ..quote(5, "Love your work.", "B", None)
+        };
+        let quotes = [
+            quote(1, "Love your work.", "A", None),
+            quote(2, "Work hard, love harder.", "B", None),
+            quote(3, "Unrelated musings.", "A", None),
+            quote(4, "Love conquers all.", "C", None),
+            archived,
+        ];
+        let index = SimilarityIndex::build(&quotes);
+        let now = Utc::now();
+
+        let ranked = index.similar(&quotes, 0, 0.0, now, 10);
+        assert_eq!(ids(&ranked), [2, 4]);
+        assert!(ranked[0].1 > ranked[1].1);
+
+        // The boost brings in the author's other quote and lifts it to the top.
+        let ranked = index.similar(&quotes, 0, 1.0, now, 10);
+        assert_eq!(ids(&ranked), [3, 2, 4]);
+        assert_eq!(index.similar(&quotes, 0, 1.0, now, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_author_boost_matches_folded_names() {
+        let quotes = [
+            quote(1, "First words.", "Émile Zola", None),
+            quote(2, "Second thoughts.", "emile zola", None),
+            quote(3, "Third time.", "Emily Zola", None),
+        ];
+        let index = SimilarityIndex::build(&quotes);
+        let ranked = index.similar(&quotes, 0, 0.5, Utc::now(), 10);
+        assert_eq!(ids(&ranked), [2]);
+        assert_eq!(ranked[0].1, 0.5);
+    }
+}
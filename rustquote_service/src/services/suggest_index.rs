@@ -0,0 +1,375 @@
+//! # Suggestion Index
+//!
+//! Type-ahead suggestions for the search box. The quote store builds a
+//! [`SuggestIndex`] once per load of the quotes file, so a lookup is a binary
+//! search over sorted keys rather than a scan of the corpus.
+//!
+//! Keys are folded with [`match_key`], so matching ignores case and accents.
+//! Authors match on the start of any word of their name ("jo" suggests
+//! "Steve Jobs"); terms are the words of quote texts, without common English
+//! stopwords. Only active quotes are indexed. Suggestions are ranked by the
+//! number of quotes they appear in.
+//!
+//! Publishing windows are kept per indexed quote and evaluated at lookup
+//! time, as in the facet index, so a scheduled quote contributes nothing
+//! before it is published and the index never has to be rebuilt as windows
+//! open and close.
+
+use crate::errors::AppError;
+use crate::models::{PublishWindow, Quote};
+use crate::utils::{is_stopword, match_key, words};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Default maximum number of suggestions returned per request.
+pub const DEFAULT_SUGGEST_MAX_RESULTS: usize = 10;
+
+/// Upper bound for the configurable maximum number of suggestions.
+pub const SUGGEST_MAX_RESULTS_LIMIT: usize = 100;
+
+/// Terms shorter than this are not suggested.
+const MIN_TERM_LEN: usize = 2;
+
+/// What a suggestion completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestKind {
+    Author,
+    Term,
+}
+
+impl FromStr for SuggestKind {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "author" => Ok(SuggestKind::Author),
+            "term" => Ok(SuggestKind::Term),
+            _ => Err(AppError::BadRequest(format!(
+                "Unknown kind '{}'; expected author or term.",
+                value
+            ))),
+        }
+    }
+}
+
+/// One suggestion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Suggestion {
+    /// The completed author name or term, in its most common spelling.
+    pub text: String,
+    /// The number of active, published quotes by the author or containing the term.
+    pub quotes: usize,
+}
+
+/// Prefix indexes over the authors and terms of a corpus.
+#[derive(Debug, Clone, Default)]
+pub struct SuggestIndex {
+    authors: PrefixIndex,
+    terms: PrefixIndex,
+}
+
+impl SuggestIndex {
+    /// Indexes the active quotes in `quotes`.
+    pub fn build(quotes: &[Quote]) -> Self {
+        let mut authors = Tally::default();
+        let mut terms = Tally::default();
+        for quote in quotes.iter().filter(|q| q.status.is_active()) {
+            let author = quote.author.trim();
+            if !author.is_empty() {
+                authors.add(match_key(author), author, quote.publish_window());
+            }
+
+            let mut seen: Vec<String> = Vec::new();
+            for word in words(&quote.text) {
+                if word.chars().count() < MIN_TERM_LEN || is_stopword(&word) || seen.contains(&word)
+                {
+                    continue;
+                }
+                seen.push(word);
+            }
+            for word in seen {
+                let spelling = original_spelling(&quote.text, &word);
+                terms.add(word, &spelling, quote.publish_window());
+            }
+        }
+
+        SuggestIndex {
+            authors: authors.into_index(words),
+            terms: terms.into_index(|key| vec![key.to_string()]),
+        }
+    }
+
+    /// Returns up to `limit` suggestions of `kind` starting with `prefix`,
+    /// counting only quotes published at `at`, most frequent first, ties in
+    /// alphabetical order.
+    pub fn suggest(
+        &self,
+        kind: SuggestKind,
+        prefix: &str,
+        limit: usize,
+        at: DateTime<Utc>,
+    ) -> Vec<Suggestion> {
+        let index = match kind {
+            SuggestKind::Author => &self.authors,
+            SuggestKind::Term => &self.terms,
+        };
+        index.lookup(&match_key(prefix), limit, at)
+    }
+}
+
+/// Collects the spelling and publishing window of every quote by folded key.
+#[derive(Default)]
+struct Tally {
+    quotes: HashMap<String, Vec<(String, PublishWindow)>>,
+}
+
+impl Tally {
+    fn add(&mut self, key: String, spelling: &str, window: PublishWindow) {
+        self.quotes
+            .entry(key)
+            .or_default()
+            .push((spelling.to_string(), window));
+    }
+
+    /// Builds the index, with each entry reachable from the keys `keys_for`
+    /// derives from its folded key.
+    fn into_index(self, keys_for: impl Fn(&str) -> Vec<String>) -> PrefixIndex {
+        let mut entries: Vec<Entry> = Vec::with_capacity(self.quotes.len());
+        let mut keys: Vec<(String, usize)> = Vec::new();
+        for (key, quotes) in self.quotes {
+            let mut spellings: Vec<String> = quotes.iter().map(|(s, _)| s.clone()).collect();
+            spellings.sort();
+            spellings.dedup();
+            let mut entry = Entry {
+                unscheduled: vec![0; spellings.len()],
+                scheduled: Vec::new(),
+                spellings,
+            };
+            for (spelling, window) in quotes {
+                let spelling = entry
+                    .spellings
+                    .binary_search(&spelling)
+                    .expect("every spelling was collected");
+                if window.is_unbounded() {
+                    entry.unscheduled[spelling] += 1;
+                } else {
+                    entry.scheduled.push((spelling, window));
+                }
+            }
+            for index_key in keys_for(&key) {
+                keys.push((index_key, entries.len()));
+            }
+            entries.push(entry);
+        }
+        keys.sort();
+        keys.dedup();
+        PrefixIndex { keys, entries }
+    }
+}
+
+/// The quotes behind one suggestion.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Spellings of the author or term, in alphabetical order.
+    spellings: Vec<String>,
+    /// Quotes without a publishing window, by spelling.
+    unscheduled: Vec<usize>,
+    /// Spelling and window of each quote with a publishing window.
+    scheduled: Vec<(usize, PublishWindow)>,
+}
+
+impl Entry {
+    /// The suggestion as of `at`, in its most common spelling among the
+    /// quotes published then, or `None` if there are none.
+    fn suggestion_at(&self, at: DateTime<Utc>) -> Option<Suggestion> {
+        let mut counts = self.unscheduled.clone();
+        for (spelling, window) in &self.scheduled {
+            if window.contains(at) {
+                counts[*spelling] += 1;
+            }
+        }
+        let quotes: usize = counts.iter().sum();
+        if quotes == 0 {
+            return None;
+        }
+        let (spelling, _) = counts
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(&a.0)))?;
+        Some(Suggestion {
+            text: self.spellings[spelling].clone(),
+            quotes,
+        })
+    }
+}
+
+/// Folded keys in sorted order, each pointing at an entry, so that all keys
+/// with a given prefix form one contiguous run.
+#[derive(Debug, Clone, Default)]
+struct PrefixIndex {
+    keys: Vec<(String, usize)>,
+    entries: Vec<Entry>,
+}
+
+impl PrefixIndex {
+    fn lookup(&self, prefix: &str, limit: usize, at: DateTime<Utc>) -> Vec<Suggestion> {
+        if prefix.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let start = self.keys.partition_point(|(key, _)| key.as_str() < prefix);
+        let mut matches: Vec<usize> = self.keys[start..]
+            .iter()
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(_, entry)| *entry)
+            .collect();
+        matches.sort_unstable();
+        matches.dedup();
+
+        let mut suggestions: Vec<Suggestion> = matches
+            .iter()
+            .filter_map(|&i| self.entries[i].suggestion_at(at))
+            .collect();
+        suggestions.sort_by(|a, b| b.quotes.cmp(&a.quotes).then_with(|| a.text.cmp(&b.text)));
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+/// The lowercase form of `word` as written in `text`, keeping its accents,
+/// e.g. "café" for the folded word "cafe".
+fn original_spelling(text: &str, word: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .find(|w| match_key(w) == word)
+        .map(str::to_lowercase)
+        .unwrap_or_else(|| word.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    fn texts(suggestions: &[Suggestion]) -> Vec<(&str, usize)> {
+        suggestions
+            .iter()
+            .map(|s| (s.text.as_str(), s.quotes))
+            .collect()
+    }
+
+    #[test]
+    fn test_author_suggestions() {
+        let index = SuggestIndex::build(&[
+            quote(1, "One.", "Émile Zola", None),
+            quote(2, "Two.", "Emile Zola", None),
+            quote(3, "Three.", "Émile Zola", None),
+            quote(4, "Four.", "Steve Jobs", None),
+            quote(5, "Five.", "Emily Dickinson", None),
+        ]);
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Author, "EMI", 10, Utc::now())),
+            [("Émile Zola", 3), ("Emily Dickinson", 1)]
+        );
+        // Any word of the name can start the match.
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Author, "jo", 10, Utc::now())),
+            [("Steve Jobs", 1)]
+        );
+        assert_eq!(
+            index
+                .suggest(SuggestKind::Author, "emi", 1, Utc::now())
+                .len(),
+            1
+        );
+        assert!(index
+            .suggest(SuggestKind::Author, "", 10, Utc::now())
+            .is_empty());
+        assert!(index
+            .suggest(SuggestKind::Author, "x", 10, Utc::now())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_term_suggestions() {
+        let archived = Quote {
+            status: crate::models::QuoteStatus::Archived,
+            ..quote(4, "Stars and stairs.", "Editor", None)
+        };
+        let index = SuggestIndex::build(&[
+            quote(1, "Stay hungry, stay foolish.", "Steve Jobs", None),
+            quote(2, "The café stays open.", "Someone", None),
+            quote(3, "Stay the course at the Café.", "Someone", None),
+            archived,
+        ]);
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Term, "st", 10, Utc::now())),
+            [("stay", 2), ("stays", 1)]
+        );
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Term, "CAFE", 10, Utc::now())),
+            [("café", 2)]
+        );
+        // Stopwords are not suggested.
+        assert!(index
+            .suggest(SuggestKind::Term, "the", 10, Utc::now())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_scheduled_quotes_count_only_while_published() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let scheduled = |id, text, author| Quote {
+            publish_at: Some(at("2025-12-01T00:00:00Z")),
+            expire_at: Some(at("2025-12-27T00:00:00Z")),
+            ..quote(id, text, author, None)
+        };
+        let index = SuggestIndex::build(&[
+            quote(1, "Stay hungry.", "Steve Jobs", None),
+            scheduled(2, "Stay merry.", "STEVE JOBS"),
+            scheduled(3, "Stay merry and bright.", "STEVE JOBS"),
+            scheduled(4, "Merry Christmas.", "Santa"),
+        ]);
+
+        let before = at("2025-11-30T23:59:59Z");
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Author, "ste", 10, before)),
+            [("Steve Jobs", 1)]
+        );
+        assert!(index
+            .suggest(SuggestKind::Author, "sa", 10, before)
+            .is_empty());
+        assert!(index
+            .suggest(SuggestKind::Term, "mer", 10, before)
+            .is_empty());
+
+        let during = at("2025-12-01T00:00:00Z");
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Author, "ste", 10, during)),
+            [("STEVE JOBS", 3)]
+        );
+        assert_eq!(
+            texts(&index.suggest(SuggestKind::Term, "mer", 10, during)),
+            [("merry", 3)]
+        );
+
+        let after = at("2025-12-27T00:00:00Z");
+        assert!(index
+            .suggest(SuggestKind::Term, "mer", 10, after)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_kind_from_str() {
+        assert_eq!(
+            " Author ".parse::<SuggestKind>().unwrap(),
+            SuggestKind::Author
+        );
+        assert!(matches!(
+            "tag".parse::<SuggestKind>(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+//! # Shutdown Signal
+//!
+//! This module provides a cloneable signal that tells the HTTP server, the
+//! gRPC server and long-lived connections such as WebSocket streams that the
+//! process is shutting down.
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// A one-shot, cloneable shutdown signal.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl Shutdown {
+    /// Signals shutdown to every current and future waiter.
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Returns `true` once shutdown has been triggered.
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Completes once shutdown has been triggered.
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this cannot fail.
+        let _ = receiver.wait_for(|&triggered| triggered).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_completes_after_trigger() {
+        let shutdown = Shutdown::default();
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+        assert!(!shutdown.is_triggered());
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        // Waiting after the fact returns immediately.
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait())
+            .await
+            .unwrap();
+    }
+}
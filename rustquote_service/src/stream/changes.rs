@@ -0,0 +1,228 @@
+//! # Quote Change Log
+//!
+//! Turns the revisions recorded by corpus reloads into numbered change
+//! events for the Server-Sent Events stream. Events are numbered from 1 in
+//! the order they were observed, and the most recent [`REPLAY_BUFFER_SIZE`]
+//! are kept so that a reconnecting client can catch up from its
+//! `Last-Event-ID`.
+//!
+//! Changes are described as the public sees them: a quote that becomes
+//! active and published is `added`, one that stops being so is `removed`,
+//! and a visible quote whose content changed is `changed`. Edits to archived,
+//! deleted or unpublished quotes produce no events.
+
+use crate::models::Quote;
+use crate::services::quote_store::Corpus;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Number of change events kept for `Last-Event-ID` resumption.
+pub const REPLAY_BUFFER_SIZE: usize = 256;
+
+/// How a reload changed a publicly visible quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+impl ChangeKind {
+    /// The SSE event name for this kind of change.
+    pub fn event_name(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Changed => "changed",
+            ChangeKind::Removed => "removed",
+        }
+    }
+}
+
+/// One numbered change event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteChange {
+    /// Event id, increasing by one per event.
+    pub id: u64,
+    pub kind: ChangeKind,
+    /// The corpus version in which the change was observed.
+    pub corpus_version: u64,
+    /// The quote after the change, or its last visible version if removed.
+    pub quote: Quote,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Corpus version up to which revisions have been turned into events.
+    seen_version: Option<u64>,
+    /// Id of the most recent event, or 0 before the first one.
+    latest_id: u64,
+    events: VecDeque<QuoteChange>,
+}
+
+/// Numbered quote change events shared by every stream.
+#[derive(Debug, Default)]
+pub struct ChangeLog {
+    inner: Mutex<Inner>,
+}
+
+impl ChangeLog {
+    /// Records events for the reloads that produced `corpus` since the last
+    /// call. The first call only sets the starting point, since there is
+    /// nothing earlier to compare against.
+    ///
+    /// Visibility is evaluated at `now`.
+    pub fn observe(&self, corpus: &Corpus, now: DateTime<Utc>) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(seen_version) = inner.seen_version else {
+            inner.seen_version = Some(corpus.version);
+            return;
+        };
+        if corpus.version <= seen_version {
+            return;
+        }
+
+        for revision in corpus.revisions_since(seen_version) {
+            let before = visible_at(&revision.before, now);
+            let after = visible_at(&revision.after, now);
+            let (kind, quote) = match (before, after) {
+                (None, Some(after)) => (ChangeKind::Added, after),
+                (Some(_), Some(after)) => (ChangeKind::Changed, after),
+                (Some(before), None) => (ChangeKind::Removed, before),
+                (None, None) => continue,
+            };
+            inner.latest_id += 1;
+            let change = QuoteChange {
+                id: inner.latest_id,
+                kind,
+                corpus_version: revision.corpus_version,
+                quote: quote.clone(),
+            };
+            inner.events.push_back(change);
+            if inner.events.len() > REPLAY_BUFFER_SIZE {
+                inner.events.pop_front();
+            }
+        }
+        inner.seen_version = Some(corpus.version);
+    }
+
+    /// Id of the most recent event, or 0 if there has been none.
+    pub fn latest_id(&self) -> u64 {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .latest_id
+    }
+
+    /// Returns the events after `last_id`, oldest first.
+    ///
+    /// Returns `None` if the log cannot tell what happened since `last_id`:
+    /// either some of the events after it were already dropped from the
+    /// buffer, or it was never issued (for example, it predates a restart).
+    pub fn since(&self, last_id: u64) -> Option<Vec<QuoteChange>> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let oldest_id = inner.events.front().map_or(inner.latest_id + 1, |e| e.id);
+        if last_id > inner.latest_id || last_id + 1 < oldest_id {
+            return None;
+        }
+        Some(
+            inner
+                .events
+                .iter()
+                .filter(|e| e.id > last_id)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// Returns the quote if it is active and published at `now`.
+fn visible_at(quote: &Option<Quote>, now: DateTime<Utc>) -> Option<&Quote> {
+    quote
+        .as_ref()
+        .filter(|q| q.status.is_active() && q.is_published_at(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+    use crate::models::QuoteStatus;
+
+    #[test]
+    fn test_observe_describes_visible_changes() {
+        let now = Utc::now();
+        let log = ChangeLog::default();
+        let v1 = Corpus::from_load(
+            None,
+            vec![
+                quote(1, "One", "Author", None),
+                quote(2, "Two", "Author", None),
+            ],
+            now,
+        );
+        log.observe(&v1, now);
+        assert_eq!(log.latest_id(), 0);
+
+        let archived = Quote {
+            status: QuoteStatus::Archived,
+            ..quote(4, "Archived", "Author", None)
+        };
+        let v2 = Corpus::from_load(
+            Some(&v1),
+            vec![
+                quote(2, "Two, edited", "Author", None),
+                quote(3, "Three", "Author", None),
+                archived,
+            ],
+            now,
+        );
+        log.observe(&v2, now);
+        // Observing the same version again records nothing.
+        log.observe(&v2, now);
+
+        let changes = log.since(0).unwrap();
+        let summary: Vec<(u64, ChangeKind, u32)> =
+            changes.iter().map(|c| (c.id, c.kind, c.quote.id)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, ChangeKind::Removed, 1),
+                (2, ChangeKind::Changed, 2),
+                (3, ChangeKind::Added, 3),
+            ]
+        );
+        assert_eq!(changes[0].quote.text, "One");
+        assert_eq!(changes[1].quote.text, "Two, edited");
+        assert!(changes.iter().all(|c| c.corpus_version == 2));
+        assert_eq!(log.since(2).unwrap().len(), 1);
+        assert!(log.since(3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_since_rejects_evicted_and_unknown_ids() {
+        let now = Utc::now();
+        let log = ChangeLog::default();
+        let mut corpus = Corpus::from_load(None, vec![], now);
+        log.observe(&corpus, now);
+        assert!(log.since(0).unwrap().is_empty());
+        assert!(log.since(1).is_none());
+
+        for round in 0..REPLAY_BUFFER_SIZE + 10 {
+            let text = format!("Version {}", round);
+            corpus = Corpus::from_load(Some(&corpus), vec![quote(1, &text, "Author", None)], now);
+            log.observe(&corpus, now);
+        }
+        let latest = log.latest_id();
+        assert_eq!(latest, (REPLAY_BUFFER_SIZE + 10) as u64);
+        assert!(log.since(0).is_none());
+        assert!(log.since(latest - REPLAY_BUFFER_SIZE as u64 - 1).is_none());
+        assert_eq!(
+            log.since(latest - REPLAY_BUFFER_SIZE as u64).unwrap().len(),
+            REPLAY_BUFFER_SIZE
+        );
+        assert!(log.since(latest + 1).is_none());
+    }
+}
@@ -0,0 +1,335 @@
+//! # Quote Streams
+//!
+//! This module holds what live quote streams have in common: a
+//! [`Subscription`] describing how often to push a quote, which quotes are
+//! eligible and in which order they rotate, and the [`Rotation`] that picks
+//! the next quote from the current corpus. The `ws` submodule serves it over
+//! WebSocket and the `sse` submodule over Server-Sent Events; `changes` keeps
+//! the reload events that the latter pushes.
+
+pub mod changes;
+pub mod sse;
+pub mod ws;
+
+use crate::errors::AppError;
+use crate::models::Quote;
+use crate::services::quote_service;
+use crate::services::quote_store::Corpus;
+use crate::utils::match_key;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::OwnedSemaphorePermit;
+
+/// Shortest accepted push interval, in seconds.
+pub const MIN_INTERVAL_SECS: u64 = 5;
+
+/// Longest accepted push interval, in seconds.
+pub const MAX_INTERVAL_SECS: u64 = 3_600;
+
+/// Push interval used when a subscription does not set one, in seconds.
+pub const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+/// Order in which a stream moves through the eligible quotes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationMode {
+    /// A random quote each time, never the same one twice in a row unless it
+    /// is the only one.
+    #[default]
+    Random,
+    /// Quotes in id order, starting over after the last one.
+    Sequential,
+    /// The quote of the day, as served by the daily feeds.
+    Daily,
+}
+
+impl FromStr for RotationMode {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "random" => Ok(RotationMode::Random),
+            "sequential" => Ok(RotationMode::Sequential),
+            "daily" => Ok(RotationMode::Daily),
+            _ => Err(AppError::BadRequest(format!(
+                "Unknown rotation '{}'; expected random, sequential or daily.",
+                value
+            ))),
+        }
+    }
+}
+
+/// Restricts which quotes a stream may push.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamFilters {
+    /// Only quotes by this author, matched ignoring case and accents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Only quotes at least this many characters long.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    /// Only quotes at most this many characters long.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
+
+impl StreamFilters {
+    /// Returns `true` if `quote` passes every filter.
+    pub fn matches(&self, quote: &Quote) -> bool {
+        let length = quote.text.chars().count();
+        self.author
+            .as_deref()
+            .is_none_or(|author| match_key(author) == match_key(&quote.author))
+            && self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+    }
+}
+
+/// What a client asked a stream to push.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Seconds between quotes.
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub rotation: RotationMode,
+    #[serde(default)]
+    pub filters: StreamFilters,
+}
+
+fn default_interval_secs() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Subscription {
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            rotation: RotationMode::default(),
+            filters: StreamFilters::default(),
+        }
+    }
+}
+
+impl Subscription {
+    /// Checks the interval and filter bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::BadRequest`] describing the first invalid setting.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&self.interval_secs) {
+            return Err(AppError::BadRequest(format!(
+                "interval_secs must be between {} and {}, got {}.",
+                MIN_INTERVAL_SECS, MAX_INTERVAL_SECS, self.interval_secs
+            )));
+        }
+        if let (Some(min), Some(max)) = (self.filters.min_length, self.filters.max_length) {
+            if min > max {
+                return Err(AppError::BadRequest(format!(
+                    "min_length ({}) must not exceed max_length ({}).",
+                    min, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Time between quotes.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+/// Reserves one of the stream connection slots for a new stream.
+///
+/// # Errors
+///
+/// Returns an [`AppError::ServiceUnavailable`] if the stream connection limit
+/// has been reached or the server is shutting down.
+pub fn acquire_stream_permit(app_state: &AppState) -> Result<OwnedSemaphorePermit, AppError> {
+    if app_state.shutdown.is_triggered() {
+        return Err(AppError::ServiceUnavailable(
+            "The server is shutting down.".to_string(),
+        ));
+    }
+    app_state
+        .stream_connections
+        .clone()
+        .try_acquire_owned()
+        .map_err(|_| {
+            tracing::warn!("Rejected a quote stream: connection limit reached.");
+            AppError::ServiceUnavailable(format!(
+                "Too many open quote streams; at most {} are allowed.",
+                app_state.max_stream_connections
+            ))
+        })
+}
+
+/// Waits until every open quote stream has closed, e.g. after shutdown has
+/// been triggered.
+pub async fn wait_for_streams_to_close(app_state: &AppState) {
+    let all = u32::try_from(app_state.max_stream_connections).unwrap_or(u32::MAX);
+    // The semaphore is never closed, so this only returns once all permits are back.
+    let _ = app_state.stream_connections.acquire_many(all).await;
+}
+
+/// Picks successive quotes for a subscription.
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    mode: RotationMode,
+    filters: StreamFilters,
+    last_id: Option<u32>,
+}
+
+impl Rotation {
+    /// Starts a rotation for `subscription`.
+    pub fn new(subscription: &Subscription) -> Self {
+        Rotation {
+            mode: subscription.rotation,
+            filters: subscription.filters.clone(),
+            last_id: None,
+        }
+    }
+
+    /// Picks the next quote among those that are active, published at `now`
+    /// and pass the filters, or `None` if there are none.
+    pub fn next<'a>(&mut self, corpus: &'a Corpus, now: DateTime<Utc>) -> Option<&'a Quote> {
+        let candidates = quote_service::listable_quotes_at(&corpus.quotes, now)
+            .filter(|q| self.filters.matches(q));
+        let picked = match self.mode {
+            RotationMode::Random => {
+                let candidates: Vec<&Quote> = candidates.collect();
+                let mut rng = rand::thread_rng();
+                candidates
+                    .iter()
+                    .filter(|q| candidates.len() == 1 || Some(q.id) != self.last_id)
+                    .choose(&mut rng)
+                    .copied()
+            }
+            RotationMode::Sequential => {
+                let mut candidates: Vec<&Quote> = candidates.collect();
+                candidates.sort_by_key(|q| q.id);
+                candidates
+                    .iter()
+                    .find(|q| self.last_id.is_some_and(|last| q.id > last))
+                    .or_else(|| candidates.first())
+                    .copied()
+            }
+            RotationMode::Daily => {
+                quote_service::get_quote_of_the_day_among(candidates, now.date_naive())
+            }
+        };
+        if let Some(quote) = picked {
+            self.last_id = Some(quote.id);
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quote::fixtures::quote;
+
+    fn corpus() -> Corpus {
+        let quotes = vec![
+            quote(3, "Short", "Ada", None),
+            quote(1, "A longer quote", "Émile", None),
+            quote(2, "Tiny", "Ada", None),
+        ];
+        Corpus::from_load(None, quotes, Utc::now())
+    }
+
+    fn rotation(rotation: RotationMode, filters: StreamFilters) -> Rotation {
+        Rotation::new(&Subscription {
+            rotation,
+            filters,
+            ..Subscription::default()
+        })
+    }
+
+    #[test]
+    fn test_sequential_rotation_wraps_in_id_order() {
+        let corpus = corpus();
+        let mut rotation = rotation(RotationMode::Sequential, StreamFilters::default());
+        let ids: Vec<u32> = (0..4)
+            .map(|_| rotation.next(&corpus, Utc::now()).unwrap().id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_random_rotation_does_not_repeat() {
+        let corpus = corpus();
+        let mut rotation = rotation(RotationMode::Random, StreamFilters::default());
+        let mut last = rotation.next(&corpus, Utc::now()).unwrap().id;
+        for _ in 0..20 {
+            let next = rotation.next(&corpus, Utc::now()).unwrap().id;
+            assert_ne!(next, last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn test_filters_restrict_candidates() {
+        let corpus = corpus();
+        let filters = StreamFilters {
+            author: Some("ADA".to_string()),
+            max_length: Some(4),
+            ..StreamFilters::default()
+        };
+        let mut rotation = rotation(RotationMode::Random, filters);
+        // Only "Tiny" matches, so it repeats.
+        assert_eq!(rotation.next(&corpus, Utc::now()).unwrap().id, 2);
+        assert_eq!(rotation.next(&corpus, Utc::now()).unwrap().id, 2);
+
+        let filters = StreamFilters {
+            author: Some("emile".to_string()),
+            ..StreamFilters::default()
+        };
+        assert_eq!(
+            self::rotation(RotationMode::Daily, filters)
+                .next(&corpus, Utc::now())
+                .unwrap()
+                .id,
+            1
+        );
+    }
+
+    #[test]
+    fn test_rotation_mode_from_str() {
+        assert_eq!(
+            " Daily ".parse::<RotationMode>().unwrap(),
+            RotationMode::Daily
+        );
+        assert!(matches!(
+            "shuffle".parse::<RotationMode>(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_subscription_validation() {
+        assert!(Subscription::default().validate().is_ok());
+        let too_fast = Subscription {
+            interval_secs: 1,
+            ..Subscription::default()
+        };
+        assert!(matches!(too_fast.validate(), Err(AppError::BadRequest(_))));
+        let inverted = Subscription {
+            filters: StreamFilters {
+                min_length: Some(10),
+                max_length: Some(5),
+                ..StreamFilters::default()
+            },
+            ..Subscription::default()
+        };
+        assert!(matches!(inverted.validate(), Err(AppError::BadRequest(_))));
+    }
+}
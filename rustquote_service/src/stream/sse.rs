@@ -0,0 +1,412 @@
+//! # Server-Sent Events Quote Stream
+//!
+//! Serves `GET /api/v1/stream/sse` for clients that cannot use WebSocket.
+//! The subscription is given in the query string and cannot be changed
+//! afterwards. The stream carries these events:
+//!
+//! - `quote`: the next quote of the rotation, once per interval.
+//! - `added`, `changed`, `removed`: a reload of the quotes file changed a
+//!   public quote; see [`super::changes`].
+//! - `resync`: the stream cannot replay what the client missed, so it should
+//!   refetch whatever it keeps.
+//! - `stream-error`: a problem that does not end the stream. It is not named
+//!   `error` so that it does not collide with `EventSource`'s own error event.
+//!
+//! Every event except `stream-error` carries an id: the id of the most recent
+//! change event delivered. A client reconnecting with `Last-Event-ID` first
+//! receives the change events it missed, provided they are still in the
+//! replay buffer. Reloads are checked every [`RELOAD_POLL_INTERVAL`].
+
+use super::changes::QuoteChange;
+use super::{acquire_stream_permit, Rotation, RotationMode, StreamFilters, Subscription};
+use crate::errors::AppError;
+use crate::responses::{ErrorResponse, QuoteResponse};
+use crate::services::quote_store::Corpus;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
+use tokio::time::{interval, interval_at, Instant, MissedTickBehavior};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use utoipa::IntoParams;
+
+/// Header sent by reconnecting `EventSource` clients.
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// How often open streams check the quotes file for reloads.
+pub const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time between keepalive comments on an otherwise idle stream.
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Reconnection delay suggested to clients.
+pub const RETRY_DELAY: Duration = Duration::from_secs(3);
+
+/// A client that does not accept an event within this time is disconnected.
+pub const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events buffered per stream before the sender waits for the client.
+const EVENT_BUFFER: usize = 16;
+
+/// Query parameters accepted by the SSE stream.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SseQueryParams {
+    /// Seconds between quotes.
+    pub interval_secs: Option<String>,
+    /// `random` (default), `sequential` or `daily`.
+    pub rotation: Option<String>,
+    /// Only quotes by this author, matched ignoring case and accents.
+    pub author: Option<String>,
+    /// Only quotes at least this many characters long.
+    pub min_length: Option<String>,
+    /// Only quotes at most this many characters long.
+    pub max_length: Option<String>,
+}
+
+impl SseQueryParams {
+    /// Builds and validates the subscription described by the parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::BadRequest`] if a parameter is malformed or out
+    /// of range.
+    pub fn subscription(&self) -> Result<Subscription, AppError> {
+        let defaults = Subscription::default();
+        let subscription = Subscription {
+            interval_secs: parse_param("interval_secs", &self.interval_secs)?
+                .unwrap_or(defaults.interval_secs),
+            rotation: self
+                .rotation
+                .as_deref()
+                .map(RotationMode::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+            filters: StreamFilters {
+                author: self
+                    .author
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|a| !a.is_empty())
+                    .map(str::to_string),
+                min_length: parse_param("min_length", &self.min_length)?,
+                max_length: parse_param("max_length", &self.max_length)?,
+            },
+        };
+        subscription.validate()?;
+        Ok(subscription)
+    }
+}
+
+fn parse_param<T: FromStr>(name: &str, value: &Option<String>) -> Result<Option<T>, AppError> {
+    value
+        .as_deref()
+        .map(|v| {
+            v.trim().parse::<T>().map_err(|_| {
+                AppError::BadRequest(format!(
+                    "{} must be a non-negative number, got '{}'.",
+                    name, v
+                ))
+            })
+        })
+        .transpose()
+}
+
+#[derive(Serialize)]
+struct QuoteData {
+    /// Counts the quotes sent on this stream, starting at 1.
+    sequence: u64,
+    quote: QuoteResponse,
+}
+
+#[derive(Serialize)]
+struct ChangeData {
+    corpus_version: u64,
+    quote: QuoteResponse,
+}
+
+#[derive(Serialize)]
+struct ResyncData {
+    latest_event_id: u64,
+    message: &'static str,
+}
+
+#[derive(Serialize)]
+struct ErrorData<'a> {
+    error_code: &'static str,
+    message: &'a str,
+}
+
+/// Handles `GET /api/v1/stream/sse`.
+///
+/// # Errors
+///
+/// Returns an [`AppError::BadRequest`] for an invalid subscription, an
+/// [`AppError::ServiceUnavailable`] if the stream connection limit has been
+/// reached or the server is shutting down, and an
+/// [`AppError::QuoteSourcingError`] if the quotes cannot be loaded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stream/sse",
+    tag = "Streams",
+    operation_id = "streamQuotesSse",
+    summary = "Stream quotes as Server-Sent Events",
+    description = "Pushes a rotating quote and reload change events. Supports `Last-Event-ID` resumption.",
+    params(SseQueryParams, ("Last-Event-ID" = Option<String>, Header, description = "Id of the last event received, to resume from")),
+    responses(
+        (status = 200, description = "An event stream", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Invalid subscription", body = ErrorResponse),
+        (status = 503, description = "Too many open streams, or shutting down", body = ErrorResponse),
+        (status = 500, description = "The quotes file could not be loaded", body = ErrorResponse),
+    )
+)]
+pub async fn stream_sse_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SseQueryParams>,
+) -> Result<Response, AppError> {
+    let subscription = params.subscription()?;
+    let corpus = app_state.quote_store.current().await?;
+    let permit = acquire_stream_permit(&app_state)?;
+    app_state.quote_changes.observe(&corpus, Utc::now());
+
+    // Without a Last-Event-ID the stream starts from now. An id that cannot
+    // be parsed is treated like one that was never issued.
+    let cursor = match headers.get(LAST_EVENT_ID_HEADER) {
+        None => app_state.quote_changes.latest_id(),
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|id| id.trim().parse::<u64>().ok())
+            .unwrap_or(u64::MAX),
+    };
+
+    let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+    tokio::spawn(run_stream(sender, app_state, subscription, cursor, permit));
+    let events = ReceiverStream::new(receiver).map(Ok::<_, Infallible>);
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::new().interval(KEEPALIVE_INTERVAL))
+        .into_response())
+}
+
+/// The client went away or stopped accepting events.
+#[derive(Debug)]
+struct Disconnected;
+
+async fn send(sender: &mpsc::Sender<Event>, event: Event) -> Result<(), Disconnected> {
+    match tokio::time::timeout(SEND_TIMEOUT, sender.send(event)).await {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(Disconnected),
+    }
+}
+
+async fn send_json<T: Serialize>(
+    sender: &mpsc::Sender<Event>,
+    event: Event,
+    data: &T,
+) -> Result<(), Disconnected> {
+    let event = event.json_data(data).map_err(|_| Disconnected)?;
+    send(sender, event).await
+}
+
+async fn send_error(sender: &mpsc::Sender<Event>, error: &AppError) -> Result<(), Disconnected> {
+    let data = ErrorData {
+        error_code: error.error_code(),
+        message: error.message(),
+    };
+    send_json(sender, Event::default().event("stream-error"), &data).await
+}
+
+/// Runs one stream until the client leaves, stops accepting events or the
+/// server shuts down. Holding `permit` keeps the connection slot reserved.
+async fn run_stream(
+    sender: mpsc::Sender<Event>,
+    app_state: AppState,
+    subscription: Subscription,
+    cursor: u64,
+    permit: OwnedSemaphorePermit,
+) {
+    let mut stream = StreamState {
+        sender,
+        app_state,
+        filters: subscription.filters.clone(),
+        cursor,
+    };
+    if let Err(error) = stream.serve(&subscription).await {
+        tracing::info!("Quote event stream dropped: {:?}", error);
+    }
+    drop(permit);
+}
+
+struct StreamState {
+    sender: mpsc::Sender<Event>,
+    app_state: AppState,
+    filters: StreamFilters,
+    /// Id of the most recent change event delivered or skipped.
+    cursor: u64,
+}
+
+impl StreamState {
+    async fn serve(&mut self, subscription: &Subscription) -> Result<(), Disconnected> {
+        let mut rotation = Rotation::new(subscription);
+        let mut ticker = interval(subscription.interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut reload_poll =
+            interval_at(Instant::now() + RELOAD_POLL_INTERVAL, RELOAD_POLL_INTERVAL);
+        reload_poll.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut sequence = 0u64;
+        // Only the first of a run of empty picks is reported.
+        let mut reported_empty = false;
+
+        send(&self.sender, Event::default().retry(RETRY_DELAY)).await?;
+        self.catch_up().await?;
+
+        loop {
+            tokio::select! {
+                _ = self.app_state.shutdown.wait() => return Ok(()),
+                _ = self.sender.closed() => return Ok(()),
+                _ = reload_poll.tick() => {
+                    self.poll_reload().await?;
+                }
+                _ = ticker.tick() => {
+                    let Some(corpus) = self.poll_reload().await? else {
+                        continue;
+                    };
+                    match rotation.next(&corpus, Utc::now()) {
+                        Some(quote) => {
+                            reported_empty = false;
+                            sequence += 1;
+                            let data = QuoteData {
+                                sequence,
+                                quote: QuoteResponse::from(quote),
+                            };
+                            let event = Event::default()
+                                .event("quote")
+                                .id(self.cursor.to_string());
+                            send_json(&self.sender, event, &data).await?;
+                        }
+                        None if !reported_empty => {
+                            reported_empty = true;
+                            let error = AppError::NotFound(
+                                "No active quotes match the subscription.".to_string(),
+                            );
+                            send_error(&self.sender, &error).await?;
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Picks up a reload of the quotes file, if any, and sends the change
+    /// events it produced. Returns the current corpus, or `None` if it could
+    /// not be loaded, in which case the client has been told.
+    async fn poll_reload(&mut self) -> Result<Option<Arc<Corpus>>, Disconnected> {
+        match self.app_state.quote_store.current().await {
+            Ok(corpus) => {
+                self.app_state.quote_changes.observe(&corpus, Utc::now());
+                self.catch_up().await?;
+                Ok(Some(corpus))
+            }
+            Err(service_error) => {
+                tracing::error!(
+                    "Failed to load quotes for an event stream: {}",
+                    service_error
+                );
+                send_error(&self.sender, &AppError::from(service_error)).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Sends the change events after the cursor that match the filters, or a
+    /// `resync` event if they are no longer available.
+    async fn catch_up(&mut self) -> Result<(), Disconnected> {
+        let changes = self.app_state.quote_changes.since(self.cursor);
+        let Some(changes) = changes else {
+            self.cursor = self.app_state.quote_changes.latest_id();
+            let data = ResyncData {
+                latest_event_id: self.cursor,
+                message: "Missed events are no longer available; refetch any stored quotes.",
+            };
+            let event = Event::default().event("resync").id(self.cursor.to_string());
+            return send_json(&self.sender, event, &data).await;
+        };
+        for change in changes {
+            self.cursor = change.id;
+            if self.filters.matches(&change.quote) {
+                self.send_change(&change).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_change(&self, change: &QuoteChange) -> Result<(), Disconnected> {
+        let data = ChangeData {
+            corpus_version: change.corpus_version,
+            quote: QuoteResponse::from(&change.quote),
+        };
+        let event = Event::default()
+            .event(change.kind.event_name())
+            .id(change.id.to_string());
+        send_json(&self.sender, event, &data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_params_build_subscription() {
+        let params = SseQueryParams {
+            interval_secs: Some("10".to_string()),
+            rotation: Some("sequential".to_string()),
+            author: Some(" Ada ".to_string()),
+            max_length: Some("80".to_string()),
+            ..SseQueryParams::default()
+        };
+        let subscription = params.subscription().unwrap();
+        assert_eq!(subscription.interval_secs, 10);
+        assert_eq!(subscription.rotation, RotationMode::Sequential);
+        assert_eq!(subscription.filters.author.as_deref(), Some("Ada"));
+        assert_eq!(subscription.filters.max_length, Some(80));
+        assert_eq!(
+            SseQueryParams::default().subscription().unwrap(),
+            Subscription::default()
+        );
+
+        for params in [
+            SseQueryParams {
+                interval_secs: Some("2".to_string()),
+                ..SseQueryParams::default()
+            },
+            SseQueryParams {
+                min_length: Some("short".to_string()),
+                ..SseQueryParams::default()
+            },
+            SseQueryParams {
+                rotation: Some("shuffle".to_string()),
+                ..SseQueryParams::default()
+            },
+        ] {
+            assert!(matches!(
+                params.subscription(),
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+}
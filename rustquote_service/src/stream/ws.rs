@@ -0,0 +1,269 @@
+//! # WebSocket Quote Stream
+//!
+//! Serves `GET /api/v1/stream/ws`. After the upgrade the server sends a
+//! `welcome` frame; the client then sends a `subscribe` frame and receives a
+//! `quote` frame every interval until it unsubscribes, subscribes again with
+//! new settings, or disconnects. All frames are JSON text frames with a
+//! `type` field.
+//!
+//! The server pings every [`PING_INTERVAL`] and drops connections that have
+//! sent nothing for [`IDLE_TIMEOUT`]. Quotes are sent one at a time: a slow
+//! client skips ticks instead of building up a queue, and one that does not
+//! accept a frame within [`SEND_TIMEOUT`] is disconnected. On shutdown every
+//! stream is closed with status 1001 (going away).
+
+use super::{
+    acquire_stream_permit, Rotation, Subscription, DEFAULT_INTERVAL_SECS, MAX_INTERVAL_SECS,
+    MIN_INTERVAL_SECS,
+};
+use crate::errors::AppError;
+use crate::responses::{ErrorResponse, QuoteResponse};
+use crate::AppState;
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::{interval, interval_at, Instant, Interval, MissedTickBehavior};
+
+/// Time between keepalive pings.
+pub const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A connection that has sent nothing, not even a pong, for this long is dropped.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(75);
+
+/// A client that does not accept a frame within this time is disconnected.
+pub const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest message a client may send, in bytes.
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 4 * 1024;
+
+/// Frames sent by clients.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Starts pushing quotes, replacing any previous subscription.
+    Subscribe(Subscription),
+    /// Stops pushing quotes but keeps the connection open.
+    Unsubscribe,
+}
+
+/// Frames sent by the server.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    /// Sent once after the upgrade, with the accepted interval range.
+    Welcome {
+        min_interval_secs: u64,
+        max_interval_secs: u64,
+        default_interval_secs: u64,
+    },
+    /// Confirms a subscription, with defaults filled in.
+    Subscribed(&'a Subscription),
+    Unsubscribed,
+    Quote {
+        /// Counts the quotes sent on this connection, starting at 1.
+        sequence: u64,
+        quote: QuoteResponse,
+    },
+    /// Reports a problem without closing the connection.
+    Error {
+        error_code: &'static str,
+        message: String,
+    },
+}
+
+impl From<&AppError> for ServerMessage<'_> {
+    fn from(error: &AppError) -> Self {
+        ServerMessage::Error {
+            error_code: error.error_code(),
+            message: error.message().to_string(),
+        }
+    }
+}
+
+/// Handles `GET /api/v1/stream/ws`.
+///
+/// # Errors
+///
+/// Returns an [`AppError::ServiceUnavailable`] if the stream connection limit
+/// has been reached or the server is shutting down.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stream/ws",
+    tag = "Streams",
+    operation_id = "streamQuotesWs",
+    summary = "Stream quotes over WebSocket",
+    description = "Upgrades to a WebSocket that pushes quotes on a subscription; see the API documentation for the message format.",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 503, description = "Too many open streams, or shutting down", body = ErrorResponse),
+    )
+)]
+pub async fn stream_ws_handler(
+    State(app_state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let permit = acquire_stream_permit(&app_state)?;
+    Ok(ws
+        .max_message_size(MAX_CLIENT_MESSAGE_BYTES)
+        .max_frame_size(MAX_CLIENT_MESSAGE_BYTES)
+        .on_upgrade(move |socket| async move {
+            run_stream(socket, app_state).await;
+            drop(permit);
+        }))
+}
+
+/// Why sending a frame failed; either way the connection is abandoned.
+#[derive(Debug)]
+enum SendError {
+    Closed,
+    TimedOut,
+}
+
+async fn send(socket: &mut WebSocket, message: Message) -> Result<(), SendError> {
+    match tokio::time::timeout(SEND_TIMEOUT, socket.send(message)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(_)) => Err(SendError::Closed),
+        Err(_) => Err(SendError::TimedOut),
+    }
+}
+
+async fn send_json(socket: &mut WebSocket, message: &ServerMessage<'_>) -> Result<(), SendError> {
+    let text = serde_json::to_string(message).map_err(|_| SendError::Closed)?;
+    send(socket, Message::Text(text)).await
+}
+
+/// Completes at the next tick of `ticker`, or never without a subscription.
+async fn next_tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Runs one upgraded connection until the client leaves, stops responding or
+/// the server shuts down.
+async fn run_stream(mut socket: WebSocket, app_state: AppState) {
+    if let Err(error) = serve_stream(&mut socket, &app_state).await {
+        tracing::info!("Quote stream dropped: {:?}", error);
+    }
+}
+
+async fn serve_stream(socket: &mut WebSocket, app_state: &AppState) -> Result<(), SendError> {
+    let mut rotation: Option<Rotation> = None;
+    let mut ticker: Option<Interval> = None;
+    let mut sequence = 0u64;
+    // Only the first of a run of empty picks is reported.
+    let mut reported_empty = false;
+    let mut keepalive = interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+    let mut last_seen = Instant::now();
+
+    send_json(
+        socket,
+        &ServerMessage::Welcome {
+            min_interval_secs: MIN_INTERVAL_SECS,
+            max_interval_secs: MAX_INTERVAL_SECS,
+            default_interval_secs: DEFAULT_INTERVAL_SECS,
+        },
+    )
+    .await?;
+
+    loop {
+        tokio::select! {
+            _ = app_state.shutdown.wait() => {
+                let close = Message::Close(Some(CloseFrame {
+                    code: close_code::AWAY,
+                    reason: "Server is shutting down.".into(),
+                }));
+                return send(socket, close).await;
+            }
+            incoming = socket.recv() => {
+                let Some(Ok(message)) = incoming else {
+                    return Ok(());
+                };
+                last_seen = Instant::now();
+                match message {
+                    Message::Text(text) => match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(ClientMessage::Subscribe(subscription)) => {
+                            if let Err(error) = subscription.validate() {
+                                send_json(socket, &(&error).into()).await?;
+                                continue;
+                            }
+                            let mut new_ticker = interval(subscription.interval());
+                            new_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                            ticker = Some(new_ticker);
+                            rotation = Some(Rotation::new(&subscription));
+                            reported_empty = false;
+                            send_json(socket, &ServerMessage::Subscribed(&subscription)).await?;
+                        }
+                        Ok(ClientMessage::Unsubscribe) => {
+                            ticker = None;
+                            rotation = None;
+                            send_json(socket, &ServerMessage::Unsubscribed).await?;
+                        }
+                        Err(e) => {
+                            let error = AppError::BadRequest(format!("Invalid message: {}", e));
+                            send_json(socket, &(&error).into()).await?;
+                        }
+                    },
+                    Message::Binary(_) => {
+                        let error = AppError::BadRequest(
+                            "Binary frames are not supported; send JSON text frames.".to_string(),
+                        );
+                        send_json(socket, &(&error).into()).await?;
+                    }
+                    // Pings are answered automatically; pongs only refresh `last_seen`.
+                    Message::Ping(_) | Message::Pong(_) => {}
+                    Message::Close(_) => return Ok(()),
+                }
+            }
+            _ = next_tick(&mut ticker) => {
+                let Some(rotation) = rotation.as_mut() else {
+                    continue;
+                };
+                let corpus = match app_state.quote_store.current().await {
+                    Ok(corpus) => corpus,
+                    Err(service_error) => {
+                        tracing::error!("Failed to load quotes for a stream: {}", service_error);
+                        send_json(socket, &(&AppError::from(service_error)).into()).await?;
+                        continue;
+                    }
+                };
+                match rotation.next(&corpus, Utc::now()) {
+                    Some(quote) => {
+                        reported_empty = false;
+                        sequence += 1;
+                        let message = ServerMessage::Quote {
+                            sequence,
+                            quote: QuoteResponse::from(quote),
+                        };
+                        send_json(socket, &message).await?;
+                    }
+                    None if !reported_empty => {
+                        reported_empty = true;
+                        let error = AppError::NotFound(
+                            "No active quotes match the subscription.".to_string(),
+                        );
+                        send_json(socket, &(&error).into()).await?;
+                    }
+                    None => {}
+                }
+            }
+            _ = keepalive.tick() => {
+                if last_seen.elapsed() > IDLE_TIMEOUT {
+                    tracing::info!("Closing an unresponsive quote stream.");
+                    return Ok(());
+                }
+                send(socket, Message::Ping(Vec::new())).await?;
+            }
+        }
+    }
+}
@@ -5,6 +5,9 @@
 //!
 //! Currently, it contains placeholder functionality.
 
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
 /// Placeholder function for demonstrating a utility.
 ///
 /// This function will be replaced with actual utility logic
@@ -12,3 +15,187 @@
 pub fn placeholder_util_function() {
     println!("Placeholder util function from utils module");
 }
+
+/// Removes diacritics by decomposing `text` and dropping combining marks,
+/// so that "Émile" becomes "Emile".
+pub fn fold_accents(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+/// Normalizes text for case- and accent-insensitive matching, so that
+/// " Émile ZOLA" and "emile zola" compare equal.
+pub fn match_key(text: &str) -> String {
+    fold_accents(text.trim()).to_lowercase()
+}
+
+/// Common English words that carry little meaning on their own, sorted.
+pub const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "are", "as", "at", "be", "but", "by", "do", "for", "from", "has",
+    "have", "he", "her", "his", "i", "if", "in", "is", "it", "its", "me", "my", "no", "not", "of",
+    "on", "or", "our", "she", "so", "that", "the", "their", "them", "they", "this", "to", "was",
+    "we", "were", "what", "when", "which", "who", "will", "with", "you", "your",
+];
+
+/// Returns `true` if `word`, as produced by [`words`], is a [`STOPWORDS`] entry.
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.binary_search(&word).is_ok()
+}
+
+/// Splits text into words for indexing, normalized like [`match_key`].
+/// Anything that is not a letter or digit separates words.
+pub fn words(text: &str) -> Vec<String> {
+    match_key(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Escapes text for inclusion in XML, HTML or SVG element content and
+/// attribute values.
+///
+/// Characters that XML 1.0 does not allow at all (most C0 control characters
+/// and non-characters) are dropped, since they cannot be escaped either.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Derives a stable, name-based UUID string from `name`.
+///
+/// The UUID is built from the SHA-256 digest of `name` and marked as an
+/// RFC 9562 version 8 (custom) UUID, so the same name always yields the same
+/// identifier. Used for feed entry and calendar event IDs.
+pub fn name_uuid(name: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(name.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x80;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Converts text into a lowercase, hyphen-separated, URL-safe slug.
+///
+/// Accents are folded first; then only ASCII letters and digits are kept and
+/// every other run of characters becomes a single hyphen. The result is cut at
+/// a hyphen boundary so it is at most `max_len` bytes long.
+pub fn slugify(text: &str, max_len: usize) -> String {
+    let folded = fold_accents(text);
+    let mut slug = String::with_capacity(folded.len().min(max_len));
+    for word in folded
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let extra = if slug.is_empty() { 0 } else { 1 };
+        if slug.len() + extra + word.len() > max_len {
+            if slug.is_empty() {
+                slug.push_str(&word[..max_len].to_ascii_lowercase());
+            }
+            break;
+        }
+        if extra == 1 {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_accents() {
+        assert_eq!(
+            fold_accents("Émile Zola, façade, naïve"),
+            "Emile Zola, facade, naive"
+        );
+    }
+
+    #[test]
+    fn test_stopwords_are_sorted() {
+        assert!(STOPWORDS.windows(2).all(|w| w[0] < w[1]));
+        assert!(is_stopword("the"));
+        assert!(!is_stopword("theory"));
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("Don't panic — Ça va, 42!"),
+            ["don", "t", "panic", "ca", "va", "42"]
+        );
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"<b>"Tom" & 'Jerry'</b>"#),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &apos;Jerry&apos;&lt;/b&gt;"
+        );
+        assert_eq!(escape_xml("plain"), "plain");
+        assert_eq!(escape_xml("bell\u{7}\tend\u{ffff}"), "bell\tend");
+    }
+
+    #[test]
+    fn test_name_uuid_is_stable_and_well_formed() {
+        let uuid = name_uuid("rustquote:daily:2025-06-01");
+        assert_eq!(uuid, name_uuid("rustquote:daily:2025-06-01"));
+        assert_ne!(uuid, name_uuid("rustquote:daily:2025-06-02"));
+        assert_eq!(uuid.len(), 36);
+        let groups: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12]
+        );
+        assert!(groups[2].starts_with('8'));
+        assert!(matches!(
+            groups[3].chars().next(),
+            Some('8' | '9' | 'a' | 'b')
+        ));
+    }
+
+    #[test]
+    fn test_slugify_basic() {
+        assert_eq!(
+            slugify("Stay hungry, stay foolish.", 80),
+            "stay-hungry-stay-foolish"
+        );
+    }
+
+    #[test]
+    fn test_slugify_truncates_at_word_boundary() {
+        assert_eq!(slugify("one two three", 9), "one-two");
+        assert_eq!(slugify("abcdefghij", 4), "abcd");
+    }
+
+    #[test]
+    fn test_slugify_drops_non_ascii_and_punctuation() {
+        assert_eq!(slugify("  --Émile  Zola!! ", 80), "emile-zola");
+        assert_eq!(slugify("Lao Tzu 老子", 80), "lao-tzu");
+        assert_eq!(slugify("!!!", 80), "");
+    }
+}
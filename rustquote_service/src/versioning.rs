@@ -0,0 +1,235 @@
+//! # API Versioning
+//!
+//! REST routes live under a version prefix, `/api/v1` or `/api/v2`, and that
+//! prefix always decides the version served. The unversioned aliases
+//! `/api/quote` and `/api/quote/:id` pick the version from the
+//! [`API_VERSION_HEADER`] request header instead, defaulting to
+//! [`ApiVersion::LATEST`], and are forwarded to the versioned route. Every
+//! versioned response says which version produced it in the same header.
+//!
+//! v1 routes that have a v2 successor are deprecated: [`deprecated_v1`] marks
+//! their responses with `Deprecation`, `Sunset` and a `successor-version`
+//! link. Their bodies do not change.
+
+use crate::errors::AppError;
+use axum::{
+    extract::Request,
+    http::{
+        header::{LINK, VARY},
+        HeaderMap, HeaderName, HeaderValue, Uri,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use tower::ServiceExt;
+
+/// Request and response header carrying the API version number.
+pub const API_VERSION_HEADER: &str = "api-version";
+
+/// When the deprecated v1 routes were deprecated.
+pub const V1_DEPRECATED_AT: &str = "2026-10-19T00:00:00Z";
+
+/// After this instant the deprecated v1 routes may be removed.
+pub const V1_SUNSET_AT: &str = "2027-06-30T00:00:00Z";
+
+/// Routes that [`deprecated_v1`] is applied to, in OpenAPI path syntax.
+pub const DEPRECATED_V1_PATHS: &[&str] = &["/api/v1/quote", "/api/v1/quote/{id}"];
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// A REST API version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// The version served by unversioned aliases when none is requested.
+    pub const LATEST: ApiVersion = ApiVersion::V2;
+
+    /// The version number, as used in paths and headers.
+    pub fn number(self) -> u8 {
+        match self {
+            ApiVersion::V1 => 1,
+            ApiVersion::V2 => 2,
+        }
+    }
+
+    /// The path prefix for this version, e.g. `/api/v2`.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+            ApiVersion::V2 => "/api/v2",
+        }
+    }
+
+    /// Parses an [`API_VERSION_HEADER`] value: `1`, `2`, `v1` or `v2`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        let number = name
+            .strip_prefix('v')
+            .or_else(|| name.strip_prefix('V'))
+            .unwrap_or(name);
+        match number {
+            "1" => Some(ApiVersion::V1),
+            "2" => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+
+    /// Picks the version requested by an [`API_VERSION_HEADER`], or
+    /// [`ApiVersion::LATEST`] without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AppError::BadRequest`] for an unknown version.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, AppError> {
+        let Some(value) = headers.get(API_VERSION_HEADER) else {
+            return Ok(ApiVersion::LATEST);
+        };
+        value
+            .to_str()
+            .ok()
+            .and_then(ApiVersion::from_name)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Unsupported API version '{}'; expected 1 or 2.",
+                    String::from_utf8_lossy(value.as_bytes())
+                ))
+            })
+    }
+
+    /// The version whose prefix starts `path`, if any.
+    fn from_path(path: &str) -> Option<Self> {
+        [ApiVersion::V1, ApiVersion::V2].into_iter().find(|v| {
+            path.strip_prefix(v.prefix())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// Middleware that adds the [`API_VERSION_HEADER`] to responses from
+/// versioned routes.
+pub async fn api_version_header(request: Request, next: Next) -> Response {
+    let version = ApiVersion::from_path(request.uri().path());
+    let mut response = next.run(request).await;
+    if let Some(version) = version {
+        response.headers_mut().insert(
+            API_VERSION_HEADER,
+            HeaderValue::from(u16::from(version.number())),
+        );
+    }
+    response
+}
+
+/// Middleware for deprecated v1 routes: adds `Deprecation` and `Sunset`
+/// headers, and a `Link` to the same resource under `/api/v2`.
+pub async fn deprecated_v1(request: Request, next: Next) -> Response {
+    let successor = successor_path(request.uri());
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, structured_date(V1_DEPRECATED_AT));
+    if let Ok(sunset) = HeaderValue::from_str(&crate::caching::http_date(parse(V1_SUNSET_AT))) {
+        headers.insert(SUNSET, sunset);
+    }
+    if let Some(link) = successor.and_then(|path| {
+        HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", path)).ok()
+    }) {
+        headers.append(LINK, link);
+    }
+    response
+}
+
+fn parse(at: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(at)
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+/// Formats an instant as a structured-field date, e.g. `@1792368000`.
+fn structured_date(at: &str) -> HeaderValue {
+    HeaderValue::try_from(format!("@{}", parse(at).timestamp()))
+        .unwrap_or_else(|_| HeaderValue::from_static("?1"))
+}
+
+/// The `/api/v2` path, with the query, for a `/api/v1` request URI.
+fn successor_path(uri: &Uri) -> Option<String> {
+    let rest = uri.path().strip_prefix(ApiVersion::V1.prefix())?;
+    let query = uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
+    Some(format!("{}{}{}", ApiVersion::V2.prefix(), rest, query))
+}
+
+/// Serves an unversioned `/api/...` request by forwarding it to the same path
+/// under the version chosen by [`ApiVersion::from_headers`].
+///
+/// Responses vary on the version header, so caches keep them apart.
+pub async fn forward_unversioned(router: Router, request: Request) -> Response {
+    let mut response = match versioned_request(request) {
+        Ok(request) => match router.oneshot(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        },
+        Err(error) => error.into_response(),
+    };
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static(API_VERSION_HEADER));
+    response
+}
+
+/// Rewrites an unversioned request to the path of the requested version.
+fn versioned_request(mut request: Request) -> Result<Request, AppError> {
+    let version = ApiVersion::from_headers(request.headers())?;
+    let uri = request.uri();
+    let rest = uri.path().strip_prefix("/api").unwrap_or(uri.path());
+    let query = uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
+    *request.uri_mut() = format!("{}{}{}", version.prefix(), rest, query)
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid request path.".to_string()))?;
+    // Drop the alias route's matched path and parameters, which the
+    // versioned route would otherwise see as its own.
+    request.extensions_mut().clear();
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_from_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            ApiVersion::from_headers(&headers).unwrap(),
+            ApiVersion::LATEST
+        );
+        headers.insert(API_VERSION_HEADER, HeaderValue::from_static("v1"));
+        assert_eq!(ApiVersion::from_headers(&headers).unwrap(), ApiVersion::V1);
+        headers.insert(API_VERSION_HEADER, HeaderValue::from_static(" 2 "));
+        assert_eq!(ApiVersion::from_headers(&headers).unwrap(), ApiVersion::V2);
+        headers.insert(API_VERSION_HEADER, HeaderValue::from_static("3"));
+        assert!(matches!(
+            ApiVersion::from_headers(&headers),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_version_from_path_and_successor() {
+        assert_eq!(ApiVersion::from_path("/api/v1/quote"), Some(ApiVersion::V1));
+        assert_eq!(ApiVersion::from_path("/api/v2"), Some(ApiVersion::V2));
+        assert_eq!(ApiVersion::from_path("/api/v10/quote"), None);
+        assert_eq!(ApiVersion::from_path("/api/health"), None);
+
+        let uri: Uri = "/api/v1/quote/7?format=text".parse().unwrap();
+        assert_eq!(
+            successor_path(&uri).as_deref(),
+            Some("/api/v2/quote/7?format=text")
+        );
+        assert_eq!(structured_date("1970-01-02T00:00:00Z"), "@86400");
+    }
+}
@@ -1,12 +1,43 @@
+use rustquote_service::feeds::ical;
+use rustquote_service::services::suggest_index;
 use std::env;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use thiserror::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AppConfig {
     pub server_address: SocketAddr,
+    pub grpc_address: SocketAddr,
     pub quotes_file_path: PathBuf,
+    pub admin_token: Option<String>,
+    pub public_base_url: Option<String>,
+    pub calendar_days: u32,
+    pub graphiql_enabled: bool,
+    pub max_stream_connections: usize,
+    pub suggest_max_results: usize,
+    pub max_duplicate_rate: Option<f64>,
+}
+
+// The configuration is logged at startup, so keep the admin token out of it.
+impl std::fmt::Debug for AppConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppConfig")
+            .field("server_address", &self.server_address)
+            .field("grpc_address", &self.grpc_address)
+            .field("quotes_file_path", &self.quotes_file_path)
+            .field(
+                "admin_token",
+                &self.admin_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("public_base_url", &self.public_base_url)
+            .field("calendar_days", &self.calendar_days)
+            .field("graphiql_enabled", &self.graphiql_enabled)
+            .field("max_stream_connections", &self.max_stream_connections)
+            .field("suggest_max_results", &self.suggest_max_results)
+            .field("max_duplicate_rate", &self.max_duplicate_rate)
+            .finish()
+    }
 }
 
 #[derive(Error, Debug)]
@@ -18,6 +49,16 @@ pub enum ConfigError {
     },
     #[error("Invalid server address: {0}")]
     InvalidServerAddress(String),
+    #[error("Invalid calendar horizon: {0}")]
+    InvalidCalendarDays(String),
+    #[error("Invalid boolean setting: {0}")]
+    InvalidFlag(String),
+    #[error("Invalid stream connection limit: {0}")]
+    InvalidStreamConnections(String),
+    #[error("Invalid suggestion limit: {0}")]
+    InvalidSuggestMaxResults(String),
+    #[error("Invalid duplicate rate: {0}")]
+    InvalidDuplicateRate(String),
 }
 
 impl From<env::VarError> for ConfigError {
@@ -33,7 +74,13 @@ impl From<env::VarError> for ConfigError {
 }
 
 const DEFAULT_SERVER_ADDRESS: &str = "0.0.0.0:8080";
+const DEFAULT_GRPC_ADDRESS: &str = "0.0.0.0:50051";
 const DEFAULT_QUOTES_FILE_PATH: &str = "data/quotes.json";
+const DEFAULT_CALENDAR_DAYS: u32 = ical::DEFAULT_CALENDAR_DAYS;
+const DEFAULT_MAX_STREAM_CONNECTIONS: usize = rustquote_service::DEFAULT_MAX_STREAM_CONNECTIONS;
+// Keeps the limit within what the stream semaphore can hand out.
+const MAX_STREAM_CONNECTIONS_LIMIT: usize = 100_000;
+const DEFAULT_SUGGEST_MAX_RESULTS: usize = suggest_index::DEFAULT_SUGGEST_MAX_RESULTS;
 
 pub fn load_config() -> Result<AppConfig, ConfigError> {
     // Attempt to load .env file. It's okay if it's not found (e.g., in production).
@@ -49,6 +96,17 @@ pub fn load_config() -> Result<AppConfig, ConfigError> {
         ))
     })?;
 
+    // The gRPC API is served by the same process on its own port.
+    let grpc_address_str =
+        env::var("RUSTQUOTE_GRPC_ADDRESS").unwrap_or_else(|_| DEFAULT_GRPC_ADDRESS.to_string());
+
+    let grpc_address = grpc_address_str.parse::<SocketAddr>().map_err(|e| {
+        ConfigError::InvalidServerAddress(format!(
+            "Failed to parse gRPC address '{}': {}",
+            grpc_address_str, e
+        ))
+    })?;
+
     let quotes_file_path_str = env::var("RUSTQUOTE_QUOTES_FILE_PATH")
         .unwrap_or_else(|_| DEFAULT_QUOTES_FILE_PATH.to_string());
 
@@ -58,12 +116,114 @@ pub fn load_config() -> Result<AppConfig, ConfigError> {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(quotes_file_path_str)
     };
 
+    // Admin-only features (such as previewing scheduled quotes) stay disabled without a token.
+    let admin_token = env::var("RUSTQUOTE_ADMIN_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+
+    // Absolute links in feeds use this base; without it they follow the request's Host header.
+    let public_base_url = env::var("RUSTQUOTE_PUBLIC_BASE_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+
+    let calendar_days = match env::var("RUSTQUOTE_CALENDAR_DAYS") {
+        Ok(days_str) => days_str
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|days| (1..=ical::MAX_CALENDAR_DAYS).contains(days))
+            .ok_or_else(|| {
+                ConfigError::InvalidCalendarDays(format!(
+                    "'{}' is not a number of days between 1 and {}",
+                    days_str,
+                    ical::MAX_CALENDAR_DAYS
+                ))
+            })?,
+        Err(_) => DEFAULT_CALENDAR_DAYS,
+    };
+
+    // The GraphiQL IDE is a development aid, so it stays off unless asked for.
+    let graphiql_enabled = match env::var("RUSTQUOTE_GRAPHIQL") {
+        Ok(flag) => parse_flag("RUSTQUOTE_GRAPHIQL", &flag)?,
+        Err(_) => false,
+    };
+
+    let max_stream_connections = match env::var("RUSTQUOTE_MAX_STREAM_CONNECTIONS") {
+        Ok(limit_str) => limit_str
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| (1..=MAX_STREAM_CONNECTIONS_LIMIT).contains(limit))
+            .ok_or_else(|| {
+                ConfigError::InvalidStreamConnections(format!(
+                    "'{}' is not a number between 1 and {}",
+                    limit_str, MAX_STREAM_CONNECTIONS_LIMIT
+                ))
+            })?,
+        Err(_) => DEFAULT_MAX_STREAM_CONNECTIONS,
+    };
+
+    let suggest_max_results = match env::var("RUSTQUOTE_SUGGEST_MAX_RESULTS") {
+        Ok(limit_str) => limit_str
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| (1..=suggest_index::SUGGEST_MAX_RESULTS_LIMIT).contains(limit))
+            .ok_or_else(|| {
+                ConfigError::InvalidSuggestMaxResults(format!(
+                    "'{}' is not a number between 1 and {}",
+                    limit_str,
+                    suggest_index::SUGGEST_MAX_RESULTS_LIMIT
+                ))
+            })?,
+        Err(_) => DEFAULT_SUGGEST_MAX_RESULTS,
+    };
+
+    // Without a maximum, data files load however many near duplicates they hold.
+    let max_duplicate_rate = match env::var("RUSTQUOTE_MAX_DUPLICATE_RATE") {
+        Ok(rate_str) if !rate_str.trim().is_empty() => Some(
+            rate_str
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    ConfigError::InvalidDuplicateRate(format!(
+                        "'{}' is not a fraction between 0 and 1",
+                        rate_str
+                    ))
+                })?,
+        ),
+        _ => None,
+    };
+
     Ok(AppConfig {
         server_address,
+        grpc_address,
         quotes_file_path,
+        admin_token,
+        public_base_url,
+        calendar_days,
+        graphiql_enabled,
+        max_stream_connections,
+        suggest_max_results,
+        max_duplicate_rate,
     })
 }
 
+/// Parses a boolean setting such as `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`.
+fn parse_flag(variable: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" | "" => Ok(false),
+        _ => Err(ConfigError::InvalidFlag(format!(
+            "{} must be true or false, got '{}'",
+            variable, value
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,6 +292,142 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_load_config_admin_token() {
+        env::remove_var("RUSTQUOTE_ADMIN_TOKEN");
+        assert_eq!(load_config().unwrap().admin_token, None);
+
+        let _guard_token = EnvVarGuard::new("RUSTQUOTE_ADMIN_TOKEN", "s3cret");
+        let config = load_config().unwrap();
+        assert_eq!(config.admin_token, Some("s3cret".to_string()));
+        assert!(!format!("{:?}", config).contains("s3cret"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_public_base_url() {
+        env::remove_var("RUSTQUOTE_PUBLIC_BASE_URL");
+        assert_eq!(load_config().unwrap().public_base_url, None);
+
+        let _guard_url = EnvVarGuard::new("RUSTQUOTE_PUBLIC_BASE_URL", "https://quotes.example/");
+        assert_eq!(
+            load_config().unwrap().public_base_url,
+            Some("https://quotes.example".to_string())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_calendar_days() {
+        env::remove_var("RUSTQUOTE_CALENDAR_DAYS");
+        assert_eq!(load_config().unwrap().calendar_days, DEFAULT_CALENDAR_DAYS);
+
+        let guard_days = EnvVarGuard::new("RUSTQUOTE_CALENDAR_DAYS", "90");
+        assert_eq!(load_config().unwrap().calendar_days, 90);
+        drop(guard_days);
+
+        let _guard_days = EnvVarGuard::new("RUSTQUOTE_CALENDAR_DAYS", "0");
+        assert!(matches!(
+            load_config(),
+            Err(ConfigError::InvalidCalendarDays(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_graphiql_flag() {
+        env::remove_var("RUSTQUOTE_GRAPHIQL");
+        assert!(!load_config().unwrap().graphiql_enabled);
+
+        let guard_flag = EnvVarGuard::new("RUSTQUOTE_GRAPHIQL", "true");
+        assert!(load_config().unwrap().graphiql_enabled);
+        drop(guard_flag);
+
+        let _guard_flag = EnvVarGuard::new("RUSTQUOTE_GRAPHIQL", "maybe");
+        assert!(matches!(load_config(), Err(ConfigError::InvalidFlag(_))));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_grpc_address() {
+        env::remove_var("RUSTQUOTE_GRPC_ADDRESS");
+        assert_eq!(
+            load_config().unwrap().grpc_address,
+            DEFAULT_GRPC_ADDRESS.parse::<SocketAddr>().unwrap()
+        );
+
+        let guard_addr = EnvVarGuard::new("RUSTQUOTE_GRPC_ADDRESS", "127.0.0.1:9090");
+        assert_eq!(
+            load_config().unwrap().grpc_address,
+            "127.0.0.1:9090".parse::<SocketAddr>().unwrap()
+        );
+        drop(guard_addr);
+
+        let _guard_addr = EnvVarGuard::new("RUSTQUOTE_GRPC_ADDRESS", "not-an-address");
+        assert!(matches!(
+            load_config(),
+            Err(ConfigError::InvalidServerAddress(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_max_stream_connections() {
+        env::remove_var("RUSTQUOTE_MAX_STREAM_CONNECTIONS");
+        assert_eq!(
+            load_config().unwrap().max_stream_connections,
+            DEFAULT_MAX_STREAM_CONNECTIONS
+        );
+
+        let guard_limit = EnvVarGuard::new("RUSTQUOTE_MAX_STREAM_CONNECTIONS", "500");
+        assert_eq!(load_config().unwrap().max_stream_connections, 500);
+        drop(guard_limit);
+
+        let _guard_limit = EnvVarGuard::new("RUSTQUOTE_MAX_STREAM_CONNECTIONS", "0");
+        assert!(matches!(
+            load_config(),
+            Err(ConfigError::InvalidStreamConnections(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_suggest_max_results() {
+        env::remove_var("RUSTQUOTE_SUGGEST_MAX_RESULTS");
+        assert_eq!(
+            load_config().unwrap().suggest_max_results,
+            DEFAULT_SUGGEST_MAX_RESULTS
+        );
+
+        let guard_limit = EnvVarGuard::new("RUSTQUOTE_SUGGEST_MAX_RESULTS", "25");
+        assert_eq!(load_config().unwrap().suggest_max_results, 25);
+        drop(guard_limit);
+
+        let _guard_limit = EnvVarGuard::new("RUSTQUOTE_SUGGEST_MAX_RESULTS", "101");
+        assert!(matches!(
+            load_config(),
+            Err(ConfigError::InvalidSuggestMaxResults(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_max_duplicate_rate() {
+        env::remove_var("RUSTQUOTE_MAX_DUPLICATE_RATE");
+        assert_eq!(load_config().unwrap().max_duplicate_rate, None);
+
+        let guard_rate = EnvVarGuard::new("RUSTQUOTE_MAX_DUPLICATE_RATE", "0.05");
+        assert_eq!(load_config().unwrap().max_duplicate_rate, Some(0.05));
+        drop(guard_rate);
+
+        let _guard_rate = EnvVarGuard::new("RUSTQUOTE_MAX_DUPLICATE_RATE", "5%");
+        assert!(matches!(
+            load_config(),
+            Err(ConfigError::InvalidDuplicateRate(_))
+        ));
+    }
+
     #[test]
     #[serial]
     fn test_load_config_invalid_address_format() {
@@ -4,7 +4,8 @@
 //! It initializes and runs the server defined in the `rustquote_service` library.
 
 // The `rustquote_service` crate name comes from Cargo.toml's [package] name
-use rustquote_service::run_server;
+use rustquote_service::{grpc, run_server, stream, AppState};
+use std::time::Duration;
 // Assuming config_manager.rs is in src/ alongside main.rs
 mod config_manager;
 use config_manager::load_config;
@@ -33,9 +34,75 @@ async fn main() {
         }
     };
 
-    if let Err(e) = run_server(app_config.server_address, app_config.quotes_file_path).await {
+    let app_state = AppState::new(app_config.quotes_file_path)
+        .with_admin_token(app_config.admin_token)
+        .with_public_base_url(app_config.public_base_url)
+        .with_calendar_days(app_config.calendar_days)
+        .with_graphiql(app_config.graphiql_enabled)
+        .with_max_stream_connections(app_config.max_stream_connections)
+        .with_suggest_max_results(app_config.suggest_max_results)
+        .with_max_duplicate_rate(app_config.max_duplicate_rate);
+
+    let shutdown = app_state.shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("Binary: Shutdown signal received, closing connections...");
+        shutdown.trigger();
+    });
+
+    // Serve HTTP and gRPC from the same process; if either fails, stop.
+    let servers = async {
+        tokio::try_join!(
+            run_server(app_config.server_address, app_state.clone()),
+            grpc::run_grpc_server(app_config.grpc_address, app_state.clone()),
+        )
+    };
+    if let Err(e) = servers.await {
         tracing::error!("Binary: Server error: {}", e);
         std::process::exit(1);
     }
+    // Give open quote streams a moment to send their close frames.
+    if tokio::time::timeout(
+        STREAM_CLOSE_GRACE,
+        stream::wait_for_streams_to_close(&app_state),
+    )
+    .await
+    .is_err()
+    {
+        tracing::warn!("Binary: Some quote streams did not close in time.");
+    }
     tracing::info!("Binary: Server shut down gracefully.");
 }
+
+/// How long to wait for quote streams to close after shutdown.
+const STREAM_CLOSE_GRACE: Duration = Duration::from_secs(5);
+
+/// Completes on Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Binary: Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Binary: Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -6,20 +6,29 @@ use rustquote_service::app; // Use the app router from the library
 use rustquote_service::models::Quote;
 use rustquote_service::responses::QuoteResponse;
 use rustquote_service::AppState; // Import AppState
-use serde_json;
-use std::path::PathBuf;
-use std::sync::Arc; // For AppState
-                    // std::fs::File and std::io::Write are not directly needed in tests anymore if create_temp_quotes_file handles it
-use tempfile::NamedTempFile;
-// std::path::{Path, PathBuf} are not directly needed if NamedTempFile handles paths
+use std::path::{Path, PathBuf};
+use tempfile::{NamedTempFile, TempDir};
 use tower::util::ServiceExt; // Corrected import for ServiceExt
 
+// A quotes file in a directory of its own, so the history file the quote store
+// saves next to it is deleted with it when it goes out of scope.
+struct TempQuotesFile {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempQuotesFile {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
 // Helper function to create a temporary quotes file and return it
-// NamedTempFile handles its own deletion when it goes out of scope.
-fn create_temp_quotes_file(content: &str) -> NamedTempFile {
-    let mut file = NamedTempFile::new().expect("Failed to create temp file");
-    std::io::Write::write_all(&mut file, content.as_bytes()).expect("Failed to write to temp file");
-    file
+fn create_temp_quotes_file(content: &str) -> TempQuotesFile {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("quotes.json");
+    std::fs::write(&path, content).expect("Failed to write to temp file");
+    TempQuotesFile { _dir: dir, path }
 }
 
 #[tokio::test]
@@ -29,9 +38,7 @@ async fn test_get_quote_handler_success() {
         {"id": 2, "quote": "Another test quote.", "author": "Tester2", "source": null}
     ]"#;
     let temp_file = create_temp_quotes_file(quotes_content);
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let response = router
@@ -66,9 +73,7 @@ async fn test_get_quote_handler_success() {
 #[tokio::test]
 async fn test_get_quote_handler_empty_file() {
     let temp_file = create_temp_quotes_file("[]"); // Empty JSON array
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let response = router
@@ -107,9 +112,7 @@ async fn test_get_quote_handler_file_not_found() {
     let non_existent_path = temp_file.path().to_str().unwrap().to_string();
     drop(temp_file); // Delete the temp file, ensuring path does not exist
 
-    let app_state = AppState {
-        quotes_file_path: Arc::new(PathBuf::from(non_existent_path)),
-    };
+    let app_state = AppState::new(PathBuf::from(non_existent_path));
     let router = app(app_state);
 
     let response = router
@@ -144,9 +147,7 @@ async fn test_get_quote_handler_file_not_found() {
 #[tokio::test]
 async fn test_get_quote_handler_invalid_json() {
     let temp_file = create_temp_quotes_file("[{{\"id\":1, \"quote\":\"bad json\"}}]"); // Malformed JSON
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let response = router
@@ -183,9 +184,7 @@ async fn test_get_quote_handler_malformed_quote_data() {
         {"id": 2, "author": "Malformed Author"}
     ]"#; // Second quote is missing "quote"
     let temp_file = create_temp_quotes_file(malformed_quotes_content);
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let response = router
@@ -221,9 +220,7 @@ async fn test_get_quote_handler_malformed_quote_data() {
 #[tokio::test]
 async fn test_health_check_handler() {
     // Health check doesn't use quotes_file_path, so a dummy one is fine.
-    let dummy_app_state = AppState {
-        quotes_file_path: Arc::new(PathBuf::from("dummy_path_for_health_check.json")),
-    };
+    let dummy_app_state = AppState::new(PathBuf::from("dummy_path_for_health_check.json"));
     let router = app(dummy_app_state);
 
     let response = router
@@ -251,9 +248,7 @@ async fn test_get_quote_by_id_handler_success() {
         {"id": 3, "quote": "Quote number three.", "author": "Author Three", "source": "Source Three"}
     ]"#;
     let temp_file = create_temp_quotes_file(quotes_content);
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let target_id = 2;
@@ -285,9 +280,7 @@ async fn test_get_quote_by_id_handler_not_found() {
         {"id": 1, "quote": "Only one quote here.", "author": "Single Author", "source": "Single Source"}
     ]"#;
     let temp_file = create_temp_quotes_file(quotes_content);
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let non_existent_id = 999;
@@ -318,9 +311,7 @@ async fn test_get_quote_by_id_handler_not_found() {
 #[tokio::test]
 async fn test_get_quote_by_id_handler_empty_file_for_id_request() {
     let temp_file = create_temp_quotes_file("[]"); // Empty JSON array
-    let app_state = AppState {
-        quotes_file_path: Arc::new(temp_file.path().to_path_buf()),
-    };
+    let app_state = AppState::new(temp_file.path().to_path_buf());
     let router = app(app_state);
 
     let target_id = 1; // Any ID, as the file is empty
@@ -357,9 +348,7 @@ async fn test_get_quote_by_id_handler_file_not_found_for_id_request() {
     let non_existent_path = temp_file.path().to_str().unwrap().to_string();
     drop(temp_file); // Ensure file is deleted
 
-    let app_state = AppState {
-        quotes_file_path: Arc::new(PathBuf::from(non_existent_path.clone())), // Clone for use in assert message
-    };
+    let app_state = AppState::new(PathBuf::from(non_existent_path.clone()));
     let router = app(app_state);
 
     let target_id = 1;
@@ -386,3 +375,1892 @@ async fn test_get_quote_by_id_handler_file_not_found_for_id_request() {
         .contains(&format!("Quote data file not found: {}", non_existent_path)));
     assert_eq!(error_response["error_code"], "QUOTE_SOURCING_ERROR");
 }
+
+#[tokio::test]
+async fn test_get_quote_revisions_handler_records_edits() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Original text.", "author": "Editor", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf());
+    let router = app(app_state);
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/1/revisions")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let history: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(history["id"], 1);
+    assert!(history["created_at"].is_string());
+    assert_eq!(history["revisions"].as_array().unwrap().len(), 0);
+
+    std::fs::write(
+        temp_file.path(),
+        r#"[{"id": 1, "quote": "Edited text, now longer.", "author": "Editor", "source": null}]"#,
+    )
+    .unwrap();
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/1/revisions")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let history: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let revisions = history["revisions"].as_array().unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0]["kind"], "updated");
+    assert_eq!(revisions[0]["before"]["quote"], "Original text.");
+    assert_eq!(revisions[0]["after"]["quote"], "Edited text, now longer.");
+}
+
+#[tokio::test]
+async fn test_get_quote_revisions_handler_not_found() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Only quote.", "author": "Editor", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf());
+    let router = app(app_state);
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/42/revisions")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_get_quote_by_id_handler_accepts_slug_and_uid() {
+    let quotes_content = r#"[
+        {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs"},
+        {"id": 2, "quote": "Pinned identifiers.", "author": "Editor", "uid": "pinned-uid", "slug": "pinned-slug"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes_content);
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    for (key, expected_id) in [
+        ("steve-jobs-stay-hungry-stay-foolish", 1),
+        ("pinned-uid", 2),
+        ("pinned-slug", 2),
+        ("2", 2),
+    ] {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/quote/{}", key))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "lookup by {}", key);
+        let body = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let quote_response: QuoteResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(quote_response.id, expected_id, "lookup by {}", key);
+    }
+}
+
+#[tokio::test]
+async fn test_sparse_fieldsets() {
+    let quotes_content = r#"[
+        {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs", "source": "Stanford, 2005"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes_content);
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    for uri in [
+        "/api/v1/quote?fields=quote,author",
+        "/api/v1/quote/1?fields=quote,author",
+    ] {
+        let response = router
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        let body = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            r#"{"quote":"Stay hungry, stay foolish.","author":"Steve Jobs"}"#,
+            "{}",
+            uri
+        );
+    }
+
+    // Internal tools can ask for the source and lifecycle fields too.
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/1?fields=id,source,status,slug")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "id": 1,
+            "source": "Stanford, 2005",
+            "status": "active",
+            "slug": "steve-jobs-stay-hungry-stay-foolish",
+        })
+    );
+
+    // Shaped bodies are still rendered in the negotiated format.
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/1?fields=quote,author&format=text")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&body).unwrap(),
+        "\"Stay hungry, stay foolish.\"\n    -- Steve Jobs\n"
+    );
+
+    for uri in ["/api/v1/quote?fields=quote,mood", "/api/v1/quote/1?fields="] {
+        let response = router
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let message = error["message"].as_str().unwrap();
+        assert!(
+            message.contains("Valid fields: id, uid, slug, quote, author, source"),
+            "{}",
+            message
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_filter_queries() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs", "source": "Stanford, 2005"},
+            {"id": 2, "quote": "Simplicity is the ultimate sophistication.", "author": "Steve Jobs"},
+            {"id": 3, "quote": "Design is how it works.", "author": "Steve Jobs", "source": "NYT", "status": "archived"},
+            {"id": 4, "quote": "Less is more.", "author": "Mies"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let get = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        }
+    };
+
+    // author:"Steve Jobs" AND len<80 AND NOT source:null
+    let (status, quote) = get(
+        "/api/v1/quote?filter=author:%22Steve%20Jobs%22%20AND%20len%3C80%20AND%20NOT%20source:null",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(quote["id"], 1);
+
+    // Counts cover active, published quotes only.
+    let (status, count) = get("/api/v1/quotes/count?filter=author:jobs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        count,
+        serde_json::json!({"filter": "author:jobs", "count": 2})
+    );
+    let (_, count) = get("/api/v1/quotes/count").await;
+    assert_eq!(count, serde_json::json!({"count": 3}));
+
+    let (status, _) = get("/api/v1/quote?filter=author:nobody").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for uri in [
+        "/api/v1/quote?filter=len%3Cshort",
+        "/api/v1/quotes/count?filter=len%3Cshort",
+    ] {
+        let (status, error) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(
+            error["message"],
+            "Invalid filter at position 5: 'len' needs a whole number, found 'short'."
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_quote_facets() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs", "source": "Stanford, 2005"},
+            {"id": 2, "quote": "Stay humble and keep designing the future of personal computing.", "author": "Steve Jobs"},
+            {"id": 3, "quote": "Less is more.", "author": "Mies"},
+            {"id": 4, "quote": "Stay archived.", "author": "Archivist", "status": "archived"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let get = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        }
+    };
+
+    let (status, facets) = get("/api/v1/quotes/facets").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(facets["total"], 3);
+    assert_eq!(
+        facets["authors"],
+        serde_json::json!({"distinct": 2, "values": [
+            {"value": "Steve Jobs", "count": 2},
+            {"value": "Mies", "count": 1}
+        ]})
+    );
+    assert_eq!(
+        facets["sources"]["values"][0]["value"],
+        serde_json::Value::Null
+    );
+    assert_eq!(facets["sources"]["values"][0]["count"], 2);
+    let lengths: Vec<&serde_json::Value> = facets["lengths"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| &b["count"])
+        .collect();
+    assert_eq!(lengths, [2, 1, 0, 0]);
+
+    let (status, facets) =
+        get("/api/v1/quotes/facets?text=STAY&author=steve%20jobs&max_len=40").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(facets["total"], 1);
+    assert_eq!(facets["sources"]["values"][0]["value"], "Stanford, 2005");
+
+    let (_, facets) = get("/api/v1/quotes/facets?limit=1").await;
+    assert_eq!(facets["authors"]["distinct"], 2);
+    assert_eq!(facets["authors"]["values"].as_array().unwrap().len(), 1);
+
+    for uri in [
+        "/api/v1/quotes/facets?min_len=short",
+        "/api/v1/quotes/facets?min_len=50&max_len=10",
+        "/api/v1/quotes/facets?limit=0",
+    ] {
+        let (status, _) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_bulk_fetch_quotes_by_ids() {
+    let quotes_content = r#"[
+        {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs"},
+        {"id": 2, "quote": "Less is more.", "author": "Mies"},
+        {"id": 3, "quote": "Retired quote.", "author": "Editor", "status": "deleted"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes_content);
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let check = |value: serde_json::Value| {
+        let items = value["items"].as_array().unwrap();
+        let ids: Vec<&str> = items.iter().map(|i| i["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["2", "99", "steve-jobs-stay-hungry-stay-foolish", "3"]);
+        assert_eq!(items[0]["quote"]["quote"], "Less is more.");
+        assert_eq!(items[1]["error"]["error_code"], "NOT_FOUND");
+        assert!(items[1].get("quote").is_none());
+        assert_eq!(items[2]["quote"]["id"], 1);
+        assert_eq!(items[3]["error"]["error_code"], "GONE");
+        assert_eq!(value["missing"], serde_json::json!(["99", "3"]));
+    };
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quotes?ids=2,99,%20steve-jobs-stay-hungry-stay-foolish,3")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    check(serde_json::from_slice(&body).unwrap());
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/quotes:batchGet")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"ids": [2, "99", "steve-jobs-stay-hungry-stay-foolish", 3]}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    check(serde_json::from_slice(&body).unwrap());
+
+    let too_many = serde_json::json!({ "ids": vec![1; 501] }).to_string();
+    for (method, uri, body) in [
+        ("GET", "/api/v1/quotes", String::new()),
+        ("GET", "/api/v1/quotes?ids=,", String::new()),
+        (
+            "POST",
+            "/api/v1/quotes:batchGet",
+            "{\"ids\": 1}".to_string(),
+        ),
+        ("POST", "/api/v1/quotes:batchGet", too_many),
+    ] {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::BAD_REQUEST,
+            "{} {}",
+            method,
+            uri
+        );
+    }
+
+    // Only the literal custom method is served.
+    for uri in [
+        "/api/v1/quotes:batchDelete",
+        "/api/v1/quotesX:batchGet",
+        "/api/v1/quotes-archive:batchGet",
+    ] {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::from(r#"{"ids": [1]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "POST {}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_quote_lifecycle_states() {
+    let quotes_content = r#"[
+        {"id": 1, "quote": "Archived quote.", "author": "Editor", "status": "archived"},
+        {"id": 2, "quote": "Deleted quote.", "author": "Editor", "status": "deleted"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes_content);
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let fetch = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+
+    // Archived quotes stay fetchable by id.
+    assert_eq!(fetch("/api/v1/quote/1").await.status(), StatusCode::OK);
+
+    // Deleted quotes are gone.
+    let response = fetch("/api/v1/quote/2").await;
+    assert_eq!(response.status(), StatusCode::GONE);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let error_response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error_response["error_code"], "GONE");
+
+    // Neither is eligible for random selection.
+    let response = fetch("/api/v1/quote").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let error_response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        error_response["message"],
+        "No active quotes available in the data file."
+    );
+}
+
+#[tokio::test]
+async fn test_quote_removed_from_file_is_gone() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Stays.", "author": "Editor"},
+            {"id": 2, "quote": "Will be removed.", "author": "Editor"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    std::fs::write(
+        temp_file.path(),
+        r#"[{"id": 1, "quote": "Stays.", "author": "Editor"}]"#,
+    )
+    .unwrap();
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/quote/2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn test_collection_endpoints_report_missing_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let quotes_path = dir.path().join("quotes.json");
+    std::fs::write(
+        &quotes_path,
+        r#"[
+            {"id": 1, "quote": "First.", "author": "A"},
+            {"id": 2, "quote": "Second.", "author": "B", "status": "archived"}
+        ]"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("collections.json"),
+        r#"[{"id": "monday", "name": "Monday motivation", "description": "Start strong", "quote_ids": [2, 99, 1]}]"#,
+    )
+    .unwrap();
+    let router = app(AppState::new(quotes_path));
+
+    let get_json = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        }
+    };
+
+    let (status, list) = get_json("/api/v1/collections").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list[0]["id"], "monday");
+    assert_eq!(list[0]["quote_count"], 2);
+    assert_eq!(list[0]["missing_ids"], serde_json::json!([99]));
+
+    let (status, collection) = get_json("/api/v1/collections/monday").await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<u64> = collection["quotes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|q| q["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![2, 1]);
+    assert_eq!(collection["missing_ids"], serde_json::json!([99]));
+
+    // The archived quote is listed but never picked at random.
+    for _ in 0..10 {
+        let (status, quote) = get_json("/api/v1/collections/monday/random").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(quote["id"], 1);
+    }
+
+    let (status, error) = get_json("/api/v1/collections/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["error_code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_similar_quotes_admin_preview() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Love the work you do.", "author": "Confucius"},
+            {"id": 2, "quote": "Do what you love and love the work.", "author": "Steve Jobs", "publish_at": "2099-12-01T00:00:00Z"}
+        ]"#,
+    );
+    let router =
+        app(AppState::new(temp_file.path().to_path_buf())
+            .with_admin_token(Some("s3cret".to_string())));
+    let request = |uri: &str, token: Option<&str>| {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(token) = token {
+            builder = builder.header("x-admin-token", token);
+        }
+        router.clone().oneshot(builder.body(Body::empty()).unwrap())
+    };
+    let similar_ids = |body: &[u8]| -> Vec<u64> {
+        let value: serde_json::Value = serde_json::from_slice(body).unwrap();
+        value["similar"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["quote"]["id"].as_u64().unwrap())
+            .collect()
+    };
+
+    let response = request("/api/v1/quote/1/similar", None).await.unwrap();
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert!(similar_ids(&body).is_empty());
+
+    let preview = "/api/v1/quote/1/similar?preview_at=2099-12-02T00:00:00Z";
+    let response = request(preview, None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = request(preview, Some("s3cret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert_eq!(similar_ids(&body), [2]);
+    let response = request(
+        "/api/v1/quote/2/similar?preview_at=2099-12-02T00:00:00Z",
+        Some("s3cret"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_publishing_window_and_admin_preview() {
+    let quotes_content = r#"[
+        {"id": 1, "quote": "Seasonal.", "author": "Campaign", "publish_at": "2099-12-01T00:00:00Z", "expire_at": "2099-12-27T00:00:00Z"},
+        {"id": 2, "quote": "Expired.", "author": "Campaign", "expire_at": "2000-01-01T00:00:00Z"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes_content);
+    let router =
+        app(AppState::new(temp_file.path().to_path_buf())
+            .with_admin_token(Some("s3cret".to_string())));
+
+    let request = |uri: &str, token: Option<&str>| {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(token) = token {
+            builder = builder.header("x-admin-token", token);
+        }
+        router.clone().oneshot(builder.body(Body::empty()).unwrap())
+    };
+
+    // Outside their windows, both quotes are unavailable.
+    let response = request("/api/v1/quote/1", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = request("/api/v1/quote/2", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = request("/api/v1/quote", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // An admin can preview the corpus as it will be during the campaign.
+    let preview = "/api/v1/quote?preview_at=2099-12-24T12:00:00Z";
+    let response = request(preview, Some("s3cret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let quote_response: QuoteResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(quote_response.id, 1);
+
+    let preview_by_id = "/api/v1/quote/1?preview_at=2099-12-24T12:00:00Z";
+    let response = request(preview_by_id, Some("s3cret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Previews need the right token and a valid timestamp.
+    let response = request(preview, None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = request(preview, Some("wrong")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = request("/api/v1/quote?preview_at=tomorrow", Some("s3cret"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_content_negotiation_for_quotes_and_errors() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Simple & true.", "author": "Tester", "source": null}]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let fetch = |uri: &'static str, accept: Option<&'static str>| {
+        let router = router.clone();
+        async move {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(accept) = accept {
+                builder = builder.header("accept", accept);
+            }
+            let response = router
+                .oneshot(builder.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                content_type,
+                String::from_utf8(body.to_vec()).unwrap(),
+            )
+        }
+    };
+
+    let (status, content_type, body) = fetch("/api/v1/quote/1", Some("text/plain")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/plain"));
+    assert_eq!(body, "\"Simple & true.\"\n    -- Tester\n");
+
+    let (_, content_type, body) = fetch("/api/v1/quote", Some("text/html")).await;
+    assert!(content_type.starts_with("text/html"));
+    assert!(body.contains("<p>Simple &amp; true.</p>"));
+
+    let (_, content_type, body) = fetch("/api/v1/quote/1?format=markdown", Some("text/html")).await;
+    assert!(content_type.starts_with("text/markdown"));
+    assert!(body.starts_with("> Simple & true."));
+
+    let (status, content_type, body) = fetch("/api/v1/quote/99", Some("application/xml")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(content_type.starts_with("application/xml"));
+    assert!(body.contains("<error><error_code>NOT_FOUND</error_code>"));
+
+    let (status, content_type, _) = fetch("/api/v1/quote/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("application/json"));
+
+    let (status, _, body) = fetch("/api/v1/quote/1", Some("image/png")).await;
+    assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    let error_response: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(error_response["error_code"], "NOT_ACCEPTABLE");
+}
+
+#[tokio::test]
+async fn test_quote_card_svg_endpoints() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Less <is> more & then some.", "author": "Tester", "source": null},
+            {"id": 2, "quote": "Gone.", "author": "Tester", "source": null, "status": "deleted"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let fetch = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                content_type,
+                String::from_utf8(body.to_vec()).unwrap(),
+            )
+        }
+    };
+
+    let (status, content_type, body) =
+        fetch("/api/v1/quote/1/card.svg?theme=dark&size=square").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("image/svg+xml"));
+    assert!(body.contains(r#"width="1080" height="1080""#));
+    assert!(body.contains("Less &lt;is&gt; more &amp; then some."));
+    assert!(!body.contains("<is>"));
+
+    let (status, content_type, _) = fetch("/api/v1/quote/random/card.svg").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("image/svg+xml"));
+
+    let (status, _, body) = fetch("/api/v1/quote/1/card.svg?theme=neon").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Valid themes"));
+
+    let (status, _, _) = fetch("/api/v1/quote/2/card.svg").await;
+    assert_eq!(status, StatusCode::GONE);
+    let (status, _, _) = fetch("/api/v1/quote/9/card.svg").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_quote_card_png_endpoint() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Pictures speak.", "author": "Tester", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf());
+    let router = app(app_state.clone());
+
+    let fetch = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let body = body::to_bytes(response.into_body(), 16 * 1024 * 1024)
+                .await
+                .unwrap();
+            (status, content_type, body)
+        }
+    };
+
+    let uri = "/api/v1/quote/1/card.png?theme=sepia&width=400&height=300";
+    let (status, content_type, first) = fetch(uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/png");
+    assert!(first.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert_eq!(app_state.card_cache.len(), 1);
+
+    // The second request is served from the cache.
+    let (_, _, second) = fetch(uri).await;
+    assert_eq!(first, second);
+    assert_eq!(app_state.card_cache.len(), 1);
+
+    let (status, _, _) = fetch("/api/v1/quote/random/card.png?width=300&height=300").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(app_state.card_cache.len(), 2);
+
+    let (status, _, body) = fetch("/api/v1/quote/1/card.png?width=100000").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error_response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error_response["error_code"], "BAD_REQUEST");
+    let (status, _, _) = fetch("/api/v1/quote/1/card.png?height=abc").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_daily_and_new_quote_feeds() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Tom & Jerry <3", "author": "Cartoonist", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf())
+        .with_public_base_url(Some("https://quotes.example/".to_string()));
+    let router = app(app_state);
+
+    let fetch = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                content_type,
+                String::from_utf8(body.to_vec()).unwrap(),
+            )
+        }
+    };
+
+    let (status, content_type, rss) = fetch("/feeds/daily.rss").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("application/rss+xml"));
+    assert_eq!(rss.matches("<item>").count(), 7);
+    assert!(rss.contains("Tom &amp; Jerry &lt;3"));
+    assert!(rss.contains(
+        r#"<atom:link href="https://quotes.example/feeds/daily.rss" rel="self" type="application/rss+xml"/>"#
+    ));
+    assert!(rss.contains("<link>https://quotes.example/api/v1/quote/1?format=html</link>"));
+
+    let (status, content_type, atom) = fetch("/feeds/daily.atom").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("application/atom+xml"));
+    assert_eq!(atom.matches("<entry>").count(), 7);
+    let (_, _, atom_again) = fetch("/feeds/daily.atom").await;
+    assert_eq!(atom, atom_again);
+
+    let (status, _, new_feed) = fetch("/feeds/new.atom").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(new_feed.matches("<entry>").count(), 0);
+
+    std::fs::write(
+        temp_file.path(),
+        r#"[
+            {"id": 1, "quote": "Tom & Jerry <3", "author": "Cartoonist", "source": null},
+            {"id": 2, "quote": "Freshly added.", "author": "Newcomer", "source": null}
+        ]"#,
+    )
+    .unwrap();
+    let (_, _, new_feed) = fetch("/feeds/new.atom").await;
+    assert_eq!(new_feed.matches("<entry>").count(), 1);
+    assert!(new_feed.contains("<author><name>Newcomer</name></author>"));
+    assert!(new_feed.contains("https://quotes.example/api/v1/quote/2?format=html"));
+}
+
+#[tokio::test]
+async fn test_daily_calendar_feed() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Plan, then act; repeat.", "author": "Planner", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf()).with_calendar_days(5);
+    let router = app(app_state);
+
+    let fetch = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                content_type,
+                String::from_utf8(body.to_vec()).unwrap(),
+            )
+        }
+    };
+
+    let (status, content_type, ics) = fetch("/feeds/daily.ics").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/calendar"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 5);
+    assert!(ics.contains(r"Plan\, then act\; repeat."));
+    let (_, _, again) = fetch("/feeds/daily.ics").await;
+    assert_eq!(ics, again);
+
+    let (_, _, ics) = fetch("/feeds/daily.ics?days=10").await;
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 10);
+
+    let (status, _, body) = fetch("/feeds/daily.ics?days=5000").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("BAD_REQUEST"));
+}
+
+#[tokio::test]
+async fn test_conditional_requests_and_cache_control() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Cache me.", "author": "Tester", "source": null}]"#,
+    );
+    let router =
+        app(AppState::new(temp_file.path().to_path_buf())
+            .with_admin_token(Some("s3cret".to_string())));
+
+    let request = |uri: &'static str, headers: Vec<(&'static str, String)>| {
+        let router = router.clone();
+        async move {
+            let mut builder = Request::builder().uri(uri);
+            for (name, value) in headers {
+                builder = builder.header(name, value);
+            }
+            router
+                .oneshot(builder.body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+    let header = |response: &axum::response::Response, name: &str| {
+        response
+            .headers()
+            .get(name)
+            .map(|v| v.to_str().unwrap().to_string())
+    };
+
+    let response = request("/api/v1/quote/1", vec![]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = header(&response, "etag").expect("by-id responses carry an ETag");
+    let last_modified = header(&response, "last-modified").unwrap();
+    assert_eq!(
+        header(&response, "cache-control").unwrap(),
+        "public, max-age=86400"
+    );
+
+    let response = request("/api/v1/quote/1", vec![("if-none-match", etag.clone())]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(header(&response, "etag").unwrap(), etag);
+    let body = body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert!(body.is_empty());
+
+    let response = request(
+        "/api/v1/quote/1",
+        vec![("if-modified-since", last_modified.clone())],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Other renderings of the same URL get their own ETag.
+    let response = request(
+        "/api/v1/quote/1",
+        vec![
+            ("accept", "text/html".to_string()),
+            ("if-none-match", etag.clone()),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(header(&response, "etag").unwrap(), etag);
+
+    // Random quotes and admin previews are never cached.
+    let response = request("/api/v1/quote", vec![]).await;
+    assert_eq!(header(&response, "cache-control").unwrap(), "no-store");
+    assert!(header(&response, "etag").is_none());
+    let response = request(
+        "/api/v1/quote/1?preview_at=2030-01-01T00:00:00Z",
+        vec![("x-admin-token", "s3cret".to_string())],
+    )
+    .await;
+    assert_eq!(
+        header(&response, "cache-control").unwrap(),
+        "private, no-store"
+    );
+    assert!(header(&response, "etag").is_none());
+
+    // Editing the corpus invalidates the ETag.
+    std::fs::write(
+        temp_file.path(),
+        r#"[{"id": 1, "quote": "Cache me, edited.", "author": "Tester", "source": null}]"#,
+    )
+    .unwrap();
+    let response = request("/api/v1/quote/1", vec![("if-none-match", etag.clone())]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(header(&response, "etag").unwrap(), etag);
+}
+
+#[tokio::test]
+async fn test_graphql_endpoint_and_graphiql_flag() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "First.", "author": "Ada", "source": null},
+            {"id": 2, "quote": "Second.", "author": "Ada", "source": "Notes"},
+            {"id": 3, "quote": "Gone.", "author": "Ada", "source": null, "status": "deleted"}]"#,
+    );
+    let query = |router: axum::Router, body: &'static str| async move {
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    };
+
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let value = query(
+        router.clone(),
+        r#"{"query": "query Q($id: String!) { quote(id: $id) { text author { name quotes(excludeId: 1) { text } } } corpus { quoteCount authorCount } }", "variables": {"id": "1"}}"#,
+    )
+    .await;
+    assert_eq!(
+        value["data"],
+        serde_json::json!({
+            "quote": {"text": "First.", "author": {"name": "Ada", "quotes": [{"text": "Second."}]}},
+            "corpus": {"quoteCount": 2, "authorCount": 1}
+        })
+    );
+
+    let value = query(
+        router.clone(),
+        r#"{"query": "{ quote(id: \"3\") { text } }"}"#,
+    )
+    .await;
+    assert_eq!(value["data"], serde_json::Value::Null);
+    assert_eq!(value["errors"][0]["extensions"]["code"], "GONE");
+
+    let graphiql = |router: axum::Router| async move {
+        router
+            .oneshot(
+                Request::builder()
+                    .uri("/graphql")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    };
+    assert_eq!(graphiql(router).await.status(), StatusCode::NOT_FOUND);
+
+    let router = app(AppState::new(temp_file.path().to_path_buf()).with_graphiql(true));
+    let response = graphiql(router).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("graphiql"));
+}
+
+#[tokio::test]
+async fn test_grpc_server_with_health_checking() {
+    use rustquote_service::grpc::{self, proto};
+    use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient};
+
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Over the wire.", "author": "Tester", "source": null}]"#,
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app_state = AppState::new(temp_file.path().to_path_buf());
+    tokio::spawn(async move { grpc::serve(listener, app_state).await.unwrap() });
+
+    let channel = tonic::transport::Channel::from_shared(format!("http://{}", address))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = proto::quote_service_client::QuoteServiceClient::new(channel.clone());
+    let quote = client
+        .get_quote(proto::GetQuoteRequest { id: "1".into() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(quote.text, "Over the wire.");
+    let status = client
+        .get_quote(proto::GetQuoteRequest { id: "99".into() })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    let mut health = HealthClient::new(channel);
+    let response = health
+        .check(tonic_health::pb::HealthCheckRequest {
+            service: "rustquote.v1.QuoteService".into(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.status(), ServingStatus::Serving);
+}
+
+/// Reads the next JSON text frame from a WebSocket client.
+async fn next_ws_json<S>(socket: &mut S) -> serde_json::Value
+where
+    S: futures_util::Stream<
+            Item = Result<
+                tokio_tungstenite::tungstenite::Message,
+                tokio_tungstenite::tungstenite::Error,
+            >,
+        > + Unpin,
+{
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    loop {
+        match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Ping(_) | Message::Pong(_) => continue,
+            other => panic!("unexpected frame: {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_quote_stream() {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::{protocol::frame::coding::CloseCode, Message};
+
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 3, "quote": "Third.", "author": "Ada", "source": null},
+            {"id": 1, "quote": "First.", "author": "Grace", "source": null},
+            {"id": 2, "quote": "Second.", "author": "Ada", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf()).with_max_stream_connections(1);
+    let shutdown = app_state.shutdown.clone();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/api/v1/stream/ws", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app(app_state)).await.unwrap() });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str())
+        .await
+        .unwrap();
+    let welcome = next_ws_json(&mut socket).await;
+    assert_eq!(welcome["type"], "welcome");
+    assert_eq!(welcome["min_interval_secs"], 5);
+
+    // Invalid subscriptions and malformed messages are reported without closing.
+    socket
+        .send(Message::text(
+            r#"{"type": "subscribe", "interval_secs": 1}"#,
+        ))
+        .await
+        .unwrap();
+    let error = next_ws_json(&mut socket).await;
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["error_code"], "BAD_REQUEST");
+    socket.send(Message::text("not json")).await.unwrap();
+    assert_eq!(next_ws_json(&mut socket).await["error_code"], "BAD_REQUEST");
+
+    socket
+        .send(Message::text(
+            r#"{"type": "subscribe", "interval_secs": 60, "rotation": "sequential", "filters": {"author": "ADA"}}"#,
+        ))
+        .await
+        .unwrap();
+    let subscribed = next_ws_json(&mut socket).await;
+    assert_eq!(subscribed["type"], "subscribed");
+    assert_eq!(subscribed["rotation"], "sequential");
+    let quote = next_ws_json(&mut socket).await;
+    assert_eq!(quote["type"], "quote");
+    assert_eq!(quote["sequence"], 1);
+    assert_eq!(quote["quote"]["id"], 2);
+
+    // The limit is one stream, so a second client is turned away.
+    match tokio_tungstenite::connect_async(url.as_str()).await {
+        Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE)
+        }
+        Err(other) => panic!("expected a 503 handshake response, got {:?}", other),
+        Ok(_) => panic!("expected the second stream to be rejected"),
+    }
+
+    shutdown.trigger();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+        other => panic!("expected a close frame, got {:?}", other),
+    }
+}
+
+/// Reads Server-Sent Events from a streaming response body.
+struct SseReader {
+    stream: axum::body::BodyDataStream,
+    buffer: String,
+}
+
+/// One parsed SSE event.
+#[derive(Debug)]
+struct SseEvent {
+    event: String,
+    id: Option<String>,
+    data: serde_json::Value,
+}
+
+impl SseReader {
+    fn new(response: axum::response::Response) -> Self {
+        SseReader {
+            stream: response.into_body().into_data_stream(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Returns the next event that has data, skipping comments and
+    /// retry-only events.
+    async fn next_event(&mut self) -> SseEvent {
+        use futures_util::StreamExt;
+
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                let mut event = SseEvent {
+                    event: "message".to_string(),
+                    id: None,
+                    data: serde_json::Value::Null,
+                };
+                for line in block.lines() {
+                    if let Some(name) = line.strip_prefix("event: ") {
+                        event.event = name.to_string();
+                    } else if let Some(id) = line.strip_prefix("id: ") {
+                        event.id = Some(id.to_string());
+                    } else if let Some(data) = line.strip_prefix("data: ") {
+                        event.data = serde_json::from_str(data).unwrap();
+                    }
+                }
+                if !event.data.is_null() {
+                    return event;
+                }
+                continue;
+            }
+            let chunk =
+                tokio::time::timeout(std::time::Duration::from_secs(10), self.stream.next())
+                    .await
+                    .expect("timed out waiting for an event")
+                    .expect("stream ended")
+                    .unwrap();
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_sse_quote_stream_with_reload_events_and_resumption() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "First.", "author": "Ada", "source": null},
+            {"id": 2, "quote": "Second.", "author": "Ada", "source": null}]"#,
+    );
+    let app_state = AppState::new(temp_file.path().to_path_buf());
+    let router = app(app_state.clone());
+    let stream_request = |last_event_id: Option<&str>| {
+        let mut builder =
+            Request::builder().uri("/api/v1/stream/sse?interval_secs=3600&rotation=sequential");
+        if let Some(id) = last_event_id {
+            builder = builder.header("Last-Event-ID", id);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+
+    let response = router.clone().oneshot(stream_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let mut events = SseReader::new(response);
+    let first = events.next_event().await;
+    assert_eq!(first.event, "quote");
+    assert_eq!(first.id.as_deref(), Some("0"));
+    assert_eq!(first.data["sequence"], 1);
+    assert_eq!(first.data["quote"]["id"], 1);
+
+    // Edit one quote, remove another and add a third.
+    std::fs::write(
+        temp_file.path(),
+        r#"[{"id": 1, "quote": "First, edited.", "author": "Ada", "source": null},
+            {"id": 3, "quote": "Third.", "author": "Ada", "source": null}]"#,
+    )
+    .unwrap();
+    let changes: Vec<SseEvent> = vec![
+        events.next_event().await,
+        events.next_event().await,
+        events.next_event().await,
+    ];
+    let summary: Vec<(&str, Option<&str>, &serde_json::Value)> = changes
+        .iter()
+        .map(|e| (e.event.as_str(), e.id.as_deref(), &e.data["quote"]["id"]))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("changed", Some("1"), &serde_json::json!(1)),
+            ("removed", Some("2"), &serde_json::json!(2)),
+            ("added", Some("3"), &serde_json::json!(3)),
+        ]
+    );
+    assert_eq!(changes[0].data["quote"]["quote"], "First, edited.");
+    assert_eq!(changes[0].data["corpus_version"], 2);
+
+    // A client that saw event 1 gets the rest replayed before its first quote.
+    let response = router
+        .clone()
+        .oneshot(stream_request(Some("1")))
+        .await
+        .unwrap();
+    let mut resumed = SseReader::new(response);
+    assert_eq!(resumed.next_event().await.event, "removed");
+    assert_eq!(resumed.next_event().await.event, "added");
+    let quote = resumed.next_event().await;
+    assert_eq!(quote.event, "quote");
+    assert_eq!(quote.id.as_deref(), Some("3"));
+
+    // An id the server never issued asks the client to resync.
+    let response = router
+        .clone()
+        .oneshot(stream_request(Some("99")))
+        .await
+        .unwrap();
+    let resync = SseReader::new(response).next_event().await;
+    assert_eq!(resync.event, "resync");
+    assert_eq!(resync.id.as_deref(), Some("3"));
+    assert_eq!(resync.data["latest_event_id"], 3);
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/stream/sse?interval_secs=1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_api_versions_and_v1_deprecation() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Stay hungry.", "author": "Steve Jobs", "source": "Stanford, 2005"}]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let get = |uri: &str, version: Option<&str>| {
+        let mut builder = Request::builder().uri(uri).header("host", "quotes.test");
+        if let Some(version) = version {
+            builder = builder.header("api-version", version);
+        }
+        router.clone().oneshot(builder.body(Body::empty()).unwrap())
+    };
+
+    // v1 keeps its exact body and is marked as deprecated.
+    let response = get("/api/v1/quote/1", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers().clone();
+    assert_eq!(headers["api-version"], "1");
+    assert!(headers["deprecation"].to_str().unwrap().starts_with('@'));
+    assert_eq!(headers["sunset"], "Wed, 30 Jun 2027 00:00:00 GMT");
+    assert_eq!(
+        headers["link"],
+        "</api/v2/quote/1>; rel=\"successor-version\""
+    );
+    let body = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(
+        &body[..],
+        br#"{"id":1,"quote":"Stay hungry.","author":"Steve Jobs"}"#
+    );
+
+    // v2 returns the full resource.
+    let response = get("/api/v2/quote/1", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["api-version"], "2");
+    assert!(response.headers().get("deprecation").is_none());
+    assert!(response.headers().get("etag").is_some());
+    let body = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let resource: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(resource["source"], "Stanford, 2005");
+    assert_eq!(
+        resource["links"]["self"],
+        "http://quotes.test/api/v2/quote/1"
+    );
+    assert_eq!(
+        resource["links"]["card_svg"],
+        "http://quotes.test/api/v1/quote/1/card.svg"
+    );
+    assert_eq!(resource["metadata"]["status"], "active");
+    assert_eq!(resource["metadata"]["corpus_version"], 1);
+
+    let response = get("/api/v2/quote", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+
+    // Unversioned aliases follow the Api-Version header, defaulting to v2.
+    let response = get("/api/quote/1", None).await.unwrap();
+    assert_eq!(response.headers()["api-version"], "2");
+    assert!(response
+        .headers()
+        .get_all("vary")
+        .iter()
+        .any(|v| v == "api-version"));
+    let response = get("/api/quote/1", Some("1")).await.unwrap();
+    assert_eq!(response.headers()["api-version"], "1");
+    assert!(response.headers().get("deprecation").is_some());
+    let body = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(
+        &body[..],
+        br#"{"id":1,"quote":"Stay hungry.","author":"Steve Jobs"}"#
+    );
+    let response = get("/api/quote", Some("v9")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Routes registered in `app()` that are deliberately left out of the
+/// OpenAPI document.
+const UNDOCUMENTED_ROUTES: &[&str] = &["/test"];
+
+/// The paths registered by `app()`, in OpenAPI syntax (`:id` becomes `{id}`).
+fn registered_route_paths() -> std::collections::BTreeSet<String> {
+    rustquote_service::ROUTES
+        .iter()
+        .map(|route| route.path)
+        .chain(rustquote_service::UNVERSIONED_ROUTES.iter().copied())
+        .filter(|path| !UNDOCUMENTED_ROUTES.contains(path))
+        .map(|path| {
+            path.split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(name) => format!("{{{}}}", name),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_openapi_spec_matches_routes() {
+    let temp_file = create_temp_quotes_file(
+        r#"[{"id": 1, "quote": "Stay hungry.", "author": "Steve Jobs", "source": null}]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/openapi.json")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let paths = spec["paths"].as_object().unwrap();
+
+    // Every route is documented, and nothing else is.
+    let documented: std::collections::BTreeSet<String> = paths.keys().cloned().collect();
+    assert_eq!(
+        documented,
+        registered_route_paths(),
+        "routes and OpenAPI paths differ"
+    );
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+    for (path, item) in paths {
+        let uri = path.replace("{id}", "1");
+        for method in METHODS {
+            let response = router
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method.to_uppercase().as_str())
+                        .uri(&uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let deprecated = response.headers().contains_key("deprecation");
+            let Some(operation) = item.get(method) else {
+                assert_eq!(
+                    status,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "{} {} is served but not documented",
+                    method,
+                    path
+                );
+                continue;
+            };
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {} is documented but not served",
+                method,
+                path
+            );
+            // The router's own 404 has an empty body; handlers always explain
+            // theirs. Other bodies may be endless streams, so are not read.
+            let routed = status != StatusCode::NOT_FOUND
+                || !body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap()
+                    .is_empty();
+            assert!(routed, "{} {} is documented but not routed", method, path);
+            assert_eq!(
+                operation["deprecated"] == serde_json::Value::Bool(true),
+                deprecated,
+                "{} {} deprecation differs between spec and headers",
+                method,
+                path
+            );
+        }
+    }
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/api/docs")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let page = std::str::from_utf8(&body).unwrap();
+    assert!(page.contains(r#"data-spec-url="openapi.json""#));
+    assert!(!page.contains("<script src="), "the viewer is not embedded");
+}
+
+#[tokio::test]
+async fn test_corpus_stats() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Less is more.", "author": "Mies", "source": "Interview"},
+            {"id": 2, "quote": "Less is more.", "author": "Browning"},
+            {"id": 3, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs", "status": "archived"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let get_stats = || async {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/stats")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    };
+
+    let stats = get_stats().await;
+    assert_eq!(stats["corpus_version"], 1);
+    assert_eq!(stats["total_quotes"], 3);
+    assert_eq!(stats["by_status"]["archived"], 1);
+    assert_eq!(stats["authors"].as_array().unwrap().len(), 3);
+    assert_eq!(stats["missing_source"], 2);
+    assert_eq!(stats["length"]["min"], 13);
+    assert_eq!(stats["length"]["percentiles"]["p50"], 13);
+    assert_eq!(stats["duplicates"]["groups"], 1);
+    assert_eq!(stats["duplicates"]["ids"], serde_json::json!([[1, 2]]));
+    // Served from the same load until the file changes.
+    assert_eq!(get_stats().await["loaded_at"], stats["loaded_at"]);
+
+    std::fs::write(
+        temp_file.path(),
+        r#"[{"id": 1, "quote": "Less is more.", "author": "Mies", "source": "Interview"}]"#,
+    )
+    .unwrap();
+    let stats = get_stats().await;
+    assert_eq!(stats["corpus_version"], 2);
+    assert_eq!(stats["total_quotes"], 1);
+    assert_eq!(stats["missing_source"], 0);
+    assert_eq!(stats["duplicates"]["groups"], 0);
+}
+
+#[tokio::test]
+async fn test_near_duplicate_diagnostics() {
+    let quotes = r#"[
+        {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs"},
+        {"id": 2, "quote": "Less is more.", "author": "Mies"},
+        {"id": 3, "quote": "Stay hungry; stay FOOLISH!", "author": "Steve Jobs", "status": "archived"},
+        {"id": 4, "quote": "Simplicity is the ultimate sophistication.", "author": "Leonardo da Vinci"}
+    ]"#;
+    let temp_file = create_temp_quotes_file(quotes);
+    let admin_state = || {
+        AppState::new(temp_file.path().to_path_buf()).with_admin_token(Some("s3cret".to_string()))
+    };
+    let request = |token: Option<&str>| {
+        let mut builder = Request::builder().uri("/api/v1/diagnostics/duplicates");
+        if let Some(token) = token {
+            builder = builder.header("x-admin-token", token);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+    let get_duplicates =
+        |state: AppState| async move { app(state).oneshot(request(Some("s3cret"))).await.unwrap() };
+
+    // The report quotes every quote in full, so it is admin-only.
+    for (state, token) in [
+        (admin_state(), None),
+        (admin_state(), Some("wrong")),
+        (
+            AppState::new(temp_file.path().to_path_buf()),
+            Some("s3cret"),
+        ),
+    ] {
+        let response = app(state).oneshot(request(token)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    let response = get_duplicates(admin_state()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(report["total_quotes"], 4);
+    assert_eq!(report["duplicate_quotes"], 1);
+    assert_eq!(report["duplicate_rate"], 0.25);
+    let clusters = report["clusters"].as_array().unwrap();
+    assert_eq!(clusters.len(), 1);
+    let ids: Vec<&serde_json::Value> = clusters[0]["quotes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|q| &q["id"])
+        .collect();
+    assert_eq!(ids, [1, 3]);
+
+    // A file above the configured rate is refused.
+    let state = admin_state().with_max_duplicate_rate(Some(0.2));
+    let response = get_duplicates(state).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = body::to_bytes(response.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("near duplicates"));
+
+    let state = admin_state().with_max_duplicate_rate(Some(0.25));
+    assert_eq!(get_duplicates(state).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_suggest_authors_and_terms() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Stay hungry, stay foolish.", "author": "Steve Jobs"},
+            {"id": 2, "quote": "Simplicity is the ultimate sophistication.", "author": "Steve Jobs"},
+            {"id": 3, "quote": "The café never sleeps.", "author": "Émile Zola"},
+            {"id": 4, "quote": "Hidden stardust.", "author": "Stella Archived", "status": "archived"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()).with_suggest_max_results(5));
+    let get = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        }
+    };
+
+    let (status, value) = get("/api/v1/suggest?prefix=ST&kind=author").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(value["kind"], "author");
+    assert_eq!(
+        value["suggestions"],
+        serde_json::json!([{"text": "Steve Jobs", "quotes": 2}])
+    );
+
+    let (_, value) = get("/api/v1/suggest?prefix=emile&kind=author").await;
+    assert_eq!(value["suggestions"][0]["text"], "Émile Zola");
+
+    let (_, value) = get("/api/v1/suggest?prefix=s").await;
+    let terms: Vec<&str> = value["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(terms, ["simplicity", "sleeps", "sophistication", "stay"]);
+
+    let (_, value) = get("/api/v1/suggest?prefix=cafe&kind=term&limit=1").await;
+    assert_eq!(
+        value["suggestions"],
+        serde_json::json!([{"text": "café", "quotes": 1}])
+    );
+
+    for uri in [
+        "/api/v1/suggest",
+        "/api/v1/suggest?prefix=%20",
+        "/api/v1/suggest?prefix=st&kind=tag",
+        "/api/v1/suggest?prefix=st&limit=6",
+        "/api/v1/suggest?prefix=st&limit=0",
+    ] {
+        let (status, value) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(value["error_code"], "BAD_REQUEST");
+    }
+}
+
+#[tokio::test]
+async fn test_similar_quotes() {
+    let temp_file = create_temp_quotes_file(
+        r#"[
+            {"id": 1, "quote": "Love the work you do.", "author": "Confucius"},
+            {"id": 2, "quote": "Do what you love and love the work.", "author": "Steve Jobs"},
+            {"id": 3, "quote": "Real artists ship.", "author": "Steve Jobs"},
+            {"id": 4, "quote": "Work expands to fill the time available.", "author": "Parkinson"},
+            {"id": 5, "quote": "Work is love made visible.", "author": "Gibran", "status": "archived"},
+            {"id": 6, "quote": "Nothing else in common.", "author": "Nobody"}
+        ]"#,
+    );
+    let router = app(AppState::new(temp_file.path().to_path_buf()));
+    let get = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            )
+        }
+    };
+    let ids = |value: &serde_json::Value| -> Vec<u64> {
+        value["similar"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["quote"]["id"].as_u64().unwrap())
+            .collect()
+    };
+
+    let (status, value) = get("/api/v1/quote/1/similar").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(value["id"], 1);
+    // Archived and unrelated quotes are not recommended.
+    assert_eq!(ids(&value), [2, 4]);
+    let score = value["similar"][0]["score"].as_f64().unwrap();
+    assert!(score > 0.0 && score <= 1.0);
+
+    let (_, value) = get("/api/v1/quote/2/similar?limit=1").await;
+    assert_eq!(ids(&value), [1]);
+
+    let (_, value) = get("/api/v1/quote/2/similar?author_boost=1").await;
+    assert_eq!(ids(&value), [3, 1, 4]);
+
+    let (status, _) = get("/api/v1/quote/99/similar").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    for uri in [
+        "/api/v1/quote/1/similar?limit=0",
+        "/api/v1/quote/1/similar?limit=51",
+        "/api/v1/quote/1/similar?author_boost=2",
+    ] {
+        let (status, _) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}